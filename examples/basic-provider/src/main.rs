@@ -1,78 +1,76 @@
+use async_trait::async_trait;
 use iamctl_rust_sdk::prelude::*;
 use iamctl_rust_sdk::server::JsonRpcServer;
 use iamctl_rust_sdk::utils::init_logging;
-use async_trait::async_trait;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, IamResource)]
+#[iam(resource_type = "user", schema_version = 1)]
 struct UserSpec {
+    #[iam(force_new)]
     username: String,
     email: String,
     #[serde(default)]
     is_admin: bool,
 }
 
-struct ExampleProvider;
+/// Handles the 'user' resource type. A real provider would call the IAM API here.
+struct UserHandler;
 
 #[async_trait]
-impl Provider for ExampleProvider {
-    fn metadata(&self) -> ProviderMetadata {
-        ProviderMetadata {
-            name: "example-basic-provider".to_string(),
-            version: "0.1.0".to_string(),
-            author: Some("IAMCTL Team".to_string()),
-            repository: Some("https://github.com/iamctl/rust-sdk".to_string()),
-            description: Some("A basic example provider demonstrating the IAMCTL Rust SDK".to_string()),
-        }
+impl ResourceHandler for UserHandler {
+    type Spec = UserSpec;
+
+    async fn create(&self, address: &ResourceAddress, spec: UserSpec) -> anyhow::Result<UserSpec> {
+        eprintln!("creating {address}");
+        Ok(spec)
     }
 
-    fn capabilities(&self) -> ProviderCapabilities {
-        ProviderCapabilities {
-            supported_resources: vec!["user".to_string()],
-            can_import: true,
-            can_validate: true,
-        }
+    async fn read(
+        &self,
+        _address: &ResourceAddress,
+        spec: UserSpec,
+    ) -> anyhow::Result<Option<UserSpec>> {
+        Ok(Some(spec))
     }
 
-    async fn plan(&self, request: PlanRequest) -> anyhow::Result<PlanResponse> {
-        let mut changes = vec![];
-        for resource in request.resources {
-            changes.push(Change {
-                address: resource.address.clone(),
-                change_type: ChangeType::Create,
-                before: None,
-                after: Some(resource),
-                computed_fields: vec!["id".to_string()],
-            });
-        }
-        Ok(PlanResponse { changes })
+    async fn update(
+        &self,
+        address: &ResourceAddress,
+        _before: UserSpec,
+        after: UserSpec,
+    ) -> anyhow::Result<UserSpec> {
+        eprintln!("updating {address}");
+        Ok(after)
     }
 
-    async fn apply(&self, request: ApplyRequest) -> anyhow::Result<ApplyResponse> {
-        let mut successful_addresses = vec![];
-        for change in request.changes {
-            successful_addresses.push(change.address);
-        }
-        Ok(ApplyResponse {
-            successful_addresses,
-            failed_addresses: vec![],
-        })
+    async fn delete(&self, address: &ResourceAddress, _spec: UserSpec) -> anyhow::Result<()> {
+        eprintln!("deleting {address}");
+        Ok(())
     }
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     init_logging();
-    
-    let provider = ExampleProvider;
-    let mut server = JsonRpcServer::new(provider);
-    
-    // Register schema for the 'user' resource type
-    server.register_type_schema::<UserSpec>("user");
-    
+
+    // The builder derives plan/apply, the capability list and the 'user' schema
+    // (including the `#[iam(...)]` field attributes) from the registered handlers.
+    let provider = ProviderBuilder::new(ProviderMetadata {
+        name: "example-basic-provider".to_string(),
+        version: "0.1.0".to_string(),
+        author: Some("IAMCTL Team".to_string()),
+        repository: Some("https://github.com/iamctl/rust-sdk".to_string()),
+        description: Some("A basic example provider demonstrating the IAMCTL Rust SDK".to_string()),
+    })
+    .typed_resource(UserHandler)
+    .build();
+
+    let server = JsonRpcServer::new(provider);
+
     eprintln!("Starting example-basic-provider JSON-RPC server on stdin/stdout...");
     server.run().await?;
-    
+
     Ok(())
 }
@@ -22,17 +22,26 @@ impl Provider for MockProvider {
             supported_resources: vec!["realm".to_string(), "client".to_string()],
             can_import: true,
             can_validate: true,
+            can_refresh: false,
+            config_schema: None,
+            can_discover: false,
+            resource_types: vec![],
+            data_sources: vec![],
         }
     }
 
     async fn plan(&self, _request: PlanRequest) -> anyhow::Result<PlanResponse> {
-        Ok(PlanResponse { changes: vec![] })
+        Ok(PlanResponse {
+            changes: vec![],
+            diagnostics: vec![],
+        })
     }
 
     async fn apply(&self, _request: ApplyRequest) -> anyhow::Result<ApplyResponse> {
         Ok(ApplyResponse {
             successful_addresses: vec![],
-            failed_addresses: vec![],
+            diagnostics: vec![],
+            results: vec![],
         })
     }
 }
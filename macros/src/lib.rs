@@ -0,0 +1,69 @@
+//! Procedural macros for the iamctl Rust SDK. Use them through the SDK's `derive` feature
+//! rather than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, ItemImpl};
+
+mod provider;
+mod resource;
+
+/// Implements `iamctl_rust_sdk::provider::IamResource` for a spec struct.
+///
+/// ```ignore
+/// #[derive(Serialize, Deserialize, JsonSchema, IamResource)]
+/// #[iam(resource_type = "user", schema_version = 2)]
+/// struct UserSpec {
+///     #[iam(computed)]
+///     id: Option<String>,
+///     #[iam(force_new)]
+///     username: String,
+///     #[iam(sensitive)]
+///     password: String,
+/// }
+/// ```
+///
+/// Field names follow `#[serde(rename)]` and `#[serde(rename_all)]`, so they match the
+/// property names in the derived JSON schema.
+#[proc_macro_derive(IamResource, attributes(iam))]
+pub fn derive_iam_resource(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    resource::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Generates a provider from the handler methods of an inherent impl block.
+///
+/// Methods marked `#[iam(create)]`, `#[iam(read)]`, `#[iam(update)]` and
+/// `#[iam(delete)]` (plus an optional `#[iam(remote_id)]`) are grouped by their spec
+/// type, which must implement `IamResource`; every spec type needs all four CRUD
+/// methods. An optional `#[iam(configure)] fn(&self, Config) -> Result<()>` receives the
+/// provider configuration. The macro adds `into_provider()` and `run()` to the type.
+///
+/// ```ignore
+/// #[iamctl_rust_sdk::provider(name = "keycloak", version = env!("CARGO_PKG_VERSION"))]
+/// impl Keycloak {
+///     #[iam(create)]
+///     async fn create_user(&self, address: &ResourceAddress, spec: UserSpec) -> anyhow::Result<UserSpec> { .. }
+///     // read, update, delete ...
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     Keycloak::new().run().await?;
+///     Ok(())
+/// }
+/// ```
+///
+/// `version` defaults to `env!("CARGO_PKG_VERSION")`; `author`, `repository` and
+/// `description` are optional.
+#[proc_macro_attribute]
+pub fn provider(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut parsed = provider::ProviderArgs::default();
+    let parser = syn::meta::parser(|meta| parsed.parse(meta));
+    parse_macro_input!(args with parser);
+    let item = parse_macro_input!(input as ItemImpl);
+    provider::expand(parsed, item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
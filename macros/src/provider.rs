@@ -0,0 +1,411 @@
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use std::collections::BTreeMap;
+use syn::spanned::Spanned;
+use syn::{Expr, FnArg, ImplItem, ImplItemFn, ItemImpl, ReturnType, Type};
+
+const CRUD: [&str; 4] = ["create", "read", "update", "delete"];
+
+/// Arguments of `#[provider(...)]`.
+#[derive(Default)]
+pub(crate) struct ProviderArgs {
+    name: Option<Expr>,
+    version: Option<Expr>,
+    author: Option<Expr>,
+    repository: Option<Expr>,
+    description: Option<Expr>,
+}
+
+impl ProviderArgs {
+    pub(crate) fn parse(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        let slot = if meta.path.is_ident("name") {
+            &mut self.name
+        } else if meta.path.is_ident("version") {
+            &mut self.version
+        } else if meta.path.is_ident("author") {
+            &mut self.author
+        } else if meta.path.is_ident("repository") {
+            &mut self.repository
+        } else if meta.path.is_ident("description") {
+            &mut self.description
+        } else {
+            return Err(
+                meta.error("expected `name`, `version`, `author`, `repository` or `description`")
+            );
+        };
+        *slot = Some(meta.value()?.parse()?);
+        Ok(())
+    }
+}
+
+/// Handler methods registered for one spec type.
+struct SpecHandlers {
+    spec: Type,
+    /// Method name and the span of its return type, per operation.
+    methods: BTreeMap<&'static str, (syn::Ident, Span)>,
+}
+
+pub(crate) fn expand(args: ProviderArgs, mut item: ItemImpl) -> syn::Result<TokenStream2> {
+    if item.trait_.is_some() {
+        return Err(syn::Error::new_spanned(
+            &item.self_ty,
+            "#[provider] must be placed on an inherent impl block",
+        ));
+    }
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.generics,
+            "#[provider] does not support generic providers",
+        ));
+    }
+    let name = args.name.ok_or_else(|| {
+        syn::Error::new(Span::call_site(), "missing `name = \"...\"` in #[provider]")
+    })?;
+    let version = args
+        .version
+        .map(ToTokens::into_token_stream)
+        .unwrap_or_else(|| quote!(env!("CARGO_PKG_VERSION")));
+    let optional = |expr: Option<Expr>| match expr {
+        Some(expr) => {
+            quote!(::std::option::Option::Some(::std::string::ToString::to_string(&#expr)))
+        }
+        None => quote!(::std::option::Option::None),
+    };
+    let author = optional(args.author);
+    let repository = optional(args.repository);
+    let description = optional(args.description);
+
+    let mut specs: Vec<SpecHandlers> = vec![];
+    let mut configure: Option<(syn::Ident, Type)> = None;
+    for impl_item in &mut item.items {
+        let ImplItem::Fn(method) = impl_item else {
+            continue;
+        };
+        let Some(kind) = take_kind(method)? else {
+            continue;
+        };
+        if kind == "configure" {
+            let config = check_configure(method)?;
+            if configure.is_some() {
+                return Err(syn::Error::new_spanned(
+                    &method.sig.ident,
+                    "duplicate #[iam(configure)] method",
+                ));
+            }
+            configure = Some((method.sig.ident.clone(), config));
+            continue;
+        }
+
+        let spec = check_handler(kind, method)?;
+        let key = spec.to_token_stream().to_string();
+        let index = match specs
+            .iter()
+            .position(|s| s.spec.to_token_stream().to_string() == key)
+        {
+            Some(index) => index,
+            None => {
+                specs.push(SpecHandlers {
+                    spec,
+                    methods: BTreeMap::new(),
+                });
+                specs.len() - 1
+            }
+        };
+        if specs[index]
+            .methods
+            .insert(kind, (method.sig.ident.clone(), return_type_span(method)))
+            .is_some()
+        {
+            return Err(syn::Error::new_spanned(
+                &method.sig.ident,
+                format!("duplicate #[iam({kind})] method for `{key}`"),
+            ));
+        }
+    }
+
+    let self_ty = &item.self_ty;
+    let self_name = match self_ty.as_ref() {
+        Type::Path(path) => path.path.segments.last().map(|s| s.ident.clone()),
+        _ => None,
+    }
+    .ok_or_else(|| syn::Error::new_spanned(self_ty, "#[provider] expects a named type"))?;
+
+    let mut handlers = vec![];
+    let mut registrations = vec![];
+    for handlers_for in &specs {
+        let spec = &handlers_for.spec;
+        let missing: Vec<_> = CRUD
+            .iter()
+            .filter(|kind| !handlers_for.methods.contains_key(*kind))
+            .map(|kind| format!("#[iam({kind})]"))
+            .collect();
+        if !missing.is_empty() {
+            return Err(syn::Error::new_spanned(
+                spec,
+                format!(
+                    "missing {} method(s) for `{}`",
+                    missing.join(", "),
+                    spec.to_token_stream()
+                ),
+            ));
+        }
+
+        let spec_name = match spec {
+            Type::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+            _ => None,
+        }
+        .unwrap_or_default();
+        let handler = format_ident!("__{}{}Handler", self_name, spec_name);
+        // Delegating calls carry the span of the method's return type, so type errors
+        // point at the user's signature rather than the attribute.
+        let call = |kind: &str, args: TokenStream2| {
+            let (method, span) = &handlers_for.methods[kind];
+            quote_spanned!(*span=> self.0.#method(#args).await.map_err(::std::convert::Into::into))
+        };
+        let create = call("create", quote!(address, spec));
+        let read = call("read", quote!(address, spec));
+        let update = call("update", quote!(address, before, after));
+        let delete = call("delete", quote!(address, spec));
+        let remote_id = handlers_for.methods.get("remote_id").map(|(method, span)| {
+            let call = quote_spanned!(*span=> self.0.#method(spec));
+            quote! {
+                fn remote_id(&self, spec: &#spec) -> ::std::option::Option<::std::string::String> {
+                    #call
+                }
+            }
+        });
+
+        handlers.push(quote! {
+            struct #handler(::std::sync::Arc<#self_ty>);
+
+            #[::iamctl_rust_sdk::__private::async_trait]
+            impl ::iamctl_rust_sdk::provider::ResourceHandler for #handler {
+                type Spec = #spec;
+
+                async fn create(
+                    &self,
+                    address: &::iamctl_rust_sdk::types::ResourceAddress,
+                    spec: #spec,
+                ) -> ::iamctl_rust_sdk::__private::anyhow::Result<#spec> {
+                    #create
+                }
+
+                async fn read(
+                    &self,
+                    address: &::iamctl_rust_sdk::types::ResourceAddress,
+                    spec: #spec,
+                ) -> ::iamctl_rust_sdk::__private::anyhow::Result<::std::option::Option<#spec>> {
+                    #read
+                }
+
+                async fn update(
+                    &self,
+                    address: &::iamctl_rust_sdk::types::ResourceAddress,
+                    before: #spec,
+                    after: #spec,
+                ) -> ::iamctl_rust_sdk::__private::anyhow::Result<#spec> {
+                    #update
+                }
+
+                async fn delete(
+                    &self,
+                    address: &::iamctl_rust_sdk::types::ResourceAddress,
+                    spec: #spec,
+                ) -> ::iamctl_rust_sdk::__private::anyhow::Result<()> {
+                    #delete
+                }
+
+                #remote_id
+            }
+        });
+        registrations.push(quote! {
+            .typed_resource(#handler(::std::sync::Arc::clone(&this)))
+        });
+    }
+
+    let configure = configure.map(|(method, config)| {
+        quote! {
+            .configure({
+                let this = ::std::sync::Arc::clone(&this);
+                move |config: #config| -> ::iamctl_rust_sdk::__private::anyhow::Result<()> {
+                    this.#method(config).map_err(::std::convert::Into::into)
+                }
+            })
+        }
+    });
+
+    Ok(quote! {
+        #item
+
+        // Errors are converted with `Into` so handlers may return any error type.
+        #[allow(clippy::useless_conversion)]
+        const _: () = {
+            #(#handlers)*
+
+            impl #self_ty {
+                /// Builds the provider from the handler methods of this impl block.
+                pub fn into_provider(self) -> ::iamctl_rust_sdk::provider::HandlerProvider {
+                    let this = ::std::sync::Arc::new(self);
+                    ::iamctl_rust_sdk::provider::ProviderBuilder::new(
+                        ::iamctl_rust_sdk::provider::ProviderMetadata {
+                            name: ::std::string::ToString::to_string(&#name),
+                            version: ::std::string::ToString::to_string(&#version),
+                            author: #author,
+                            repository: #repository,
+                            description: #description,
+                        },
+                    )
+                    #configure
+                    #(#registrations)*
+                    .build()
+                }
+
+                /// Serves the provider over stdin/stdout until the engine closes the input.
+                pub async fn run(self) -> ::iamctl_rust_sdk::utils::Result<()> {
+                    ::iamctl_rust_sdk::server::JsonRpcServer::new(self.into_provider())
+                        .run()
+                        .await
+                }
+            }
+        };
+    })
+}
+
+/// Removes the `#[iam(...)]` attribute of a method and returns the handler kind.
+fn take_kind(method: &mut ImplItemFn) -> syn::Result<Option<&'static str>> {
+    let Some(position) = method.attrs.iter().position(|a| a.path().is_ident("iam")) else {
+        return Ok(None);
+    };
+    let attr = method.attrs.remove(position);
+    let mut kind = None;
+    attr.parse_nested_meta(|meta| {
+        let found = [
+            "create",
+            "read",
+            "update",
+            "delete",
+            "remote_id",
+            "configure",
+        ]
+        .into_iter()
+        .find(|k| meta.path.is_ident(k))
+        .ok_or_else(|| {
+            meta.error("expected `create`, `read`, `update`, `delete`, `remote_id` or `configure`")
+        })?;
+        if kind.replace(found).is_some() {
+            return Err(meta.error("a method handles a single operation"));
+        }
+        Ok(())
+    })?;
+    kind.map(Some)
+        .ok_or_else(|| syn::Error::new_spanned(attr, "expected a handler operation"))
+}
+
+/// Checks the signature of a CRUD or `remote_id` method and returns its spec type.
+fn check_handler(kind: &'static str, method: &ImplItemFn) -> syn::Result<Type> {
+    let sig = &method.sig;
+    let expected = match kind {
+        "create" => "async fn(&self, &ResourceAddress, Spec) -> Result<Spec>",
+        "read" => "async fn(&self, &ResourceAddress, Spec) -> Result<Option<Spec>>",
+        "update" => "async fn(&self, &ResourceAddress, Spec, Spec) -> Result<Spec>",
+        "delete" => "async fn(&self, &ResourceAddress, Spec) -> Result<()>",
+        _ => "fn(&self, &Spec) -> Option<String>",
+    };
+    let mismatch = |span: Span| {
+        syn::Error::new(
+            span,
+            format!("#[iam({kind})] methods must have the signature `{expected}`"),
+        )
+    };
+
+    let is_async = kind != "remote_id";
+    if sig.asyncness.is_some() != is_async {
+        return Err(mismatch(sig.fn_token.span()));
+    }
+    if !matches!(sig.output, ReturnType::Type(..)) {
+        return Err(mismatch(sig.span()));
+    }
+    let args = typed_args(method).map_err(|_| mismatch(sig.span()))?;
+
+    if kind == "remote_id" {
+        let [spec] = args.as_slice() else {
+            return Err(mismatch(sig.inputs.span()));
+        };
+        return match spec {
+            Type::Reference(reference) if reference.mutability.is_none() => {
+                Ok((*reference.elem).clone())
+            }
+            other => Err(mismatch(other.span())),
+        };
+    }
+
+    let arity = if kind == "update" { 3 } else { 2 };
+    if args.len() != arity {
+        return Err(mismatch(sig.inputs.span()));
+    }
+    match args[0] {
+        Type::Reference(reference)
+            if reference.mutability.is_none()
+                && matches!(reference.elem.as_ref(), Type::Path(path)
+                    if path.path.segments.last().is_some_and(|s| s.ident == "ResourceAddress")) => {
+        }
+        other => return Err(mismatch(other.span())),
+    }
+    let spec = args[1];
+    if matches!(spec, Type::Reference(_)) {
+        return Err(mismatch(spec.span()));
+    }
+    if kind == "update"
+        && args[2].to_token_stream().to_string() != spec.to_token_stream().to_string()
+    {
+        return Err(mismatch(args[2].span()));
+    }
+    Ok(spec.clone())
+}
+
+/// Checks `fn(&self, Config) -> Result<()>` and returns the config type.
+fn check_configure(method: &ImplItemFn) -> syn::Result<Type> {
+    let sig = &method.sig;
+    let mismatch = |span: Span| {
+        syn::Error::new(
+            span,
+            "#[iam(configure)] methods must have the signature `fn(&self, Config) -> Result<()>`",
+        )
+    };
+    if sig.asyncness.is_some() {
+        return Err(mismatch(sig.asyncness.span()));
+    }
+    if !matches!(sig.output, ReturnType::Type(..)) {
+        return Err(mismatch(sig.span()));
+    }
+    match typed_args(method)
+        .map_err(|_| mismatch(sig.span()))?
+        .as_slice()
+    {
+        [config] if !matches!(config, Type::Reference(_)) => Ok((*config).clone()),
+        _ => Err(mismatch(sig.inputs.span())),
+    }
+}
+
+/// The argument types after a `&self` receiver.
+fn typed_args(method: &ImplItemFn) -> Result<Vec<&Type>, ()> {
+    let mut inputs = method.sig.inputs.iter();
+    match inputs.next() {
+        Some(FnArg::Receiver(receiver))
+            if receiver.reference.is_some() && receiver.mutability.is_none() => {}
+        _ => return Err(()),
+    }
+    inputs
+        .map(|arg| match arg {
+            FnArg::Typed(typed) => Ok(typed.ty.as_ref()),
+            FnArg::Receiver(_) => Err(()),
+        })
+        .collect()
+}
+
+fn return_type_span(method: &ImplItemFn) -> Span {
+    match &method.sig.output {
+        ReturnType::Type(_, ty) => ty.span(),
+        ReturnType::Default => method.sig.span(),
+    }
+}
@@ -0,0 +1,217 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::meta::ParseNestedMeta;
+use syn::{Attribute, Data, DeriveInput, Fields, LitInt, LitStr};
+
+pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "IamResource can only be derived for structs with named fields",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "IamResource can only be derived for structs with named fields",
+        ));
+    };
+
+    let container = ContainerAttrs::parse(&input.attrs)?;
+    let resource_type = container.resource_type.ok_or_else(|| {
+        syn::Error::new_spanned(
+            &input.ident,
+            "missing #[iam(resource_type = \"...\")] on the struct",
+        )
+    })?;
+    let schema_version = container.schema_version;
+
+    let mut computed = vec![];
+    let mut sensitive = vec![];
+    let mut force_new = vec![];
+    for field in &fields.named {
+        let flags = FieldAttrs::parse(&field.attrs)?;
+        let name = match flags.rename {
+            Some(name) => name,
+            None => {
+                let ident = field.ident.as_ref().expect("named field").to_string();
+                let ident = ident.strip_prefix("r#").unwrap_or(&ident).to_string();
+                match &container.rename_all {
+                    Some(rule) => rule.apply(&ident),
+                    None => ident,
+                }
+            }
+        };
+        if flags.computed {
+            computed.push(name.clone());
+        }
+        if flags.sensitive {
+            sensitive.push(name.clone());
+        }
+        if flags.force_new {
+            force_new.push(name);
+        }
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::iamctl_rust_sdk::provider::IamResource for #ident #ty_generics
+            #where_clause
+        {
+            const RESOURCE_TYPE: &'static str = #resource_type;
+            const SCHEMA_VERSION: u32 = #schema_version;
+            const COMPUTED_FIELDS: &'static [&'static str] = &[#(#computed),*];
+            const SENSITIVE_FIELDS: &'static [&'static str] = &[#(#sensitive),*];
+            const FORCE_NEW_FIELDS: &'static [&'static str] = &[#(#force_new),*];
+        }
+    })
+}
+
+#[derive(Default)]
+struct ContainerAttrs {
+    resource_type: Option<String>,
+    schema_version: u32,
+    rename_all: Option<RenameRule>,
+}
+
+impl ContainerAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+        for attr in attrs {
+            if attr.path().is_ident("iam") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("resource_type") {
+                        let value: LitStr = meta.value()?.parse()?;
+                        if value.value().is_empty() {
+                            return Err(meta.error("resource_type must not be empty"));
+                        }
+                        parsed.resource_type = Some(value.value());
+                        Ok(())
+                    } else if meta.path.is_ident("schema_version") {
+                        let value: LitInt = meta.value()?.parse()?;
+                        parsed.schema_version = value.base10_parse()?;
+                        Ok(())
+                    } else {
+                        Err(meta.error("expected `resource_type` or `schema_version`"))
+                    }
+                })?;
+            } else if attr.path().is_ident("serde") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename_all") {
+                        let value: LitStr = meta.value()?.parse()?;
+                        parsed.rename_all = Some(RenameRule::parse(&value)?);
+                        Ok(())
+                    } else {
+                        skip_meta(meta)
+                    }
+                })?;
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    computed: bool,
+    sensitive: bool,
+    force_new: bool,
+    rename: Option<String>,
+}
+
+impl FieldAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+        for attr in attrs {
+            if attr.path().is_ident("iam") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("computed") {
+                        parsed.computed = true;
+                    } else if meta.path.is_ident("sensitive") {
+                        parsed.sensitive = true;
+                    } else if meta.path.is_ident("force_new") {
+                        parsed.force_new = true;
+                    } else {
+                        return Err(meta.error("expected `computed`, `sensitive` or `force_new`"));
+                    }
+                    Ok(())
+                })?;
+            } else if attr.path().is_ident("serde") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") && meta.input.peek(syn::Token![=]) {
+                        let value: LitStr = meta.value()?.parse()?;
+                        parsed.rename = Some(value.value());
+                        Ok(())
+                    } else {
+                        skip_meta(meta)
+                    }
+                })?;
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// Consumes a `#[serde(...)]` entry this macro does not interpret.
+fn skip_meta(meta: ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        let _: syn::Expr = meta.value()?.parse()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(skip_meta)?;
+    }
+    Ok(())
+}
+
+/// The subset of serde's `rename_all` rules that apply to struct fields.
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn parse(value: &LitStr) -> syn::Result<Self> {
+        Ok(match value.value().as_str() {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => return Err(syn::Error::new_spanned(value, "unknown rename_all rule")),
+        })
+    }
+
+    fn apply(&self, field: &str) -> String {
+        let capitalize = |word: &str| -> String {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        };
+        match self {
+            Self::Lower | Self::Snake => field.to_string(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal => field.split('_').map(capitalize).collect(),
+            Self::Camel => {
+                let pascal: String = field.split('_').map(capitalize).collect();
+                let mut chars = pascal.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_lowercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => field.replace('_', "-").to_ascii_uppercase(),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use async_trait::async_trait;
+use iamctl_rust_sdk::plan::{computed_attributes, force_new_attributes, Planner};
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::utils::redact::{redact, sensitive_attributes, REDACTED};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, IamResource)]
+#[iam(resource_type = "user", schema_version = 2)]
+struct UserSpec {
+    #[iam(computed)]
+    #[serde(default)]
+    id: Option<String>,
+    #[iam(force_new)]
+    username: String,
+    #[iam(sensitive)]
+    password: String,
+    #[serde(default)]
+    email: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, IamResource)]
+#[iam(resource_type = "client")]
+#[serde(rename_all = "camelCase")]
+struct ClientSpec {
+    #[iam(force_new)]
+    client_id: String,
+    #[iam(sensitive)]
+    #[serde(rename = "secret", default)]
+    client_secret: Option<String>,
+    #[iam(computed)]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    created_at: Option<String>,
+}
+
+fn user(spec: serde_json::Value) -> Resource {
+    Resource {
+        address: ResourceAddress {
+            resource_type: "user".to_string(),
+            name: "alice".to_string(),
+            namespace: None,
+        },
+        spec: serde_json::from_value(spec).unwrap(),
+        metadata: HashMap::new(),
+    }
+}
+
+#[test]
+fn test_derive_declares_type_version_and_fields() {
+    assert_eq!(UserSpec::RESOURCE_TYPE, "user");
+    assert_eq!(UserSpec::SCHEMA_VERSION, 2);
+    assert_eq!(UserSpec::COMPUTED_FIELDS, ["id"]);
+    assert_eq!(UserSpec::SENSITIVE_FIELDS, ["password"]);
+    assert_eq!(UserSpec::FORCE_NEW_FIELDS, ["username"]);
+
+    assert_eq!(ClientSpec::RESOURCE_TYPE, "client");
+    assert_eq!(ClientSpec::SCHEMA_VERSION, 0);
+}
+
+#[test]
+fn test_field_names_follow_serde_renames() {
+    assert_eq!(ClientSpec::FORCE_NEW_FIELDS, ["clientId"]);
+    assert_eq!(ClientSpec::SENSITIVE_FIELDS, ["secret"]);
+    assert_eq!(ClientSpec::COMPUTED_FIELDS, ["createdAt"]);
+
+    let schema = ClientSpec::resource_schema();
+    assert_eq!(force_new_attributes(&schema), vec!["/clientId"]);
+    assert_eq!(sensitive_attributes(&schema), vec!["/secret"]);
+    assert_eq!(computed_attributes(&schema), vec!["/createdAt"]);
+}
+
+#[test]
+fn test_resource_schema_is_consumed_by_planner_and_redaction() {
+    let schema = UserSpec::resource_schema();
+    assert_eq!(schema["properties"]["id"]["readOnly"], true);
+
+    let planner = Planner::new().schema(UserSpec::RESOURCE_TYPE, &schema);
+    let current = vec![user(
+        json!({ "id": "42", "username": "alice", "password": "hunter2" }),
+    )];
+    let renamed = vec![user(
+        json!({ "id": "42", "username": "alice2", "password": "hunter2" }),
+    )];
+    let changes = planner.plan(&renamed, &current);
+    assert_eq!(changes[0].change_type, ChangeType::Replace);
+    assert_eq!(changes[0].computed_fields, vec!["/id"]);
+
+    let redacted = redact(&current[0], &sensitive_attributes(&schema));
+    assert_eq!(redacted.spec["password"], REDACTED);
+    assert_eq!(redacted.spec["username"], "alice");
+}
+
+#[test]
+fn test_validator_uses_resource_schema() {
+    let mut validator = iamctl_rust_sdk::validation::JsonSchemaValidator::new();
+    validator.add_resource_schema::<UserSpec>();
+
+    let result = validator
+        .validate(&user(json!({ "username": "alice" })))
+        .unwrap();
+    assert!(!result.valid);
+    assert!(
+        validator
+            .validate(&user(json!({ "username": "alice", "password": "x" })))
+            .unwrap()
+            .valid
+    );
+}
+
+struct UserHandler;
+
+#[async_trait]
+impl ResourceHandler for UserHandler {
+    type Spec = UserSpec;
+
+    async fn create(&self, _address: &ResourceAddress, spec: UserSpec) -> anyhow::Result<UserSpec> {
+        Ok(UserSpec {
+            id: Some("42".to_string()),
+            ..spec
+        })
+    }
+
+    async fn read(
+        &self,
+        _address: &ResourceAddress,
+        spec: UserSpec,
+    ) -> anyhow::Result<Option<UserSpec>> {
+        Ok(Some(spec))
+    }
+
+    async fn update(
+        &self,
+        _address: &ResourceAddress,
+        _before: UserSpec,
+        after: UserSpec,
+    ) -> anyhow::Result<UserSpec> {
+        Ok(after)
+    }
+
+    async fn delete(&self, _address: &ResourceAddress, _spec: UserSpec) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_typed_resource_registration() {
+    let provider = ProviderBuilder::new(ProviderMetadata {
+        name: "users".to_string(),
+        version: "0.1.0".to_string(),
+        author: None,
+        repository: None,
+        description: None,
+    })
+    .typed_resource(UserHandler)
+    .build();
+
+    let capabilities = provider.capabilities();
+    assert_eq!(capabilities.supported_resources, vec!["user"]);
+    assert_eq!(capabilities.descriptor("user").unwrap().schema_version, 2);
+    assert_eq!(
+        sensitive_attributes(&provider.schemas()["user"]),
+        vec!["/password"]
+    );
+
+    let plan = provider
+        .plan(PlanRequest {
+            workspace_path: ".".to_string(),
+            desired_state: vec![user(json!({ "username": "alice", "password": "x" }))],
+            current_state: vec![],
+            data_sources: vec![],
+        })
+        .await
+        .unwrap();
+    assert_eq!(plan.changes[0].computed_fields, vec!["/id"]);
+}
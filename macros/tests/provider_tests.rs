@@ -0,0 +1,216 @@
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::provider::{ConfigureRequest, HandlerProvider};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, IamResource)]
+#[iam(resource_type = "user")]
+struct UserSpec {
+    #[iam(computed)]
+    #[serde(default)]
+    id: Option<String>,
+    username: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, IamResource)]
+#[iam(resource_type = "group", schema_version = 3)]
+struct GroupSpec {
+    name: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct Config {
+    endpoint: String,
+}
+
+#[derive(Default)]
+struct Directory {
+    endpoint: Mutex<Option<String>>,
+    deleted: Mutex<Vec<String>>,
+}
+
+#[iamctl_rust_sdk::provider(
+    name = "directory",
+    version = "1.2.3",
+    description = "In-memory directory"
+)]
+impl Directory {
+    #[iam(configure)]
+    fn configure(&self, config: Config) -> anyhow::Result<()> {
+        *self.endpoint.lock().unwrap() = Some(config.endpoint);
+        Ok(())
+    }
+
+    #[iam(create)]
+    async fn create_user(
+        &self,
+        address: &ResourceAddress,
+        spec: UserSpec,
+    ) -> anyhow::Result<UserSpec> {
+        Ok(UserSpec {
+            id: Some(format!("id-{}", address.name)),
+            ..spec
+        })
+    }
+
+    #[iam(read)]
+    async fn read_user(
+        &self,
+        _address: &ResourceAddress,
+        spec: UserSpec,
+    ) -> anyhow::Result<Option<UserSpec>> {
+        Ok(Some(spec))
+    }
+
+    #[iam(update)]
+    async fn update_user(
+        &self,
+        _address: &ResourceAddress,
+        _before: UserSpec,
+        after: UserSpec,
+    ) -> anyhow::Result<UserSpec> {
+        Ok(after)
+    }
+
+    #[iam(delete)]
+    async fn delete_user(&self, address: &ResourceAddress, _spec: UserSpec) -> anyhow::Result<()> {
+        self.deleted.lock().unwrap().push(address.to_string());
+        Ok(())
+    }
+
+    #[iam(remote_id)]
+    fn user_id(&self, spec: &UserSpec) -> Option<String> {
+        spec.id.clone()
+    }
+
+    #[iam(create)]
+    async fn create_group(
+        &self,
+        _address: &ResourceAddress,
+        spec: GroupSpec,
+    ) -> anyhow::Result<GroupSpec> {
+        Ok(spec)
+    }
+
+    #[iam(read)]
+    async fn read_group(
+        &self,
+        _address: &ResourceAddress,
+        _spec: GroupSpec,
+    ) -> anyhow::Result<Option<GroupSpec>> {
+        Ok(None)
+    }
+
+    #[iam(update)]
+    async fn update_group(
+        &self,
+        _address: &ResourceAddress,
+        _before: GroupSpec,
+        _after: GroupSpec,
+    ) -> anyhow::Result<GroupSpec> {
+        anyhow::bail!("groups are immutable")
+    }
+
+    #[iam(delete)]
+    async fn delete_group(
+        &self,
+        _address: &ResourceAddress,
+        _spec: GroupSpec,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Methods without `#[iam]` are left alone.
+    fn endpoint(&self) -> Option<String> {
+        self.endpoint.lock().unwrap().clone()
+    }
+}
+
+fn resource(resource_type: &str, name: &str, spec: serde_json::Value) -> Resource {
+    Resource {
+        address: ResourceAddress {
+            resource_type: resource_type.to_string(),
+            name: name.to_string(),
+            namespace: None,
+        },
+        spec: serde_json::from_value(spec).unwrap(),
+        metadata: HashMap::new(),
+    }
+}
+
+fn provider() -> HandlerProvider {
+    Directory::default().into_provider()
+}
+
+#[test]
+fn test_generated_metadata_and_capabilities() {
+    let provider = provider();
+    let metadata = provider.metadata();
+    assert_eq!(metadata.name, "directory");
+    assert_eq!(metadata.version, "1.2.3");
+    assert_eq!(metadata.description.as_deref(), Some("In-memory directory"));
+    assert!(metadata.author.is_none());
+
+    let capabilities = provider.capabilities();
+    assert_eq!(capabilities.supported_resources, vec!["group", "user"]);
+    assert_eq!(capabilities.descriptor("group").unwrap().schema_version, 3);
+    assert!(capabilities.config_schema.is_some());
+    assert!(provider.schemas().contains_key("user"));
+}
+
+#[tokio::test]
+async fn test_generated_handlers_dispatch_to_methods() {
+    let provider = provider();
+    let _ = provider
+        .configure(ConfigureRequest {
+            config: json!({ "endpoint": "https://directory.local" }),
+        })
+        .await
+        .unwrap();
+
+    let desired = vec![resource("user", "alice", json!({ "username": "alice" }))];
+    let plan = provider
+        .plan(PlanRequest {
+            workspace_path: ".".to_string(),
+            desired_state: desired,
+            current_state: vec![resource("group", "admins", json!({ "name": "admins" }))],
+            data_sources: vec![],
+        })
+        .await
+        .unwrap();
+    assert_eq!(plan.changes[0].computed_fields, vec!["/id"]);
+
+    let response = provider
+        .apply(ApplyRequest {
+            changes: plan.changes,
+        })
+        .await
+        .unwrap();
+    assert_eq!(response.successful_addresses.len(), 2);
+    assert_eq!(response.results[0].remote_id.as_deref(), Some("id-alice"));
+    assert_eq!(
+        response.results[0].resource.as_ref().unwrap().spec["id"],
+        "id-alice"
+    );
+}
+
+#[test]
+fn test_methods_stay_callable() {
+    let directory = Directory::default();
+    assert!(directory.endpoint().is_none());
+    directory
+        .configure(Config {
+            endpoint: "ldap://directory".to_string(),
+        })
+        .unwrap();
+    assert_eq!(directory.endpoint().as_deref(), Some("ldap://directory"));
+}
+
+#[test]
+fn test_signature_errors() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,21 @@
+use iamctl_rust_sdk::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, JsonSchema, IamResource)]
+#[iam(resource_type = "user")]
+struct UserSpec {
+    username: String,
+}
+
+struct Directory;
+
+#[iamctl_rust_sdk::provider(name = "directory")]
+impl Directory {
+    #[iam(create)]
+    fn create_user(&self, _address: &ResourceAddress, spec: UserSpec) -> anyhow::Result<UserSpec> {
+        Ok(spec)
+    }
+}
+
+fn main() {}
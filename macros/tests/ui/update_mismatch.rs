@@ -0,0 +1,26 @@
+use iamctl_rust_sdk::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, JsonSchema, IamResource)]
+#[iam(resource_type = "user")]
+struct UserSpec {
+    username: String,
+}
+
+struct Directory;
+
+#[iamctl_rust_sdk::provider(name = "directory")]
+impl Directory {
+    #[iam(update)]
+    async fn update_user(
+        &self,
+        _address: &ResourceAddress,
+        _before: UserSpec,
+        after: String,
+    ) -> anyhow::Result<UserSpec> {
+        Ok(UserSpec { username: after })
+    }
+}
+
+fn main() {}
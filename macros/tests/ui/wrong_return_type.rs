@@ -0,0 +1,41 @@
+use iamctl_rust_sdk::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, JsonSchema, IamResource)]
+#[iam(resource_type = "user")]
+struct UserSpec {
+    username: String,
+}
+
+struct Directory;
+
+#[iamctl_rust_sdk::provider(name = "directory")]
+impl Directory {
+    #[iam(create)]
+    async fn create_user(&self, _address: &ResourceAddress, spec: UserSpec) -> anyhow::Result<UserSpec> {
+        Ok(spec)
+    }
+
+    #[iam(read)]
+    async fn read_user(&self, _address: &ResourceAddress, spec: UserSpec) -> anyhow::Result<UserSpec> {
+        Ok(spec)
+    }
+
+    #[iam(update)]
+    async fn update_user(
+        &self,
+        _address: &ResourceAddress,
+        _before: UserSpec,
+        after: UserSpec,
+    ) -> anyhow::Result<UserSpec> {
+        Ok(after)
+    }
+
+    #[iam(delete)]
+    async fn delete_user(&self, _address: &ResourceAddress, _spec: UserSpec) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+fn main() {}
@@ -0,0 +1,243 @@
+use super::ResourceDocument;
+use crate::provider::suggest_name;
+use crate::types::patch::tokens;
+use crate::types::Resource;
+use crate::utils::schema::resolve_ref;
+use crate::utils::{Error, Result};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Output format of generated configuration files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Yaml => "yaml",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// How resources are split across generated files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// One file per resource type (`user.yaml`, `client.yaml`, ...).
+    ResourceType,
+    /// One file per namespace; resources without a namespace go to `default`.
+    Namespace,
+}
+
+/// A rendered configuration file, relative to the workspace directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedFile {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+/// Renders imported or discovered resources into canonical workspace configuration.
+///
+/// Computed fields and values equal to their schema default are stripped, so the files
+/// only contain values the user controls, and `${` in strings is escaped as `$${`, so
+/// imported values such as `${role_admin}` are not read back as references.
+/// A property is computed if it is listed via [`ConfigGenerator::computed_fields`] or
+/// marked `readOnly` in the schema (schemars does this for `skip_deserializing` fields).
+pub struct ConfigGenerator {
+    format: ConfigFormat,
+    group_by: GroupBy,
+    schemas: HashMap<String, Value>,
+    computed_fields: HashMap<String, Vec<String>>,
+}
+
+impl ConfigGenerator {
+    pub fn new(format: ConfigFormat, group_by: GroupBy) -> Self {
+        Self {
+            format,
+            group_by,
+            schemas: HashMap::new(),
+            computed_fields: HashMap::new(),
+        }
+    }
+
+    /// Registers the JSON schema used to find defaults and read-only fields.
+    pub fn schema(mut self, resource_type: &str, schema: Value) -> Self {
+        let _ = self.schemas.insert(resource_type.to_string(), schema);
+        self
+    }
+
+    /// Marks fields (JSON Pointers, e.g. `/id` or `/credentials/secret_id`) as computed.
+    pub fn computed_fields(mut self, resource_type: &str, fields: &[&str]) -> Self {
+        self.computed_fields
+            .entry(resource_type.to_string())
+            .or_default()
+            .extend(fields.iter().map(|f| f.to_string()));
+        self
+    }
+
+    /// Returns the resource with computed fields and schema defaults removed and `${`
+    /// escaped in its strings.
+    pub fn canonicalize(&self, resource: &Resource) -> Resource {
+        let resource_type = &resource.address.resource_type;
+        let mut spec: Map<String, Value> = resource
+            .spec
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+
+        for path in self
+            .computed_fields
+            .get(resource_type)
+            .into_iter()
+            .flatten()
+        {
+            remove_path(&mut spec, path);
+        }
+        if let Some(schema) = self.schemas.get(resource_type) {
+            strip_with_schema(&mut spec, schema, schema);
+        }
+        spec.values_mut().for_each(escape);
+
+        Resource {
+            address: resource.address.clone(),
+            spec: spec.into_iter().collect(),
+            metadata: resource.metadata.clone(),
+        }
+    }
+
+    /// Renders the resources into files, sorted by path, with resources sorted by
+    /// address inside each file.
+    pub fn generate(&self, resources: &[Resource]) -> Result<Vec<GeneratedFile>> {
+        let mut groups: BTreeMap<String, Vec<ResourceDocument>> = BTreeMap::new();
+        for resource in resources {
+            let group = match self.group_by {
+                GroupBy::ResourceType => suggest_name(&resource.address.resource_type),
+                GroupBy::Namespace => resource
+                    .address
+                    .namespace
+                    .as_deref()
+                    .map(suggest_name)
+                    .unwrap_or_else(|| "default".to_string()),
+            };
+            groups
+                .entry(group)
+                .or_default()
+                .push(ResourceDocument::from(&self.canonicalize(resource)));
+        }
+
+        let mut files = vec![];
+        for (group, mut documents) in groups {
+            documents.sort_by(|a, b| {
+                (&a.resource_type, &a.namespace, &a.name).cmp(&(
+                    &b.resource_type,
+                    &b.namespace,
+                    &b.name,
+                ))
+            });
+            files.push(GeneratedFile {
+                path: PathBuf::from(format!("{group}.{}", self.format.extension())),
+                contents: self.render(&documents)?,
+            });
+        }
+        Ok(files)
+    }
+
+    /// Generates the files and writes them into `dir`, returning the written paths.
+    pub async fn write_to<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        resources: &[Resource],
+    ) -> Result<Vec<PathBuf>> {
+        let dir = dir.as_ref();
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to create config directory: {e}")))?;
+
+        let mut written = vec![];
+        for file in self.generate(resources)? {
+            let path = dir.join(&file.path);
+            tokio::fs::write(&path, file.contents).await.map_err(|e| {
+                Error::Internal(format!(
+                    "Failed to write config file {}: {e}",
+                    path.display()
+                ))
+            })?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+
+    fn render(&self, documents: &[ResourceDocument]) -> Result<String> {
+        match self.format {
+            ConfigFormat::Json => {
+                let mut json = serde_json::to_string_pretty(documents)?;
+                json.push('\n');
+                Ok(json)
+            }
+            ConfigFormat::Yaml => {
+                let mut yaml = String::new();
+                for (idx, document) in documents.iter().enumerate() {
+                    if idx > 0 {
+                        yaml.push_str("---\n");
+                    }
+                    yaml.push_str(&serde_yaml::to_string(document).map_err(|e| {
+                        Error::Internal(format!("Failed to render YAML config: {e}"))
+                    })?);
+                }
+                Ok(yaml)
+            }
+        }
+    }
+}
+
+fn remove_path(map: &mut Map<String, Value>, path: &str) {
+    let Ok(tokens) = tokens(path) else {
+        return;
+    };
+    let Some((last, parents)) = tokens.split_last() else {
+        return;
+    };
+    let mut current = map;
+    for key in parents {
+        match current.get_mut(key) {
+            Some(Value::Object(child)) => current = child,
+            _ => return,
+        }
+    }
+    let _ = current.remove(last);
+}
+
+/// Escapes `${` as `$${` in the value's strings, so interpolation restores them.
+fn escape(value: &mut Value) {
+    match value {
+        Value::String(text) => *text = text.replace("${", "$${"),
+        Value::Array(items) => items.iter_mut().for_each(escape),
+        Value::Object(map) => map.values_mut().for_each(escape),
+        _ => {}
+    }
+}
+
+fn strip_with_schema(map: &mut Map<String, Value>, schema: &Value, root: &Value) {
+    let Some(properties) = resolve_ref(schema, root).get("properties") else {
+        return;
+    };
+
+    map.retain(|key, value| {
+        let Some(property) = properties.get(key) else {
+            return true;
+        };
+        let read_only = property.get("readOnly").and_then(Value::as_bool) == Some(true);
+        let is_default = property.get("default") == Some(value);
+        !(read_only || is_default)
+    });
+
+    for (key, value) in map.iter_mut() {
+        if let (Value::Object(child), Some(property)) = (value, properties.get(key)) {
+            strip_with_schema(child, property, root);
+        }
+    }
+}
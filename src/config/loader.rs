@@ -0,0 +1,691 @@
+use super::variables::{VariableDeclaration, Variables};
+use super::ResourceDocument;
+use crate::types::patch::child;
+use crate::types::{Diagnostic, Resource, ResourceAddress};
+use crate::utils::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A position in a workspace configuration file. Line and column are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SourceLocation {
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl SourceLocation {
+    pub fn new(file: impl Into<PathBuf>, line: usize, column: usize) -> Self {
+        Self {
+            file: file.into(),
+            line,
+            column,
+        }
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file.display(), self.line, self.column)
+    }
+}
+
+/// A resource read from a configuration file, with where it and its fields are defined.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadedResource {
+    pub resource: Resource,
+    /// Start of the resource's document.
+    pub location: SourceLocation,
+    /// Locations of the document's keys and list items by JSON Pointer, e.g. `/spec/email`.
+    fields: BTreeMap<String, SourceLocation>,
+}
+
+impl LoadedResource {
+    /// Location of a spec attribute (a JSON Pointer, e.g. `/credentials/secret`). Values
+    /// without a location of their own, such as members of inline `{ ... }` maps, resolve
+    /// to their closest located parent, and finally to the resource.
+    pub fn attribute_location(&self, attribute: &str) -> &SourceLocation {
+        let mut path = format!("/spec{attribute}");
+        loop {
+            if let Some(location) = self.fields.get(&path) {
+                return location;
+            }
+            match path.rfind('/') {
+                Some(end) => path.truncate(end),
+                None => return self.fields.get(&path).unwrap_or(&self.location),
+            }
+        }
+    }
+}
+
+/// The resources and variable declarations of a workspace: every `.yaml`, `.yml` and
+/// `.json` file in a directory and its subdirectories.
+///
+/// Hidden files and directories (such as `.git` or `.github`) and `target` directories
+/// are skipped, as are var files named `*.vars.yaml`, `*.vars.yml` or `*.vars.json`,
+/// which hold variable values rather than documents (see [`Variables::var_file`]).
+///
+/// YAML files hold one [`ResourceDocument`] or [`VariableDeclaration`] per document
+/// (separated by `---`); JSON files hold an array of documents or a single one. Resource
+/// addresses and variable names must be unique across the workspace.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Workspace {
+    resources: Vec<LoadedResource>,
+    variables: Vec<(VariableDeclaration, SourceLocation)>,
+}
+
+/// Loads the resources of the workspace in `dir`, with variables taken from their
+/// defaults and `IAMCTL_VAR_<name>` environment variables; see [`Workspace::load`] and
+/// [`Workspace::resolve`].
+pub async fn load_resources(dir: impl AsRef<Path>) -> Result<Vec<Resource>> {
+    let workspace = Workspace::load(dir).await?;
+    workspace.resolve(&workspace.variable_values().env())
+}
+
+/// A document of a configuration file.
+enum Document {
+    Resource(LoadedResource),
+    Variable(VariableDeclaration, SourceLocation),
+}
+
+impl Workspace {
+    /// Reads the configuration files under `dir`, in path order.
+    pub async fn load(dir: impl AsRef<Path>) -> Result<Self> {
+        let mut files = vec![];
+        let mut pending = vec![dir.as_ref().to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            let mut entries = tokio::fs::read_dir(&dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                if name.starts_with('.') {
+                    continue;
+                }
+                if entry.file_type().await?.is_dir() {
+                    if name != "target" {
+                        pending.push(path);
+                    }
+                } else if ConfigFile::format(&path).is_some() && !ConfigFile::is_var_file(&path) {
+                    files.push(path);
+                }
+            }
+        }
+        files.sort();
+
+        let mut workspace = Self::default();
+        for file in files {
+            let contents = tokio::fs::read_to_string(&file).await?;
+            workspace.extend(Self::parse(&file, &contents)?)?;
+        }
+        Ok(workspace)
+    }
+
+    /// Parses the contents of one configuration file; the format follows the extension.
+    pub fn parse(file: impl AsRef<Path>, contents: &str) -> Result<Self> {
+        let file = file.as_ref();
+        let documents = match ConfigFile::format(file) {
+            Some(ConfigFile::Yaml) => parse_yaml(file, contents)?,
+            Some(ConfigFile::Json) => parse_json(file, contents)?,
+            None => {
+                return Err(Error::InvalidConfig {
+                    location: SourceLocation::new(file, 1, 1),
+                    reason: "expected a .yaml, .yml or .json file".to_string(),
+                })
+            }
+        };
+        let mut workspace = Self::default();
+        for document in documents {
+            workspace.add(document)?;
+        }
+        Ok(workspace)
+    }
+
+    /// Builds a workspace, rejecting resources defined more than once.
+    pub fn from_resources(resources: Vec<LoadedResource>) -> Result<Self> {
+        let mut workspace = Self::default();
+        for resource in resources {
+            workspace.add(Document::Resource(resource))?;
+        }
+        Ok(workspace)
+    }
+
+    /// Adds the resources and variables of another workspace, e.g. another file.
+    pub fn extend(&mut self, other: Workspace) -> Result<()> {
+        let resources = other.resources.into_iter().map(Document::Resource);
+        let variables = other
+            .variables
+            .into_iter()
+            .map(|(declaration, location)| Document::Variable(declaration, location));
+        for document in resources.chain(variables) {
+            self.add(document)?;
+        }
+        Ok(())
+    }
+
+    fn add(&mut self, document: Document) -> Result<()> {
+        match document {
+            Document::Resource(loaded) => {
+                if let Some(first) = self.get(&loaded.resource.address) {
+                    return Err(Error::DuplicateResource {
+                        address: loaded.resource.address.to_string(),
+                        first: first.location.clone(),
+                        duplicate: loaded.location,
+                    });
+                }
+                self.resources.push(loaded);
+            }
+            Document::Variable(declaration, location) => {
+                if let Some((_, first)) = self
+                    .variables
+                    .iter()
+                    .find(|(d, _)| d.name == declaration.name)
+                {
+                    return Err(Error::InvalidConfig {
+                        location,
+                        reason: format!(
+                            "variable '{}' is already declared at {first}",
+                            declaration.name
+                        ),
+                    });
+                }
+                self.variables.push((declaration, location));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn resources(&self) -> &[LoadedResource] {
+        &self.resources
+    }
+
+    pub fn get(&self, address: &ResourceAddress) -> Option<&LoadedResource> {
+        self.resources
+            .iter()
+            .find(|loaded| &loaded.resource.address == address)
+    }
+
+    pub fn to_resources(&self) -> Vec<Resource> {
+        self.resources.iter().map(|r| r.resource.clone()).collect()
+    }
+
+    pub fn into_resources(self) -> Vec<Resource> {
+        self.resources.into_iter().map(|r| r.resource).collect()
+    }
+
+    pub fn variables(&self) -> impl Iterator<Item = &VariableDeclaration> {
+        self.variables.iter().map(|(declaration, _)| declaration)
+    }
+
+    /// Where a variable is declared.
+    pub fn variable_location(&self, name: &str) -> Option<&SourceLocation> {
+        self.variables
+            .iter()
+            .find(|(declaration, _)| declaration.name == name)
+            .map(|(_, location)| location)
+    }
+
+    /// Variable values for this workspace's declarations, initially their defaults.
+    pub fn variable_values(&self) -> Variables {
+        Variables::new(self.variables())
+    }
+
+    /// The resources with `${var.name}` references replaced by their values.
+    pub fn resolve(&self, variables: &Variables) -> Result<Vec<Resource>> {
+        self.resources
+            .iter()
+            .map(|loaded| variables.interpolate(&loaded.resource))
+            .collect()
+    }
+
+    /// Where a diagnostic's resource, or its attribute (e.g. `spec/credentials/secret`),
+    /// is defined.
+    pub fn locate(&self, diagnostic: &Diagnostic) -> Option<&SourceLocation> {
+        let loaded = self.get(diagnostic.address.as_ref()?)?;
+        let attribute = diagnostic
+            .attribute
+            .as_deref()
+            .and_then(|attribute| attribute.strip_prefix("spec"));
+        Some(match attribute {
+            Some(path) if path.starts_with('/') => loaded.attribute_location(path),
+            _ => &loaded.location,
+        })
+    }
+}
+
+pub(super) enum ConfigFile {
+    Yaml,
+    Json,
+}
+
+impl ConfigFile {
+    pub(super) fn format(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "yaml" | "yml" => Some(Self::Yaml),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// True for var files, e.g. `prod.vars.yaml`, which [`Workspace::load`] skips.
+    pub(super) fn is_var_file(path: &Path) -> bool {
+        path.file_stem()
+            .and_then(|stem| Path::new(stem).extension())
+            .is_some_and(|extension| extension == "vars")
+    }
+}
+
+fn parse_yaml(file: &Path, contents: &str) -> Result<Vec<Document>> {
+    let lines: Vec<&str> = contents.lines().collect();
+    let mut documents = vec![];
+    let mut start = 0;
+    loop {
+        let end = (start + 1..lines.len())
+            .find(|&i| is_document_start(lines[i]))
+            .unwrap_or(lines.len());
+        let mut text = String::new();
+        for line in &lines[start..end] {
+            match line.trim_end() {
+                "..." => {}
+                // Blanked rather than dropped, so columns on the line stay the same.
+                _ if is_document_start(line) => text.push_str(&line.replacen("---", "   ", 1)),
+                _ => text.push_str(line),
+            }
+            text.push('\n');
+        }
+
+        let value: serde_yaml::Value =
+            serde_yaml::from_str(&text).map_err(|e| yaml_error(file, e, start))?;
+        if !value.is_null() {
+            let fields = yaml_locations(file, &lines, start, end);
+            let location = fields
+                .values()
+                .min_by_key(|l| (l.line, l.column))
+                .cloned()
+                .unwrap_or_else(|| SourceLocation::new(file, start + 1, 1));
+            // Decoding errors carry no position, so they point at the document.
+            let invalid = |e: serde_yaml::Error| Error::InvalidConfig {
+                location: location.clone(),
+                reason: e.to_string(),
+            };
+            documents.push(match value.get("variable") {
+                Some(_) => {
+                    let declaration = serde_yaml::from_value(value).map_err(invalid)?;
+                    Document::Variable(declaration, location)
+                }
+                None => {
+                    let document: ResourceDocument =
+                        serde_yaml::from_value(value).map_err(invalid)?;
+                    Document::Resource(LoadedResource {
+                        resource: document.into(),
+                        location,
+                        fields,
+                    })
+                }
+            });
+        }
+        if end == lines.len() {
+            break;
+        }
+        start = end;
+    }
+    Ok(documents)
+}
+
+fn is_document_start(line: &str) -> bool {
+    line == "---" || line.starts_with("--- ") || line.starts_with("---\t")
+}
+
+/// A YAML parse error at its position in a document starting after `offset` lines of
+/// the file, or at the document's first line if it has none.
+pub(super) fn yaml_error(file: &Path, e: serde_yaml::Error, offset: usize) -> Error {
+    let (line, column) = e.location().map_or((1, 1), |l| (l.line(), l.column()));
+    Error::InvalidConfig {
+        location: SourceLocation::new(file, offset + line, column),
+        reason: strip_position(e.to_string(), line, column),
+    }
+}
+
+pub(super) fn json_error(file: &Path, e: serde_json::Error) -> Error {
+    let (line, column) = (e.line(), e.column());
+    Error::InvalidConfig {
+        location: SourceLocation::new(file, line, column),
+        reason: strip_position(e.to_string(), line, column),
+    }
+}
+
+/// Locations of the keys and list items of a single-document file by JSON Pointer.
+pub(super) fn key_locations(file: &Path, contents: &str) -> BTreeMap<String, SourceLocation> {
+    let mut fields = BTreeMap::new();
+    match ConfigFile::format(file) {
+        Some(ConfigFile::Json) => JsonScanner::new(file, contents).value("", &mut fields),
+        _ => {
+            let lines: Vec<&str> = contents.lines().collect();
+            fields = yaml_locations(file, &lines, 0, lines.len());
+        }
+    }
+    fields
+}
+
+/// Removes the ` at line L column C` suffix serde adds to messages, as the location is
+/// reported separately.
+fn strip_position(message: String, line: usize, column: usize) -> String {
+    let suffix = format!(" at line {line} column {column}");
+    match message.strip_suffix(&suffix) {
+        Some(stripped) => stripped.to_string(),
+        None => message,
+    }
+}
+
+/// A key or list item open at some indentation while scanning a YAML document.
+struct Open {
+    indent: usize,
+    path: String,
+    item: bool,
+    next_index: usize,
+}
+
+/// Locates the keys and list items of a block-style YAML document by indentation.
+/// Members of flow collections (`{ ... }`, `[ ... ]`), the lines of block scalars and
+/// the contents of aliased values are not located.
+fn yaml_locations(
+    file: &Path,
+    lines: &[&str],
+    start: usize,
+    end: usize,
+) -> BTreeMap<String, SourceLocation> {
+    let mut fields = BTreeMap::new();
+    let mut open: Vec<Open> = vec![];
+    let mut block_scalar: Option<usize> = None;
+    let mut flow = 0;
+    for (number, line) in lines.iter().enumerate().take(end).skip(start) {
+        let replaced;
+        let mut line = *line;
+        if number == start && is_document_start(line) {
+            replaced = line.replacen("---", "   ", 1);
+            line = &replaced;
+        }
+        let content = line.trim_start();
+        let mut indent = line.len() - content.len();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+        if let Some(scalar_indent) = block_scalar {
+            if indent > scalar_indent {
+                continue;
+            }
+            block_scalar = None;
+        }
+        if flow > 0 {
+            flow = flow_nesting(content, flow);
+            continue;
+        }
+
+        let mut rest = content;
+        let column = |indent: usize| SourceLocation::new(file, number + 1, indent + 1);
+        while let Some(after_dash) = rest.strip_prefix("- ").or((rest == "-").then_some("")) {
+            while open
+                .last()
+                .is_some_and(|o| o.indent > indent || (o.indent == indent && o.item))
+            {
+                let _ = open.pop();
+            }
+            let path = match open.last_mut() {
+                Some(parent) => {
+                    parent.next_index += 1;
+                    child(&parent.path, &(parent.next_index - 1).to_string())
+                }
+                None => break,
+            };
+            let _ = fields.insert(path.clone(), column(indent));
+            open.push(Open {
+                indent,
+                path,
+                item: true,
+                next_index: 0,
+            });
+            let trimmed = after_dash.trim_start();
+            indent += 2 + after_dash.len() - trimmed.len();
+            rest = trimmed;
+        }
+
+        let value = match yaml_key(rest) {
+            Some((key, value)) => {
+                while open.last().is_some_and(|o| o.indent >= indent) {
+                    let _ = open.pop();
+                }
+                let path = child(open.last().map_or("", |parent| parent.path.as_str()), &key);
+                let _ = fields.insert(path.clone(), column(indent));
+                open.push(Open {
+                    indent,
+                    path,
+                    item: false,
+                    next_index: 0,
+                });
+                value
+            }
+            None => rest,
+        };
+        // Anchors and tags come before the value, as in `key: &anchor |`.
+        let mut value = value;
+        while value.starts_with(['&', '!']) {
+            value = value
+                .split_once(' ')
+                .map_or("", |(_, rest)| rest.trim_start());
+        }
+        if value.starts_with(['|', '>']) {
+            block_scalar = open.last().map(|o| o.indent);
+        } else if value.starts_with(['{', '[']) {
+            flow = flow_nesting(value, 0);
+        }
+    }
+    fields
+}
+
+/// The nesting of flow collections after `text`, starting from `depth`. Brackets in
+/// quotes and comments are ignored.
+fn flow_nesting(text: &str, mut depth: usize) -> usize {
+    let mut quote = None;
+    let mut previous = ' ';
+    for c in text.chars() {
+        match (quote, c) {
+            (Some(open), _) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') if previous == ' ' => break,
+            (None, '{' | '[') => depth += 1,
+            (None, '}' | ']') => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        previous = c;
+    }
+    depth
+}
+
+/// Splits `key: value` into the key and the value, or `None` if the line has no key.
+fn yaml_key(text: &str) -> Option<(String, &str)> {
+    let (key, rest) = match text.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let close = text[1..].find(quote)? + 1;
+            let rest = text[close + 1..].trim_start().strip_prefix(':')?;
+            (text[1..close].to_string(), rest)
+        }
+        '{' | '[' | '#' | '&' | '*' | '!' | '|' | '>' | '%' | '@' | '`' => return None,
+        _ => {
+            let colon = text
+                .match_indices(':')
+                .map(|(i, _)| i)
+                .find(|&i| text[i + 1..].is_empty() || text[i + 1..].starts_with([' ', '\t']))?;
+            (text[..colon].trim_end().to_string(), &text[colon + 1..])
+        }
+    };
+    if !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    Some((key, rest.trim()))
+}
+
+fn parse_json(file: &Path, contents: &str) -> Result<Vec<Document>> {
+    let value: serde_json::Value =
+        serde_json::from_str(contents).map_err(|e| json_error(file, e))?;
+
+    let mut scanner = JsonScanner::new(file, contents);
+    let mut fields = BTreeMap::new();
+    let root = scanner.position();
+    scanner.value("", &mut fields);
+    let documents = match value {
+        serde_json::Value::Array(documents) => documents
+            .into_iter()
+            .enumerate()
+            .map(|(index, document)| {
+                let prefix = child("", &index.to_string());
+                let located = fields
+                    .iter()
+                    .filter_map(|(path, location)| {
+                        let path = path.strip_prefix(&prefix)?;
+                        path.starts_with('/')
+                            .then(|| (path.to_string(), location.clone()))
+                    })
+                    .collect();
+                let location = fields.get(&prefix).unwrap_or(&root).clone();
+                (document, location, located)
+            })
+            .collect(),
+        document => vec![(document, root, fields)],
+    };
+
+    documents
+        .into_iter()
+        .map(|(document, location, fields)| {
+            let invalid = |e: serde_json::Error| Error::InvalidConfig {
+                location: location.clone(),
+                reason: e.to_string(),
+            };
+            if document.get("variable").is_some() {
+                let declaration = serde_json::from_value(document).map_err(invalid)?;
+                return Ok(Document::Variable(declaration, location));
+            }
+            let document: ResourceDocument = serde_json::from_value(document).map_err(invalid)?;
+            Ok(Document::Resource(LoadedResource {
+                resource: document.into(),
+                location,
+                fields,
+            }))
+        })
+        .collect()
+}
+
+/// Locates the members and array elements of a JSON text already known to be valid.
+struct JsonScanner<'a> {
+    file: &'a Path,
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> JsonScanner<'a> {
+    fn new(file: &'a Path, contents: &'a str) -> Self {
+        let mut scanner = Self {
+            file,
+            chars: contents.chars().peekable(),
+            line: 1,
+            column: 1,
+        };
+        scanner.skip_whitespace();
+        scanner
+    }
+
+    fn position(&self) -> SourceLocation {
+        SourceLocation::new(self.file, self.line, self.column)
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        match c {
+            '\n' => {
+                self.line += 1;
+                self.column = 1;
+            }
+            _ => self.column += 1,
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            let _ = self.next();
+        }
+    }
+
+    fn value(&mut self, path: &str, fields: &mut BTreeMap<String, SourceLocation>) {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('{') => {
+                let _ = self.next();
+                loop {
+                    self.skip_whitespace();
+                    if self.chars.peek() != Some(&'"') {
+                        let _ = self.next();
+                        break;
+                    }
+                    let location = self.position();
+                    let nested = child(path, &self.string());
+                    let _ = fields.insert(nested.clone(), location);
+                    self.skip_whitespace();
+                    let _ = self.next();
+                    self.value(&nested, fields);
+                    self.skip_whitespace();
+                    if self.next() != Some(',') {
+                        break;
+                    }
+                }
+            }
+            Some('[') => {
+                let _ = self.next();
+                for index in 0.. {
+                    self.skip_whitespace();
+                    if self.chars.peek() == Some(&']') {
+                        let _ = self.next();
+                        break;
+                    }
+                    let nested = child(path, &index.to_string());
+                    let _ = fields.insert(nested.clone(), self.position());
+                    self.value(&nested, fields);
+                    self.skip_whitespace();
+                    if self.next() != Some(',') {
+                        break;
+                    }
+                }
+            }
+            Some('"') => {
+                let _ = self.string();
+            }
+            _ => {
+                while self
+                    .chars
+                    .peek()
+                    .is_some_and(|c| !matches!(c, ',' | ']' | '}') && !c.is_whitespace())
+                {
+                    let _ = self.next();
+                }
+            }
+        }
+    }
+
+    /// Reads a string literal and returns its value.
+    fn string(&mut self) -> String {
+        let mut raw = String::from('"');
+        let _ = self.next();
+        while let Some(c) = self.next() {
+            raw.push(c);
+            match c {
+                '\\' => raw.extend(self.next()),
+                '"' => break,
+                _ => {}
+            }
+        }
+        serde_json::from_str(&raw).unwrap_or_default()
+    }
+}
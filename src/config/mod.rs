@@ -0,0 +1,61 @@
+pub mod generate;
+pub mod loader;
+pub mod variables;
+
+pub use generate::{ConfigFormat, ConfigGenerator, GeneratedFile, GroupBy};
+pub use loader::{load_resources, LoadedResource, SourceLocation, Workspace};
+pub use variables::{VariableDeclaration, VariableType, Variables};
+
+use crate::types::{Resource, ResourceAddress};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// On-disk shape of a single resource in a workspace configuration file.
+///
+/// YAML files hold one document per resource (separated by `---`); JSON files hold
+/// an array of documents or a single one. See [`Workspace`] for loading them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceDocument {
+    pub resource_type: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub spec: BTreeMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl From<&Resource> for ResourceDocument {
+    fn from(resource: &Resource) -> Self {
+        Self {
+            resource_type: resource.address.resource_type.clone(),
+            name: resource.address.name.clone(),
+            namespace: resource.address.namespace.clone(),
+            spec: resource
+                .spec
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            metadata: resource
+                .metadata
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl From<ResourceDocument> for Resource {
+    fn from(document: ResourceDocument) -> Self {
+        Self {
+            address: ResourceAddress {
+                resource_type: document.resource_type,
+                name: document.name,
+                namespace: document.namespace,
+            },
+            spec: document.spec.into_iter().collect(),
+            metadata: document.metadata.into_iter().collect(),
+        }
+    }
+}
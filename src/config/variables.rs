@@ -0,0 +1,257 @@
+use super::loader::{json_error, key_locations, yaml_error, ConfigFile, SourceLocation};
+use crate::types::patch::child;
+use crate::types::{Resource, ResourceAddress};
+use crate::utils::interpolate::{describe, insertable, interpolate_spec, DATA_PREFIX};
+use crate::utils::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Prefix of the environment variables setting input variables, e.g. `IAMCTL_VAR_realm`.
+pub const ENV_PREFIX: &str = "IAMCTL_VAR_";
+
+/// An input variable declared in a workspace document:
+///
+/// ```yaml
+/// variable: realm
+/// type: string
+/// default: demo
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VariableDeclaration {
+    #[serde(rename = "variable")]
+    pub name: String,
+    #[serde(default, rename = "type")]
+    pub variable_type: VariableType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// The type of values a variable accepts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableType {
+    String,
+    Number,
+    Bool,
+    List,
+    Map,
+    #[default]
+    Any,
+}
+
+impl VariableType {
+    pub fn accepts(&self, value: &Value) -> bool {
+        matches!(
+            (self, value),
+            (Self::Any, _)
+                | (Self::String, Value::String(_))
+                | (Self::Number, Value::Number(_))
+                | (Self::Bool, Value::Bool(_))
+                | (Self::List, Value::Array(_))
+                | (Self::Map, Value::Object(_))
+        )
+    }
+
+    fn expected(&self) -> &'static str {
+        match self {
+            Self::String => "a string",
+            Self::Number => "a number",
+            Self::Bool => "a bool",
+            Self::List => "a list",
+            Self::Map => "a map",
+            Self::Any => "any value",
+        }
+    }
+
+    /// Environment values are JSON for every type but strings, falling back to the raw
+    /// string (which then fails the type check where it is used).
+    fn parse_env(&self, raw: &str) -> Value {
+        match self {
+            Self::String => Value::String(raw.to_string()),
+            _ => serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string())),
+        }
+    }
+}
+
+/// Values of a workspace's input variables, and `${var.name}` interpolation of specs.
+///
+/// A variable without a value falls back to its default. Values set later replace earlier
+/// ones, so the usual order is [`Variables::env`], then var files, then explicit values.
+#[derive(Debug, Clone, Default)]
+pub struct Variables {
+    declarations: BTreeMap<String, VariableDeclaration>,
+    values: HashMap<String, Value>,
+}
+
+impl Variables {
+    pub fn new<'a>(declarations: impl IntoIterator<Item = &'a VariableDeclaration>) -> Self {
+        Self {
+            declarations: declarations
+                .into_iter()
+                .map(|d| (d.name.clone(), d.clone()))
+                .collect(),
+            values: HashMap::new(),
+        }
+    }
+
+    /// Sets a declared variable.
+    pub fn value(mut self, name: &str, value: Value) -> Result<Self> {
+        if !self.declarations.contains_key(name) {
+            return Err(Error::Config(format!(
+                "Value given for undeclared variable '{name}'"
+            )));
+        }
+        let _ = self.values.insert(name.to_string(), value);
+        Ok(self)
+    }
+
+    /// Sets declared variables from `IAMCTL_VAR_<name>` environment variables.
+    pub fn env(self) -> Self {
+        self.env_vars(std::env::vars())
+    }
+
+    /// Sets declared variables from `IAMCTL_VAR_<name>` entries of `vars`.
+    pub fn env_vars(mut self, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        for (key, raw) in vars {
+            let Some(name) = key.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            if let Some(declaration) = self.declarations.get(name) {
+                let value = declaration.variable_type.parse_env(&raw);
+                let _ = self.values.insert(name.to_string(), value);
+            }
+        }
+        self
+    }
+
+    /// Sets variables from a var file; see [`Variables::parse_var_file`]. Var files inside
+    /// a workspace should be named `*.vars.yaml`, `*.vars.yml` or `*.vars.json`, so
+    /// [`Workspace::load`](super::Workspace::load) does not read them as documents.
+    pub async fn var_file(self, path: impl AsRef<Path>) -> Result<Self> {
+        let contents = tokio::fs::read_to_string(path.as_ref()).await?;
+        self.parse_var_file(path, &contents)
+    }
+
+    /// Sets variables from the contents of a var file: a YAML or JSON map of variable
+    /// names to values. Every name must be declared.
+    pub fn parse_var_file(mut self, file: impl AsRef<Path>, contents: &str) -> Result<Self> {
+        let file = file.as_ref();
+        let values: BTreeMap<String, Value> = match ConfigFile::format(file) {
+            Some(ConfigFile::Yaml) => serde_yaml::from_str::<Option<_>>(contents)
+                .map_err(|e| yaml_error(file, e, 0))?
+                .unwrap_or_default(),
+            Some(ConfigFile::Json) => {
+                serde_json::from_str(contents).map_err(|e| json_error(file, e))?
+            }
+            None => {
+                return Err(Error::InvalidConfig {
+                    location: SourceLocation::new(file, 1, 1),
+                    reason: "expected a .yaml, .yml or .json var file".to_string(),
+                })
+            }
+        };
+        for (name, value) in values {
+            if !self.declarations.contains_key(&name) {
+                let location = key_locations(file, contents)
+                    .remove(&child("", &name))
+                    .unwrap_or_else(|| SourceLocation::new(file, 1, 1));
+                return Err(Error::InvalidConfig {
+                    location,
+                    reason: format!("value given for undeclared variable '{name}'"),
+                });
+            }
+            let _ = self.values.insert(name, value);
+        }
+        Ok(self)
+    }
+
+    /// The value of a variable, or its default.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values
+            .get(name)
+            .or_else(|| self.declarations.get(name)?.default.as_ref())
+    }
+
+    /// Replaces `${var.name}` references in the resource's spec strings. A string that
+    /// is a single reference takes the variable's value, whatever its type; otherwise the
+    /// value is inserted into the string. `$${` stands for a literal `${`. References to
+    /// data sources (`${data.<type>.<name>.<attribute>}`) are kept for the provider,
+    /// which resolves them when planning, and so are escapes of them (`$${data.`).
+    pub fn interpolate(&self, resource: &Resource) -> Result<Resource> {
+        let address = &resource.address;
+        interpolate_spec(resource, Some(DATA_PREFIX), &|expression| {
+            if expression.text.starts_with(DATA_PREFIX) {
+                return Ok(None);
+            }
+            let attribute = expression.attribute;
+            let name = variable_name(expression.text).ok_or_else(|| Error::Interpolation {
+                address: Box::new(address.clone()),
+                attribute: attribute.to_string(),
+                reason: format!(
+                    "unsupported expression '${{{}}}', expected '${{var.<name>}}' or \
+                     '${{data.<type>.<name>.<attribute>}}'",
+                    expression.text
+                ),
+            })?;
+            let value = self.lookup(name, address, attribute)?;
+            if expression.embedded && !insertable(value) {
+                return Err(Error::InvalidVariable {
+                    address: Box::new(address.clone()),
+                    attribute: attribute.to_string(),
+                    name: name.to_string(),
+                    reason: format!(
+                        "is {} and cannot be inserted into a string",
+                        describe(value)
+                    ),
+                });
+            }
+            Ok(Some(value.clone()))
+        })
+    }
+
+    /// The value of a referenced variable, checked against its declared type.
+    fn lookup(&self, name: &str, address: &ResourceAddress, attribute: &str) -> Result<&Value> {
+        let invalid = |reason: String| Error::InvalidVariable {
+            address: Box::new(address.clone()),
+            attribute: attribute.to_string(),
+            name: name.to_string(),
+            reason,
+        };
+        let Some(declaration) = self.declarations.get(name) else {
+            return Err(Error::UndefinedVariable {
+                address: Box::new(address.clone()),
+                attribute: attribute.to_string(),
+                name: name.to_string(),
+            });
+        };
+        let value = self.get(name).ok_or_else(|| {
+            invalid(format!(
+                "has no value; set it in a var file, with {ENV_PREFIX}{name} or as a default"
+            ))
+        })?;
+        let expected = declaration.variable_type;
+        if !expected.accepts(value) {
+            return Err(invalid(format!(
+                "expects {}, got {}",
+                expected.expected(),
+                describe(value)
+            )));
+        }
+        Ok(value)
+    }
+}
+
+/// The variable name of a `var.<name>` expression.
+fn variable_name(expression: &str) -> Option<&str> {
+    expression.strip_prefix("var.").filter(|name| {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    })
+}
@@ -28,6 +28,8 @@ use tempfile as _;
 #[cfg(test)]
 use tokio_test as _;
 
+pub mod config;
+pub mod plan;
 pub mod provider;
 pub mod server;
 pub mod state;
@@ -35,16 +37,25 @@ pub mod types;
 pub mod utils;
 pub mod validation;
 
+#[cfg(feature = "derive")]
+pub use iamctl_rust_sdk_macros::provider;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use anyhow;
+    pub use async_trait::async_trait;
+}
+
 pub mod prelude {
     pub use crate::provider::{
-        ApplyRequest, ApplyResponse, PlanRequest, PlanResponse, Provider, ProviderCapabilities,
-        ProviderMetadata,
+        ApplyRequest, ApplyResponse, IamResource, PlanRequest, PlanResponse, Provider,
+        ProviderBuilder, ProviderCapabilities, ProviderMetadata, ResourceHandler,
     };
     pub use crate::state::{State, StateBackend, StateLocking};
-    pub use crate::types::{Change, ChangeType, Resource, ResourceAddress};
-    pub use crate::utils::decode_spec;
+    pub use crate::types::{Change, ChangeType, Diagnostic, Resource, ResourceAddress};
     pub use crate::utils::init_logging;
     pub use crate::utils::provider_source::{derive_github_release_tag, GithubProviderSource};
+    pub use crate::utils::{decode_spec, encode_spec};
     pub use crate::validation::{SchemaValidator, ValidationError, ValidationResult};
 }
 
@@ -0,0 +1,272 @@
+use crate::types::patch::{child, tokens};
+use crate::types::unknown::is_unknown;
+use crate::types::{AttributeChange, ChangeType, Resource};
+use crate::utils::schema::default_values;
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, HashMap};
+
+use super::{computed_attributes, force_new_attributes, set_attributes};
+
+/// Compares resource specs attribute by attribute.
+///
+/// Attributes are JSON Pointers into the spec, e.g. `/settings/theme`, so keys may hold
+/// dots or slashes (`/attributes/pkce.code.challenge.method`). Before comparing,
+/// each spec is normalized: missing or null attributes take their registered default,
+/// and set-like arrays are sorted and deduplicated. A null attribute equals a missing
+/// one, and numbers compare by value (`1` equals `1.0`).
+///
+/// Ignored attributes never differ. Computed attributes only differ when the desired
+/// spec sets them, since the provider fills them in otherwise. With
+/// [`Differ::coerce_scalars`], a number also equals a string holding the same number,
+/// as many IAM APIs echo numbers back as strings.
+#[derive(Debug, Clone, Default)]
+pub struct Differ {
+    force_new: Vec<String>,
+    computed: Vec<String>,
+    ignored: Vec<String>,
+    sets: Vec<String>,
+    defaults: HashMap<String, Value>,
+    coerce_scalars: bool,
+}
+
+impl Differ {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks attributes whose change requires replacing the resource.
+    pub fn force_new(mut self, attributes: &[String]) -> Self {
+        extend_unique(&mut self.force_new, attributes);
+        self
+    }
+
+    /// Marks attributes filled in by the provider.
+    pub fn computed(mut self, attributes: &[String]) -> Self {
+        extend_unique(&mut self.computed, attributes);
+        self
+    }
+
+    /// Marks attributes excluded from the comparison, e.g. server-managed timestamps.
+    pub fn ignore(mut self, attributes: &[String]) -> Self {
+        extend_unique(&mut self.ignored, attributes);
+        self
+    }
+
+    /// Marks array attributes whose order and duplicates are insignificant.
+    pub fn set(mut self, attributes: &[String]) -> Self {
+        extend_unique(&mut self.sets, attributes);
+        self
+    }
+
+    /// Sets the value a missing or null attribute is compared as.
+    pub fn default_value(mut self, attribute: &str, value: Value) -> Self {
+        let _ = self.defaults.insert(attribute.to_string(), value);
+        self
+    }
+
+    /// Makes numbers equal strings holding the same number.
+    pub fn coerce_scalars(mut self, coerce: bool) -> Self {
+        self.coerce_scalars = coerce;
+        self
+    }
+
+    /// Configures the differ from a resource schema: properties flagged with
+    /// [`super::FORCE_NEW_KEY`] are force-new, `readOnly` ones computed, `uniqueItems`
+    /// arrays sets, and `default`s are registered as default values.
+    pub fn schema(mut self, schema: &Value) -> Self {
+        for (attribute, value) in default_values(schema) {
+            let _ = self.defaults.entry(attribute).or_insert(value);
+        }
+        self.force_new(&force_new_attributes(schema))
+            .computed(&computed_attributes(schema))
+            .set(&set_attributes(schema))
+    }
+
+    pub fn computed_attributes(&self) -> &[String] {
+        &self.computed
+    }
+
+    /// Returns the attribute changes from `before` to `after`, in path order. A missing
+    /// side is a resource being created or deleted.
+    pub fn diff(
+        &self,
+        before: Option<&Resource>,
+        after: Option<&Resource>,
+    ) -> Vec<AttributeChange> {
+        let normalize = |resource: Option<&Resource>| {
+            resource
+                .map(|r| self.normalize(&r.spec))
+                .unwrap_or_default()
+        };
+        let mut changes = vec![];
+        self.diff_objects(
+            "",
+            &normalize(before),
+            &normalize(after),
+            after.is_some(),
+            &mut changes,
+        );
+        changes
+    }
+
+    /// The change type for moving from `before` to `after` given their attribute changes.
+    pub fn change_type(
+        &self,
+        before: Option<&Resource>,
+        after: Option<&Resource>,
+        changes: &[AttributeChange],
+    ) -> ChangeType {
+        match (before, after) {
+            (None, Some(_)) => ChangeType::Create,
+            (Some(_), None) => ChangeType::Delete,
+            (None, None) => ChangeType::NoOp,
+            (Some(_), Some(_)) if changes.is_empty() => ChangeType::NoOp,
+            (Some(_), Some(_)) if changes.iter().any(|c| c.force_new) => ChangeType::Replace,
+            (Some(_), Some(_)) => ChangeType::Update,
+        }
+    }
+
+    fn normalize(&self, spec: &HashMap<String, Value>) -> Map<String, Value> {
+        let mut value = Value::Object(spec.clone().into_iter().collect());
+        for (attribute, default) in &self.defaults {
+            set_default(&mut value, attribute, default);
+        }
+        for attribute in &self.sets {
+            if let Some(Value::Array(items)) = value.pointer_mut(attribute) {
+                items.sort_by_cached_key(|item| item.to_string());
+                // Unknown members may turn out different, so they are all kept.
+                items.dedup_by(|a, b| !is_unknown(a) && self.equal(a, b));
+            }
+        }
+        match value {
+            Value::Object(map) => map,
+            _ => unreachable!("spec is an object"),
+        }
+    }
+
+    fn diff_objects(
+        &self,
+        prefix: &str,
+        before: &Map<String, Value>,
+        after: &Map<String, Value>,
+        has_after: bool,
+        changes: &mut Vec<AttributeChange>,
+    ) {
+        let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+        for key in keys {
+            let path = child(prefix, key);
+            if covered(&self.ignored, &path) {
+                continue;
+            }
+            let b = before.get(key).filter(|v| !v.is_null());
+            let a = after.get(key).filter(|v| !v.is_null());
+            match (b, a) {
+                (Some(Value::Object(b)), Some(Value::Object(a)))
+                    if !is_unknown(&before[key]) && !is_unknown(&after[key]) =>
+                {
+                    self.diff_objects(&path, b, a, has_after, changes);
+                }
+                (_, None) if has_after && covered(&self.computed, &path) => {}
+                (None, None) => {}
+                (Some(b), Some(a)) if self.equal(b, a) => {}
+                (b, a) => changes.push(AttributeChange {
+                    force_new: self
+                        .force_new
+                        .iter()
+                        .any(|attribute| related(attribute, &path)),
+                    path,
+                    before: b.cloned(),
+                    after: a.cloned(),
+                }),
+            }
+        }
+    }
+
+    fn equal(&self, a: &Value, b: &Value) -> bool {
+        match (a, b) {
+            // An unknown value may turn out to be anything, so it only equals another.
+            _ if is_unknown(a) || is_unknown(b) => is_unknown(a) && is_unknown(b),
+            (Value::Number(x), Value::Number(y)) => {
+                if let (Some(x), Some(y)) = (x.as_i64(), y.as_i64()) {
+                    x == y
+                } else if let (Some(x), Some(y)) = (x.as_u64(), y.as_u64()) {
+                    x == y
+                } else {
+                    x.as_f64() == y.as_f64()
+                }
+            }
+            (Value::Number(n), Value::String(s)) | (Value::String(s), Value::Number(n))
+                if self.coerce_scalars =>
+            {
+                let number = Value::Number(n.clone());
+                serde_json::from_str::<Value>(s.trim())
+                    .is_ok_and(|parsed| parsed.is_number() && self.equal(&parsed, &number))
+            }
+            (Value::Array(x), Value::Array(y)) => {
+                x.len() == y.len() && x.iter().zip(y).all(|(x, y)| self.equal(x, y))
+            }
+            (Value::Object(x), Value::Object(y)) => {
+                let present = |map: &Map<String, Value>| {
+                    map.iter()
+                        .filter(|(_, v)| !v.is_null())
+                        .map(|(k, _)| k.clone())
+                        .collect::<BTreeSet<_>>()
+                };
+                present(x) == present(y)
+                    && x.iter()
+                        .filter(|(_, v)| !v.is_null())
+                        .all(|(k, v)| self.equal(v, &y[k]))
+            }
+            _ => a == b,
+        }
+    }
+}
+
+fn extend_unique(entries: &mut Vec<String>, attributes: &[String]) {
+    for attribute in attributes {
+        if !entries.contains(attribute) {
+            entries.push(attribute.clone());
+        }
+    }
+}
+
+/// True if `path` is one of `attributes` or nested below one.
+pub(super) fn covered(attributes: &[String], path: &str) -> bool {
+    attributes.iter().any(|attribute| {
+        path == attribute
+            || path
+                .strip_prefix(attribute.as_str())
+                .is_some_and(|rest| rest.starts_with('/'))
+    })
+}
+
+/// True if either path is the other or nested below it.
+fn related(a: &str, b: &str) -> bool {
+    covered(&[a.to_string()], b) || covered(&[b.to_string()], a)
+}
+
+/// Sets `attribute` to `default` if it is missing or null, creating parent objects.
+/// Leaves the value alone if a parent is not an object.
+fn set_default(value: &mut Value, attribute: &str, default: &Value) {
+    let Ok(segments) = tokens(attribute) else {
+        return;
+    };
+    let mut current = value;
+    let mut segments = segments.into_iter().peekable();
+    while let Some(segment) = segments.next() {
+        let Value::Object(map) = current else {
+            return;
+        };
+        let entry = map.entry(segment).or_insert(Value::Null);
+        if segments.peek().is_none() {
+            if entry.is_null() {
+                *entry = default.clone();
+            }
+            return;
+        }
+        if entry.is_null() {
+            *entry = Value::Object(Map::new());
+        }
+        current = entry;
+    }
+}
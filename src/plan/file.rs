@@ -0,0 +1,130 @@
+use crate::provider::{ApplyRequest, PlanResponse, ProviderMetadata};
+use crate::state::State;
+use crate::utils::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Version of the plan file format written by this SDK.
+pub const PLAN_FILE_FORMAT_VERSION: u32 = 1;
+
+/// A saved plan, so a plan can be reviewed and later applied exactly as reviewed.
+///
+/// The file records the provider that produced the plan, a hash of the state it was
+/// planned against and a SHA-256 checksum of its own content. [`PlanFile::apply_request`]
+/// refuses a plan that was modified, comes from another provider version or whose base
+/// state has changed since.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanFile {
+    pub format_version: u32,
+    pub provider_name: String,
+    pub provider_version: String,
+    pub created_at: DateTime<Utc>,
+    /// [`state_hash`] of the state the plan was computed against.
+    pub state_hash: String,
+    pub plan: PlanResponse,
+    /// SHA-256 of the file content without this field; see [`PlanFile::compute_checksum`].
+    pub checksum: String,
+}
+
+/// Hex SHA-256 of the state's canonical JSON (object keys sorted), so equal states hash
+/// equally whatever their map order.
+pub fn state_hash(state: &State) -> Result<String> {
+    let canonical = serde_json::to_value(state)?;
+    Ok(sha256(canonical.to_string().as_bytes()))
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+impl PlanFile {
+    /// Wraps a plan computed by `provider` against `state`.
+    pub fn new(provider: &ProviderMetadata, state: &State, plan: PlanResponse) -> Result<Self> {
+        let mut file = Self {
+            format_version: PLAN_FILE_FORMAT_VERSION,
+            provider_name: provider.name.clone(),
+            provider_version: provider.version.clone(),
+            created_at: Utc::now(),
+            state_hash: state_hash(state)?,
+            plan,
+            checksum: String::new(),
+        };
+        file.checksum = file.compute_checksum()?;
+        Ok(file)
+    }
+
+    /// Hex SHA-256 of the canonical JSON of every field except `checksum`.
+    pub fn compute_checksum(&self) -> Result<String> {
+        let mut canonical = serde_json::to_value(self)?;
+        if let Some(fields) = canonical.as_object_mut() {
+            let _ = fields.remove("checksum");
+        }
+        Ok(sha256(canonical.to_string().as_bytes()))
+    }
+
+    /// Checks the format version and that the content matches the checksum.
+    pub fn verify_integrity(&self) -> Result<()> {
+        if self.format_version != PLAN_FILE_FORMAT_VERSION {
+            return Err(Error::InvalidPlanFile(format!(
+                "unsupported format version {} (expected {PLAN_FILE_FORMAT_VERSION})",
+                self.format_version
+            )));
+        }
+        if self.compute_checksum()? != self.checksum {
+            return Err(Error::InvalidPlanFile(
+                "checksum mismatch; the plan was modified after it was created".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks that the plan is intact, was produced by `provider` (name and version)
+    /// and was computed against `state`.
+    pub fn verify(&self, provider: &ProviderMetadata, state: &State) -> Result<()> {
+        self.verify_integrity()?;
+        if (&self.provider_name, &self.provider_version) != (&provider.name, &provider.version) {
+            return Err(Error::InvalidPlanFile(format!(
+                "the plan was created by {} {}, not {} {}",
+                self.provider_name, self.provider_version, provider.name, provider.version
+            )));
+        }
+        let current = state_hash(state)?;
+        if current != self.state_hash {
+            return Err(Error::StalePlan {
+                planned: self.state_hash.clone(),
+                current,
+            });
+        }
+        Ok(())
+    }
+
+    /// The apply request for this plan, after [`PlanFile::verify`].
+    pub fn apply_request(
+        &self,
+        provider: &ProviderMetadata,
+        state: &State,
+    ) -> Result<ApplyRequest> {
+        self.verify(provider, state)?;
+        Ok(ApplyRequest {
+            changes: self.plan.changes.clone(),
+        })
+    }
+
+    /// Writes the plan as pretty-printed JSON.
+    pub async fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        tokio::fs::write(path, content).await?;
+        Ok(())
+    }
+
+    /// Reads a plan file and checks its integrity.
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let content = tokio::fs::read_to_string(path).await?;
+        let file: Self = serde_json::from_str(&content)
+            .map_err(|e| Error::InvalidPlanFile(format!("cannot parse the plan: {e}")))?;
+        file.verify_integrity()?;
+        Ok(file)
+    }
+}
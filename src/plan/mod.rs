@@ -0,0 +1,187 @@
+pub mod diff;
+pub mod file;
+pub mod render;
+pub mod selector;
+
+pub use diff::Differ;
+pub use file::PlanFile;
+pub use render::{render_plan, PlanRenderer};
+pub use selector::{AddressPattern, ResourceSelector};
+
+use crate::types::patch::json_patch;
+use crate::types::{Change, ChangeType, ReplaceOrder, Resource};
+use crate::utils::schema::flagged_attributes;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Schema extension marking a property whose change requires replacing the resource.
+pub const FORCE_NEW_KEY: &str = "x-iamctl-force-new";
+
+/// `schema_with` helper marking a field as force-new:
+/// `#[schemars(schema_with = "iamctl_rust_sdk::plan::force_new::<String>")]`.
+pub fn force_new<T: JsonSchema>(gen: &mut SchemaGenerator) -> Schema {
+    let mut schema = gen.subschema_for::<T>().into_object();
+    let _ = schema
+        .extensions
+        .insert(FORCE_NEW_KEY.to_string(), Value::Bool(true));
+    Schema::Object(schema)
+}
+
+/// Computes the changes needed to move from `current` to `desired`.
+///
+/// Resources are matched by address: missing resources are created, resources whose
+/// spec differs are updated, identical ones are `NoOp`, and resources only present in
+/// `current` are deleted. Changes follow the order of `desired`, then deletions in the
+/// order of `current`.
+pub fn plan_changes(desired: &[Resource], current: &[Resource]) -> Vec<Change> {
+    Planner::new().plan(desired, current)
+}
+
+/// Plans changes like [`plan_changes`], comparing specs with a [`Differ`] per resource
+/// type. Changes carry their `attribute_changes`, and updates their JSON `patch`; a
+/// change to a force-new attribute yields `ChangeType::Replace` instead of `Update`, and
+/// the type's computed attributes are listed in `Change::computed_fields`.
+#[derive(Debug, Clone, Default)]
+pub struct Planner {
+    differs: HashMap<String, Differ>,
+    replace_order: HashMap<String, ReplaceOrder>,
+}
+
+impl Planner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks attributes (JSON Pointers into the spec) of a resource type as force-new.
+    pub fn force_new(self, resource_type: &str, attributes: &[String]) -> Self {
+        self.update_differ(resource_type, |differ| differ.force_new(attributes))
+    }
+
+    /// Marks attributes (JSON Pointers into the spec) of a resource type as computed.
+    pub fn computed(self, resource_type: &str, attributes: &[String]) -> Self {
+        self.update_differ(resource_type, |differ| differ.computed(attributes))
+    }
+
+    /// Excludes attributes (JSON Pointers into the spec) of a resource type from diffs.
+    pub fn ignore(self, resource_type: &str, attributes: &[String]) -> Self {
+        self.update_differ(resource_type, |differ| differ.ignore(attributes))
+    }
+
+    /// Configures the type's differ from its schema; see [`Differ::schema`].
+    pub fn schema(self, resource_type: &str, schema: &Value) -> Self {
+        self.update_differ(resource_type, |differ| differ.schema(schema))
+    }
+
+    /// Replaces the differ of a resource type.
+    pub fn differ(mut self, resource_type: &str, differ: Differ) -> Self {
+        let _ = self.differs.insert(resource_type.to_string(), differ);
+        self
+    }
+
+    /// Sets the step order of replacements for a resource type.
+    pub fn replace_order(mut self, resource_type: &str, order: ReplaceOrder) -> Self {
+        let _ = self.replace_order.insert(resource_type.to_string(), order);
+        self
+    }
+
+    pub fn plan(&self, desired: &[Resource], current: &[Resource]) -> Vec<Change> {
+        let current_by_address: HashMap<_, _> = current.iter().map(|r| (&r.address, r)).collect();
+        let desired_by_address: HashMap<_, _> = desired.iter().map(|r| (&r.address, r)).collect();
+        let default_differ = Differ::default();
+        let differ = |resource: &Resource| {
+            self.differs
+                .get(&resource.address.resource_type)
+                .unwrap_or(&default_differ)
+        };
+
+        let mut changes = vec![];
+        for resource in desired {
+            let differ = differ(resource);
+            let before = current_by_address.get(&resource.address).copied();
+            let attribute_changes = differ.diff(before, Some(resource));
+            let change_type = differ.change_type(before, Some(resource), &attribute_changes);
+            let replace_order = (change_type == ChangeType::Replace).then(|| {
+                self.replace_order
+                    .get(&resource.address.resource_type)
+                    .copied()
+                    .unwrap_or_default()
+            });
+            let computed_fields = match change_type {
+                ChangeType::NoOp => vec![],
+                _ => differ.computed_attributes().to_vec(),
+            };
+            let patch = match (&change_type, before) {
+                (ChangeType::Update, Some(before)) => Some(json_patch(before, &attribute_changes)),
+                _ => None,
+            };
+            changes.push(Change {
+                address: resource.address.clone(),
+                change_type,
+                before: before.cloned(),
+                after: Some(resource.clone()),
+                computed_fields,
+                replace_order,
+                attribute_changes,
+                patch,
+            });
+        }
+
+        for resource in current {
+            if !desired_by_address.contains_key(&resource.address) {
+                changes.push(Change {
+                    address: resource.address.clone(),
+                    change_type: ChangeType::Delete,
+                    before: Some(resource.clone()),
+                    after: None,
+                    computed_fields: vec![],
+                    replace_order: None,
+                    attribute_changes: differ(resource).diff(Some(resource), None),
+                    patch: None,
+                });
+            }
+        }
+
+        changes
+    }
+
+    fn update_differ(mut self, resource_type: &str, update: impl FnOnce(Differ) -> Differ) -> Self {
+        let differ = self.differs.remove(resource_type).unwrap_or_default();
+        let _ = self
+            .differs
+            .insert(resource_type.to_string(), update(differ));
+        self
+    }
+}
+
+/// Attributes (JSON Pointers into the spec) whose value differs between two resources.
+pub(crate) fn changed_attributes<'a>(
+    attributes: &'a [String],
+    before: &Resource,
+    after: &Resource,
+) -> impl Iterator<Item = &'a String> {
+    let before = serde_json::to_value(&before.spec).unwrap_or_default();
+    let after = serde_json::to_value(&after.spec).unwrap_or_default();
+    attributes
+        .iter()
+        .filter(move |attribute| before.pointer(attribute) != after.pointer(attribute))
+}
+
+/// Returns the JSON Pointers of properties marked with [`FORCE_NEW_KEY`], following
+/// `$ref`s into nested objects.
+pub fn force_new_attributes(schema: &Value) -> Vec<String> {
+    flagged_attributes(schema, FORCE_NEW_KEY)
+}
+
+/// Returns the JSON Pointers of computed properties, i.e. those marked `readOnly`.
+pub fn computed_attributes(schema: &Value) -> Vec<String> {
+    flagged_attributes(schema, "readOnly")
+}
+
+/// Returns the JSON Pointers of set-like array properties, i.e. those marked
+/// `uniqueItems` (as schemars does for `HashSet` and `BTreeSet`).
+pub fn set_attributes(schema: &Value) -> Vec<String> {
+    flagged_attributes(schema, "uniqueItems")
+}
@@ -0,0 +1,397 @@
+use super::diff::covered;
+use super::Differ;
+use crate::types::patch::{child, pointer, tokens};
+use crate::types::unknown::is_unknown;
+use crate::types::{AttributeChange, Change, ChangeType, Resource};
+use crate::utils::redact::{sensitive_attributes, REDACTED};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Placeholder for computed and unknown values the provider only knows once applied.
+pub const KNOWN_AFTER_APPLY: &str = "(known after apply)";
+
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const RED: &str = "\x1b[31m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders planned changes as a Terraform-style plan for people.
+///
+/// Each change is shown with its marker (`+` create, `~` update, `-` delete, `-/+`
+/// replace) and its attribute changes, nested by path. Computed attributes without a
+/// value and [unknown](crate::types::unknown) values are shown as `(known after apply)`,
+/// sensitive attributes as `(sensitive)`, and a summary line closes the plan. `NoOp`
+/// changes are omitted.
+#[derive(Debug, Clone, Default)]
+pub struct PlanRenderer {
+    color: bool,
+    sensitive: HashMap<String, Vec<String>>,
+}
+
+/// Renders changes with the default [`PlanRenderer`].
+pub fn render_plan(changes: &[Change]) -> String {
+    PlanRenderer::new().render(changes)
+}
+
+impl PlanRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables ANSI colour codes.
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Marks attributes (JSON Pointers into the spec) of a resource type as sensitive.
+    pub fn sensitive(mut self, resource_type: &str, attributes: &[String]) -> Self {
+        let entry = self.sensitive.entry(resource_type.to_string()).or_default();
+        for attribute in attributes {
+            if !entry.contains(attribute) {
+                entry.push(attribute.clone());
+            }
+        }
+        self
+    }
+
+    /// Marks the attributes flagged as sensitive in the type's schema.
+    pub fn schema(self, resource_type: &str, schema: &Value) -> Self {
+        self.sensitive(resource_type, &sensitive_attributes(schema))
+    }
+
+    pub fn render(&self, changes: &[Change]) -> String {
+        let mut out = String::new();
+        for change in changes {
+            if change.change_type != ChangeType::NoOp {
+                self.render_change(&mut out, change);
+                out.push('\n');
+            }
+        }
+        out.push_str(&self.summary(changes));
+        out.push('\n');
+        out
+    }
+
+    /// The closing line, e.g. `Plan: 3 to add, 1 to change, 0 to destroy.` A replacement
+    /// counts as one addition and one destruction.
+    pub fn summary(&self, changes: &[Change]) -> String {
+        let (mut add, mut change, mut destroy) = (0, 0, 0);
+        for c in changes {
+            match c.change_type {
+                ChangeType::Create => add += 1,
+                ChangeType::Update => change += 1,
+                ChangeType::Delete => destroy += 1,
+                ChangeType::Replace => {
+                    add += 1;
+                    destroy += 1;
+                }
+                ChangeType::NoOp => {}
+            }
+        }
+        if add + change + destroy == 0 {
+            return self.bold("No changes. The current state matches the desired state.");
+        }
+        format!(
+            "{} {add} to add, {change} to change, {destroy} to destroy.",
+            self.bold("Plan:")
+        )
+    }
+
+    fn render_change(&self, out: &mut String, change: &Change) {
+        let (marker, action) = match change.change_type {
+            ChangeType::Create => ("+", "will be created"),
+            ChangeType::Update => ("~", "will be updated in-place"),
+            ChangeType::Delete => ("-", "will be destroyed"),
+            ChangeType::Replace => ("-/+", "must be replaced"),
+            ChangeType::NoOp => return,
+        };
+        out.push_str(&format!(
+            "  {} {action}\n",
+            self.bold(&format!("# {}", change.address))
+        ));
+        out.push_str(&format!(
+            "{}{} {} {{\n",
+            " ".repeat(3 - marker.len()),
+            self.paint(marker),
+            change.address
+        ));
+
+        let sensitive = self
+            .sensitive
+            .get(&change.address.resource_type)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let lines = self.attribute_changes(change);
+        let block = Block::build(&lines);
+        self.render_block(out, &block, 1, sensitive);
+        out.push_str("    }\n");
+    }
+
+    /// The change's attribute changes, computing them if the change has none, plus
+    /// computed attributes the provider fills in.
+    fn attribute_changes(&self, change: &Change) -> Vec<Line> {
+        let computed;
+        let attribute_changes = match change.attribute_changes.is_empty() {
+            true => {
+                computed = Differ::new().diff(change.before.as_ref(), change.after.as_ref());
+                &computed
+            }
+            false => &change.attribute_changes,
+        };
+        let mut lines: Vec<Line> = attribute_changes.iter().map(Line::from).collect();
+
+        if matches!(change.change_type, ChangeType::Create | ChangeType::Replace) {
+            let value_at = |resource: Option<&Resource>, path: &str| {
+                resource
+                    .and_then(|r| serde_json::to_value(&r.spec).ok())
+                    .and_then(|spec| spec.pointer(path).cloned())
+                    .filter(|v| !v.is_null())
+            };
+            for path in &change.computed_fields {
+                if lines.iter().any(|l| &l.path == path)
+                    || value_at(change.after.as_ref(), path).is_some()
+                {
+                    continue;
+                }
+                lines.push(Line {
+                    path: path.clone(),
+                    before: value_at(change.before.as_ref(), path).map(Shown::Value),
+                    after: Some(Shown::KnownAfterApply),
+                    force_new: false,
+                });
+            }
+            lines.sort_by(|a, b| a.path.cmp(&b.path));
+        }
+        lines
+    }
+
+    fn render_block(&self, out: &mut String, block: &Block, depth: usize, sensitive: &[String]) {
+        let indent = " ".repeat(4 * depth + 2);
+        // Sensitive nested objects are shown as a single masked attribute.
+        let masked = |entry: &Entry| match entry {
+            Entry::Block(_, path) => covered(sensitive, path),
+            Entry::Leaf(_) => true,
+        };
+        let width = block
+            .entries
+            .iter()
+            .filter_map(|(key, entry)| masked(entry).then_some(key.len()))
+            .max()
+            .unwrap_or(0);
+        for (key, entry) in &block.entries {
+            match entry {
+                Entry::Block(_, _) if masked(entry) => {
+                    out.push_str(&format!(
+                        "{indent}{} {key:<width$} = {REDACTED}\n",
+                        self.paint("~")
+                    ));
+                }
+                Entry::Block(nested, _) => {
+                    out.push_str(&format!("{indent}{} {key} {{\n", self.paint("~")));
+                    self.render_block(out, nested, depth + 1, sensitive);
+                    out.push_str(&format!("{indent}  }}\n"));
+                }
+                Entry::Leaf(line) => {
+                    let marker = match (&line.before, &line.after) {
+                        (None, _) => "+",
+                        (_, None) => "-",
+                        _ => "~",
+                    };
+                    let hidden = covered(sensitive, &line.path);
+                    let show = |shown: &Shown| match (shown, hidden) {
+                        (Shown::KnownAfterApply, _) => KNOWN_AFTER_APPLY.to_string(),
+                        (_, true) => REDACTED.to_string(),
+                        (Shown::Value(value), false) => {
+                            format_value(value, marker, &line.path, depth, sensitive, self)
+                        }
+                    };
+                    let value = match (&line.before, &line.after) {
+                        (Some(before), Some(after)) => {
+                            format!("{} -> {}", show(before), show(after))
+                        }
+                        (Some(value), None) | (None, Some(value)) => show(value),
+                        (None, None) => continue,
+                    };
+                    let comment = match line.force_new {
+                        true => format!(" {}", self.paint_with(RED, "# forces replacement")),
+                        false => String::new(),
+                    };
+                    out.push_str(&format!(
+                        "{indent}{} {key:<width$} = {value}{comment}\n",
+                        self.paint(marker)
+                    ));
+                }
+            }
+        }
+    }
+
+    fn paint(&self, marker: &str) -> String {
+        let color = match marker {
+            "+" => GREEN,
+            "~" => YELLOW,
+            "-" => RED,
+            "-/+" => return format!("{}/{}", self.paint("-"), self.paint("+")),
+            _ => return marker.to_string(),
+        };
+        self.paint_with(color, marker)
+    }
+
+    fn paint_with(&self, color: &str, text: &str) -> String {
+        match self.color {
+            true => format!("{color}{text}{RESET}"),
+            false => text.to_string(),
+        }
+    }
+
+    fn bold(&self, text: &str) -> String {
+        self.paint_with(BOLD, text)
+    }
+}
+
+/// A value as displayed.
+enum Shown {
+    Value(Value),
+    KnownAfterApply,
+}
+
+/// One attribute change to display.
+struct Line {
+    path: String,
+    before: Option<Shown>,
+    after: Option<Shown>,
+    force_new: bool,
+}
+
+/// Attribute changes nested by path, in order.
+#[derive(Default)]
+struct Block<'a> {
+    entries: Vec<(String, Entry<'a>)>,
+}
+
+enum Entry<'a> {
+    /// A nested object with changed attributes, and its path.
+    Block(Block<'a>, String),
+    Leaf(&'a Line),
+}
+
+impl<'a> Block<'a> {
+    fn build(lines: &'a [Line]) -> Self {
+        let mut root = Block::default();
+        for line in lines {
+            let segments = tokens(&line.path).unwrap_or_default();
+            let Some((last, parents)) = segments.split_last() else {
+                continue;
+            };
+            let mut block = &mut root;
+            for (depth, segment) in parents.iter().enumerate() {
+                let key = display_key(segment);
+                let position = block
+                    .entries
+                    .iter()
+                    .position(|(k, entry)| *k == key && matches!(entry, Entry::Block(..)));
+                let index = position.unwrap_or_else(|| {
+                    let path = pointer(&segments[..=depth]);
+                    block
+                        .entries
+                        .push((key, Entry::Block(Block::default(), path)));
+                    block.entries.len() - 1
+                });
+                block = match &mut block.entries[index].1 {
+                    Entry::Block(nested, _) => nested,
+                    Entry::Leaf(_) => unreachable!("position only matches blocks"),
+                };
+            }
+            block.entries.push((display_key(last), Entry::Leaf(line)));
+        }
+        root
+    }
+}
+
+/// Formats a value; objects are expanded over several lines with the given marker.
+fn format_value(
+    value: &Value,
+    marker: &str,
+    path: &str,
+    depth: usize,
+    sensitive: &[String],
+    renderer: &PlanRenderer,
+) -> String {
+    // Only a whole added or removed object is expanded; a modified one stays inline.
+    let Value::Object(map) = value else {
+        return inline(value);
+    };
+    if map.is_empty() || marker == "~" || is_unknown(value) {
+        return inline(value);
+    }
+    let indent = " ".repeat(4 * (depth + 1) + 2);
+    let width = map
+        .keys()
+        .map(|key| display_key(key).len())
+        .max()
+        .unwrap_or(0);
+    let mut out = String::from("{\n");
+    for (key, nested) in map {
+        let nested_path = child(path, key);
+        let key = display_key(key);
+        let shown = match covered(sensitive, &nested_path) {
+            true => REDACTED.to_string(),
+            false => format_value(nested, marker, &nested_path, depth + 1, sensitive, renderer),
+        };
+        out.push_str(&format!(
+            "{indent}{} {key:<width$} = {shown}\n",
+            renderer.paint(marker)
+        ));
+    }
+    out.push_str(&format!("{}}}", " ".repeat(4 * depth + 4)));
+    out
+}
+
+/// An attribute name as displayed: quoted unless it is a plain identifier, so keys
+/// holding dots or spaces read as one name.
+fn display_key(key: &str) -> String {
+    let plain = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    match plain {
+        true => key.to_string(),
+        false => Value::String(key.to_string()).to_string(),
+    }
+}
+
+/// Formats a value on one line, e.g. `["devs", "ops"]` or `{ "theme": "dark" }`.
+fn inline(value: &Value) -> String {
+    match value {
+        _ if is_unknown(value) => KNOWN_AFTER_APPLY.to_string(),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(inline).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(map) if map.is_empty() => "{}".to_string(),
+        Value::Object(map) => {
+            let members: Vec<String> = map
+                .iter()
+                .map(|(key, value)| format!("{}: {}", Value::String(key.clone()), inline(value)))
+                .collect();
+            format!("{{ {} }}", members.join(", "))
+        }
+        _ => value.to_string(),
+    }
+}
+
+impl From<&AttributeChange> for Line {
+    fn from(change: &AttributeChange) -> Self {
+        let shown = |value: &Value| match is_unknown(value) {
+            true => Shown::KnownAfterApply,
+            false => Shown::Value(value.clone()),
+        };
+        Self {
+            path: change.path.clone(),
+            before: change.before.as_ref().map(shown),
+            after: change.after.as_ref().map(shown),
+            force_new: change.force_new,
+        }
+    }
+}
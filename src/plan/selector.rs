@@ -0,0 +1,299 @@
+use crate::provider::PlanRequest;
+use crate::state::State;
+use crate::types::{Change, Resource, ResourceAddress};
+use crate::utils::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// One element of a glob component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Literal(char),
+    /// `?`: any single character.
+    One,
+    /// `*`: any sequence of characters, including none.
+    Any,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Glob(Vec<Token>);
+
+impl Glob {
+    fn literal(value: &str) -> Self {
+        Self(value.chars().map(Token::Literal).collect())
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        let chars: Vec<char> = value.chars().collect();
+        // Classic wildcard matching, backtracking to the most recent `*`.
+        let (mut t, mut c) = (0, 0);
+        let mut star: Option<(usize, usize)> = None;
+        while c < chars.len() {
+            match self.0.get(t) {
+                Some(Token::Any) => {
+                    star = Some((t, c));
+                    t += 1;
+                }
+                Some(Token::One) => {
+                    t += 1;
+                    c += 1;
+                }
+                Some(Token::Literal(l)) if *l == chars[c] => {
+                    t += 1;
+                    c += 1;
+                }
+                _ => match star {
+                    Some((star_t, star_c)) => {
+                        star = Some((star_t, star_c + 1));
+                        t = star_t + 1;
+                        c = star_c + 1;
+                    }
+                    None => return false,
+                },
+            }
+        }
+        self.0[t..].iter().all(|token| *token == Token::Any)
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, separator: char) -> fmt::Result {
+        for token in &self.0 {
+            match token {
+                Token::Any => f.write_str("*")?,
+                Token::One => f.write_str("?")?,
+                Token::Literal(c) => {
+                    if matches!(c, '\\' | '*' | '?') || *c == separator {
+                        f.write_str("\\")?;
+                    }
+                    write!(f, "{c}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Which namespaces a pattern matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum NamespacePattern {
+    /// `type.name`: any namespace, or none.
+    Any,
+    /// `type./name`: only addresses without a namespace.
+    Absent,
+    /// `type.namespace/name`: namespaces matching the glob.
+    Glob(Glob),
+}
+
+/// A glob over resource addresses, e.g. `client.demo/*`, `user.*` or `*.prod/admin-?`.
+///
+/// Patterns use the [`ResourceAddress`] string grammar, where `*` matches any sequence
+/// of characters and `?` a single character within a component. `\*` and `\?` match
+/// the literal characters. A pattern without a namespace matches any namespace, so
+/// `user.*` selects every user, while an empty namespace only matches addresses
+/// without one, so `user./*` selects the users outside any namespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressPattern {
+    resource_type: Glob,
+    namespace: NamespacePattern,
+    name: Glob,
+}
+
+impl AddressPattern {
+    /// A pattern matching exactly the given address.
+    pub fn exact(address: &ResourceAddress) -> Self {
+        Self {
+            resource_type: Glob::literal(&address.resource_type),
+            namespace: match &address.namespace {
+                Some(namespace) => NamespacePattern::Glob(Glob::literal(namespace)),
+                None => NamespacePattern::Absent,
+            },
+            name: Glob::literal(&address.name),
+        }
+    }
+
+    pub fn matches(&self, address: &ResourceAddress) -> bool {
+        self.resource_type.matches(&address.resource_type)
+            && self.name.matches(&address.name)
+            && match (&self.namespace, &address.namespace) {
+                (NamespacePattern::Any, _) => true,
+                (NamespacePattern::Absent, namespace) => namespace.is_none(),
+                (NamespacePattern::Glob(glob), Some(namespace)) => glob.matches(namespace),
+                (NamespacePattern::Glob(_), None) => false,
+            }
+    }
+}
+
+impl fmt::Display for AddressPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.resource_type.write(f, '.')?;
+        f.write_str(".")?;
+        match &self.namespace {
+            NamespacePattern::Any => {}
+            NamespacePattern::Absent => f.write_str("/")?,
+            NamespacePattern::Glob(namespace) => {
+                namespace.write(f, '/')?;
+                f.write_str("/")?;
+            }
+        }
+        self.name.write(f, '/')
+    }
+}
+
+impl FromStr for AddressPattern {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| Error::InvalidAddress {
+            input: input.to_string(),
+            reason: reason.to_string(),
+        };
+
+        // Tokens paired with the unescaped separator they represent, if any.
+        let mut tokens: Vec<(Token, Option<char>)> = vec![];
+        let mut iter = input.chars();
+        while let Some(c) = iter.next() {
+            let token = match c {
+                '\\' => match iter.next() {
+                    Some(escaped @ ('\\' | '.' | '/' | '*' | '?')) => {
+                        (Token::Literal(escaped), None)
+                    }
+                    Some(_) => {
+                        return Err(invalid(
+                            "only `\\\\`, `\\.`, `\\/`, `\\*` and `\\?` can be escaped",
+                        ))
+                    }
+                    None => return Err(invalid("trailing backslash")),
+                },
+                '*' => (Token::Any, None),
+                '?' => (Token::One, None),
+                '.' | '/' => (Token::Literal(c), Some(c)),
+                _ => (Token::Literal(c), None),
+            };
+            tokens.push(token);
+        }
+
+        let glob = |tokens: &[(Token, Option<char>)], component: &str| {
+            if tokens.is_empty() {
+                return Err(invalid(&format!("the {component} is empty")));
+            }
+            Ok(Glob(tokens.iter().map(|(t, _)| t.clone()).collect()))
+        };
+
+        let dot = tokens
+            .iter()
+            .position(|(_, sep)| *sep == Some('.'))
+            .ok_or_else(|| invalid("expected `type.name` or `type.namespace/name`"))?;
+        let rest = &tokens[dot + 1..];
+        let slashes: Vec<_> = rest
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, sep))| *sep == Some('/'))
+            .map(|(i, _)| i)
+            .collect();
+        let (namespace, name) = match slashes.as_slice() {
+            [] => (NamespacePattern::Any, glob(rest, "name")?),
+            [0] => (NamespacePattern::Absent, glob(&rest[1..], "name")?),
+            [slash] => (
+                NamespacePattern::Glob(glob(&rest[..*slash], "namespace")?),
+                glob(&rest[slash + 1..], "name")?,
+            ),
+            _ => return Err(invalid("more than one `/`; escape `/` in names as `\\/`")),
+        };
+
+        Ok(Self {
+            resource_type: glob(&tokens[..dot], "resource type")?,
+            namespace,
+            name,
+        })
+    }
+}
+
+impl Serialize for AddressPattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for AddressPattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        pattern.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Selects the resources targeted by an operation (`-target`).
+///
+/// An address is selected if it matches any `include` pattern (or `include` is
+/// empty) and no `exclude` pattern. The empty selector selects everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceSelector {
+    #[serde(default)]
+    pub include: Vec<AddressPattern>,
+    #[serde(default)]
+    pub exclude: Vec<AddressPattern>,
+}
+
+impl ResourceSelector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn include(mut self, pattern: AddressPattern) -> Self {
+        self.include.push(pattern);
+        self
+    }
+
+    pub fn exclude(mut self, pattern: AddressPattern) -> Self {
+        self.exclude.push(pattern);
+        self
+    }
+
+    /// True if the selector selects every address.
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    pub fn matches(&self, address: &ResourceAddress) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| p.matches(address)))
+            && !self.exclude.iter().any(|p| p.matches(address))
+    }
+
+    pub fn filter_resources(&self, resources: Vec<Resource>) -> Vec<Resource> {
+        resources
+            .into_iter()
+            .filter(|r| self.matches(&r.address))
+            .collect()
+    }
+
+    /// Restricts the desired and current state of a plan to the selected resources, so
+    /// unselected resources are neither created, updated nor deleted. Data sources
+    /// are kept as inputs.
+    pub fn filter_plan_request(&self, request: PlanRequest) -> PlanRequest {
+        PlanRequest {
+            desired_state: self.filter_resources(request.desired_state),
+            current_state: self.filter_resources(request.current_state),
+            ..request
+        }
+    }
+
+    /// Returns a copy of the state holding only the selected resources.
+    pub fn filter_state(&self, state: &State) -> State {
+        State {
+            version: state.version,
+            resources: state
+                .resources
+                .iter()
+                .filter(|(_, resource)| self.matches(&resource.address))
+                .map(|(key, resource)| (key.clone(), resource.clone()))
+                .collect(),
+            metadata: state.metadata.clone(),
+        }
+    }
+
+    pub fn filter_changes(&self, changes: Vec<Change>) -> Vec<Change> {
+        changes
+            .into_iter()
+            .filter(|c| self.matches(&c.address))
+            .collect()
+    }
+}
@@ -0,0 +1,373 @@
+use super::data_source::{DataSourceHandler, ErasedDataSource, ErasedSource};
+use super::resource::{Erased, ErasedHandler, IamResource, ResourceHandler};
+use super::{
+    config_schema, ApplyError, ApplyRequest, ApplyResponse, ApplyResult, ConfigureRequest,
+    ConfigureResponse, DataSource, DataSourceDescriptor, PlanRequest, PlanResponse, Provider,
+    ProviderCapabilities, ProviderContext, ProviderMetadata, ReadDataSourceRequest,
+    ReadDataSourceResponse, RefreshRequest, RefreshResponse, RefreshedResource,
+};
+use crate::plan::Planner;
+use crate::types::unknown::unresolved_unknowns;
+use crate::types::{Change, ChangeType, ReplaceOrder, Resource};
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeMap, HashMap};
+
+type ConfigureFn = Box<dyn Fn(&ConfigureRequest) -> anyhow::Result<()> + Send + Sync>;
+
+struct ConfigHook {
+    schema: serde_json::Value,
+    apply: ConfigureFn,
+}
+
+/// Builds a [`Provider`] from per-type [`ResourceHandler`]s.
+pub struct ProviderBuilder {
+    metadata: ProviderMetadata,
+    handlers: BTreeMap<String, Box<dyn ErasedHandler>>,
+    data_sources: BTreeMap<String, Box<dyn ErasedDataSource>>,
+    config: Option<ConfigHook>,
+}
+
+impl ProviderBuilder {
+    pub fn new(metadata: ProviderMetadata) -> Self {
+        Self {
+            metadata,
+            handlers: BTreeMap::new(),
+            data_sources: BTreeMap::new(),
+            config: None,
+        }
+    }
+
+    /// Requires a typed configuration before `plan`/`apply`. The schema of `C` is
+    /// published in the capabilities and `on_configure` receives the decoded config.
+    pub fn configure<C, F>(mut self, on_configure: F) -> Self
+    where
+        C: DeserializeOwned + JsonSchema,
+        F: Fn(C) -> anyhow::Result<()> + Send + Sync + 'static,
+    {
+        self.config = Some(ConfigHook {
+            schema: config_schema::<C>(),
+            apply: Box::new(move |request| on_configure(request.decode::<C>()?)),
+        });
+        self
+    }
+
+    /// Registers the handler for a resource type, replacing any previous one.
+    pub fn resource<H: ResourceHandler>(mut self, resource_type: &str, handler: H) -> Self {
+        let _ = self
+            .handlers
+            .insert(resource_type.to_string(), Box::new(Erased::new(handler)));
+        self
+    }
+
+    /// Registers the handler under its spec's [`IamResource::RESOURCE_TYPE`], publishing
+    /// the annotated [`IamResource::resource_schema`] and the declared schema version.
+    pub fn typed_resource<H>(mut self, handler: H) -> Self
+    where
+        H: ResourceHandler,
+        H::Spec: IamResource,
+    {
+        let _ = self.handlers.insert(
+            H::Spec::RESOURCE_TYPE.to_string(),
+            Box::new(Erased::typed(handler)),
+        );
+        self
+    }
+
+    /// Registers the handler for a data source type, replacing any previous one.
+    pub fn data_source<H: DataSourceHandler>(mut self, data_source_type: &str, handler: H) -> Self {
+        let _ = self.data_sources.insert(
+            data_source_type.to_string(),
+            Box::new(ErasedSource(handler)),
+        );
+        self
+    }
+
+    pub fn build(self) -> HandlerProvider {
+        HandlerProvider {
+            metadata: self.metadata,
+            handlers: self.handlers,
+            data_sources: self.data_sources,
+            config: self.config,
+        }
+    }
+}
+
+/// A provider whose operations are dispatched to registered [`ResourceHandler`]s
+/// and [`DataSourceHandler`]s.
+pub struct HandlerProvider {
+    metadata: ProviderMetadata,
+    handlers: BTreeMap<String, Box<dyn ErasedHandler>>,
+    data_sources: BTreeMap<String, Box<dyn ErasedDataSource>>,
+    config: Option<ConfigHook>,
+}
+
+impl HandlerProvider {
+    fn handler(&self, resource: &Resource) -> anyhow::Result<&dyn ErasedHandler> {
+        self.handlers
+            .get(&resource.address.resource_type)
+            .map(|h| h.as_ref())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unsupported resource type '{}' ({})",
+                    resource.address.resource_type,
+                    resource.address
+                )
+            })
+    }
+
+    /// Planner honouring each handler's force-new attributes (from its descriptor and
+    /// schema) and replace order.
+    fn planner(&self) -> Planner {
+        self.handlers
+            .iter()
+            .fold(Planner::new(), |planner, (resource_type, handler)| {
+                let descriptor = handler.descriptor(resource_type);
+                planner
+                    .schema(resource_type, &handler.schema())
+                    .force_new(resource_type, &descriptor.force_replace_attributes)
+                    .replace_order(resource_type, descriptor.replace_order)
+            })
+    }
+
+    async fn apply_change(&self, change: &Change) -> anyhow::Result<ApplyResult> {
+        let missing = |side: &str| {
+            anyhow::anyhow!(
+                "{:?} change for {} has no '{side}' resource",
+                change.change_type,
+                change.address
+            )
+        };
+        if let Some(unresolved) = unresolved_unknowns(std::slice::from_ref(change)).first() {
+            anyhow::bail!("{}", unresolved.summary);
+        }
+
+        let (resource, remote_id) = match change.change_type {
+            ChangeType::NoOp => {
+                return Ok(ApplyResult::skipped(
+                    change.address.clone(),
+                    change.after.clone(),
+                ))
+            }
+            ChangeType::Create => {
+                let after = change.after.as_ref().ok_or_else(|| missing("after"))?;
+                let handler = self.handler(after)?;
+                let created = handler.create(after).await?;
+                let remote_id = handler.remote_id(&created);
+                (Some(created), remote_id)
+            }
+            ChangeType::Update => {
+                let before = change.before.as_ref().ok_or_else(|| missing("before"))?;
+                let after = change.after.as_ref().ok_or_else(|| missing("after"))?;
+                let handler = self.handler(after)?;
+                let updated = handler.update(before, after).await?;
+                let remote_id = handler.remote_id(&updated);
+                (Some(updated), remote_id)
+            }
+            ChangeType::Delete => {
+                let before = change.before.as_ref().ok_or_else(|| missing("before"))?;
+                let handler = self.handler(before)?;
+                handler.delete(before).await?;
+                return Ok(ApplyResult {
+                    remote_id: handler.remote_id(before),
+                    ..ApplyResult::deleted(change.address.clone())
+                });
+            }
+            ChangeType::Replace => {
+                let before = change.before.as_ref().ok_or_else(|| missing("before"))?;
+                let after = change.after.as_ref().ok_or_else(|| missing("after"))?;
+                let handler = self.handler(after)?;
+                // Once one step succeeded, a failure of the other is reported with the
+                // remote object's new standing, so the engine does not lose track of it.
+                let created = match change.replace_order.unwrap_or_default() {
+                    ReplaceOrder::DestroyBeforeCreate => {
+                        handler.delete(before).await?;
+                        match handler.create(after).await {
+                            Ok(created) => created,
+                            Err(e) => {
+                                let error = format!(
+                                    "Deleted {} but failed to create its replacement: {e}",
+                                    change.address
+                                );
+                                return Ok(ApplyResult {
+                                    deleted: true,
+                                    ..ApplyResult::failed(
+                                        change.address.clone(),
+                                        ApplyError::new(error),
+                                    )
+                                });
+                            }
+                        }
+                    }
+                    ReplaceOrder::CreateBeforeDestroy => {
+                        let created = handler.create(after).await?;
+                        if let Err(e) = handler.delete(before).await {
+                            let previous = handler
+                                .remote_id(before)
+                                .map(|id| format!(" (remote id {id})"))
+                                .unwrap_or_default();
+                            let error = format!(
+                                "Created the replacement of {} but failed to delete the \
+                                 previous object{previous}: {e}",
+                                change.address
+                            );
+                            return Ok(ApplyResult {
+                                remote_id: handler.remote_id(&created),
+                                resource: Some(created),
+                                ..ApplyResult::failed(
+                                    change.address.clone(),
+                                    ApplyError::new(error),
+                                )
+                            });
+                        }
+                        created
+                    }
+                };
+                let remote_id = handler.remote_id(&created);
+                (Some(created), remote_id)
+            }
+        };
+
+        Ok(ApplyResult {
+            remote_id,
+            ..ApplyResult::succeeded(change.address.clone(), resource)
+        })
+    }
+}
+
+#[async_trait]
+impl Provider for HandlerProvider {
+    fn metadata(&self) -> ProviderMetadata {
+        self.metadata.clone()
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            supported_resources: self.handlers.keys().cloned().collect(),
+            can_import: false,
+            can_validate: true,
+            can_refresh: true,
+            config_schema: self.config.as_ref().map(|c| c.schema.clone()),
+            can_discover: false,
+            resource_types: self
+                .handlers
+                .iter()
+                .map(|(resource_type, handler)| handler.descriptor(resource_type))
+                .collect(),
+            data_sources: self
+                .data_sources
+                .iter()
+                .map(|(data_source_type, handler)| DataSourceDescriptor {
+                    data_source_type: data_source_type.clone(),
+                    query_schema: Some(handler.query_schema()),
+                    result_schema: Some(handler.result_schema()),
+                })
+                .collect(),
+        }
+    }
+
+    fn schemas(&self) -> HashMap<String, serde_json::Value> {
+        self.handlers
+            .iter()
+            .map(|(resource_type, handler)| (resource_type.clone(), handler.schema()))
+            .collect()
+    }
+
+    async fn configure(&self, request: ConfigureRequest) -> anyhow::Result<ConfigureResponse> {
+        if let Some(config) = &self.config {
+            (config.apply)(&request)?;
+        }
+        Ok(ConfigureResponse::default())
+    }
+
+    /// Plans the desired state once its `${data...}` references are resolved from the
+    /// request's data sources.
+    async fn plan(&self, request: PlanRequest) -> anyhow::Result<PlanResponse> {
+        for resource in request.desired_state.iter().chain(&request.current_state) {
+            let _ = self.handler(resource)?;
+        }
+        let desired_state = request
+            .desired_state
+            .iter()
+            .map(|resource| request.interpolate(resource))
+            .collect::<crate::utils::Result<Vec<_>>>()?;
+        let changes = self.planner().plan(&desired_state, &request.current_state);
+        Ok(PlanResponse {
+            diagnostics: self.capabilities().check_changes(&changes),
+            changes,
+        })
+    }
+
+    async fn apply(&self, request: ApplyRequest) -> anyhow::Result<ApplyResponse> {
+        self.apply_with_context(&ProviderContext::default(), request)
+            .await
+    }
+
+    /// Applies the changes in order, reporting progress after each one. Once the
+    /// request is cancelled the remaining changes are skipped.
+    async fn apply_with_context(
+        &self,
+        ctx: &ProviderContext,
+        request: ApplyRequest,
+    ) -> anyhow::Result<ApplyResponse> {
+        let total = request.changes.len();
+        let mut results = vec![];
+        for (done, change) in request.changes.iter().enumerate() {
+            if ctx.is_cancelled() {
+                results.push(
+                    ApplyResult::skipped(change.address.clone(), None)
+                        .with_warning("Skipped: request was cancelled"),
+                );
+                continue;
+            }
+
+            let result = match self.apply_change(change).await {
+                Ok(result) => result,
+                Err(e) => {
+                    ApplyResult::failed(change.address.clone(), ApplyError::new(e.to_string()))
+                }
+            };
+            results.push(result);
+            ctx.progress().report(
+                format!("Applied {} ({}/{total})", change.address, done + 1),
+                Some(((done + 1) * 100 / total) as u8),
+            );
+        }
+        Ok(ApplyResponse::from_results(results))
+    }
+
+    async fn refresh(&self, request: RefreshRequest) -> anyhow::Result<RefreshResponse> {
+        let mut resources = vec![];
+        for resource in request.resources {
+            let refreshed = match self.handler(&resource)?.read(&resource).await? {
+                Some(live) => RefreshedResource::Present { resource: live },
+                None => RefreshedResource::Gone {
+                    address: resource.address,
+                },
+            };
+            resources.push(refreshed);
+        }
+        Ok(RefreshResponse { resources })
+    }
+
+    async fn read_data_source(
+        &self,
+        request: ReadDataSourceRequest,
+    ) -> anyhow::Result<ReadDataSourceResponse> {
+        let address = request.address;
+        let handler = self
+            .data_sources
+            .get(&address.resource_type)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unsupported data source type '{}' ({address})",
+                    address.resource_type
+                )
+            })?;
+        let values = handler.read(&address, &request.query).await?;
+        Ok(ReadDataSourceResponse {
+            data_source: DataSource { address, values },
+        })
+    }
+}
@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Notify};
+use tokio::time::Instant;
+
+/// Cooperative cancellation signal shared between the server and a running request.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<CancellationState>,
+}
+
+#[derive(Debug, Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Completes once the token is cancelled.
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// A progress update for a running request, sent to the engine as a
+/// `$/progress` notification.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    pub request_id: serde_json::Value,
+    pub message: String,
+    /// Completion percentage (0-100), if known.
+    #[serde(default)]
+    pub percentage: Option<u8>,
+}
+
+/// Sends progress updates for one request. Reports are dropped when the request
+/// was not received over a transport that forwards them.
+#[derive(Debug, Clone, Default)]
+pub struct ProgressReporter {
+    request_id: serde_json::Value,
+    sender: Option<mpsc::UnboundedSender<Progress>>,
+}
+
+impl ProgressReporter {
+    pub fn new(request_id: serde_json::Value, sender: mpsc::UnboundedSender<Progress>) -> Self {
+        Self {
+            request_id,
+            sender: Some(sender),
+        }
+    }
+
+    pub fn report(&self, message: impl Into<String>, percentage: Option<u8>) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(Progress {
+                request_id: self.request_id.clone(),
+                message: message.into(),
+                percentage: percentage.map(|p| p.min(100)),
+            });
+        }
+    }
+}
+
+/// Per-request facilities passed to the `*_with_context` provider methods.
+#[derive(Debug, Clone)]
+pub struct ProviderContext {
+    request_id: serde_json::Value,
+    method: String,
+    deadline: Option<Instant>,
+    cancellation: CancellationToken,
+    span: tracing::Span,
+    progress: ProgressReporter,
+}
+
+impl Default for ProviderContext {
+    fn default() -> Self {
+        Self::new(serde_json::Value::Null, "")
+    }
+}
+
+impl ProviderContext {
+    /// Creates a context without deadline or progress forwarding, with a tracing span
+    /// carrying the method and request id.
+    pub fn new(request_id: serde_json::Value, method: &str) -> Self {
+        let span = tracing::info_span!("provider_request", method, request_id = %request_id);
+        Self {
+            request_id,
+            method: method.to_string(),
+            deadline: None,
+            cancellation: CancellationToken::new(),
+            span,
+            progress: ProgressReporter::default(),
+        }
+    }
+
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    pub fn with_progress(mut self, progress: ProgressReporter) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    pub fn request_id(&self) -> &serde_json::Value {
+        &self.request_id
+    }
+
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Time left until the deadline; `None` without a deadline.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn cancellation(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    /// True once the engine cancelled the request or its deadline passed.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled() || self.remaining() == Some(Duration::ZERO)
+    }
+
+    /// Returns an error if the request has been cancelled, for use with `?` between steps.
+    pub fn check_cancelled(&self) -> anyhow::Result<()> {
+        if self.is_cancelled() {
+            anyhow::bail!(
+                "Request {} ({}) was cancelled",
+                self.request_id,
+                self.method
+            );
+        }
+        Ok(())
+    }
+
+    /// Span scoped to the request; enter it or use it as a parent for provider logs.
+    pub fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+
+    pub fn progress(&self) -> &ProgressReporter {
+        &self.progress
+    }
+}
@@ -0,0 +1,63 @@
+use crate::types::ResourceAddress;
+use crate::utils::encode_spec;
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Typed lookup of an object the workspace does not manage, e.g. an existing realm
+/// or a built-in role.
+///
+/// Register implementations with [`ProviderBuilder::data_source`](super::ProviderBuilder::data_source);
+/// the query and result schemas are published in the capabilities.
+#[async_trait]
+pub trait DataSourceHandler: Send + Sync + 'static {
+    /// Arguments identifying the object, decoded from `ReadDataSourceRequest::query`.
+    type Query: DeserializeOwned + JsonSchema + Send + 'static;
+    /// Attributes of the object, returned as `DataSource::values`.
+    type Output: Serialize + JsonSchema + Send + 'static;
+
+    async fn read(
+        &self,
+        address: &ResourceAddress,
+        query: Self::Query,
+    ) -> anyhow::Result<Self::Output>;
+}
+
+/// Object-safe view of a [`DataSourceHandler`] operating on untyped values.
+#[async_trait]
+pub(crate) trait ErasedDataSource: Send + Sync {
+    fn query_schema(&self) -> serde_json::Value;
+    fn result_schema(&self) -> serde_json::Value;
+    async fn read(
+        &self,
+        address: &ResourceAddress,
+        query: &HashMap<String, serde_json::Value>,
+    ) -> anyhow::Result<HashMap<String, serde_json::Value>>;
+}
+
+pub(crate) struct ErasedSource<H>(pub(crate) H);
+
+#[async_trait]
+impl<H: DataSourceHandler> ErasedDataSource for ErasedSource<H> {
+    fn query_schema(&self) -> serde_json::Value {
+        serde_json::to_value(schemars::schema_for!(H::Query)).unwrap_or_default()
+    }
+
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::to_value(schemars::schema_for!(H::Output)).unwrap_or_default()
+    }
+
+    async fn read(
+        &self,
+        address: &ResourceAddress,
+        query: &HashMap<String, serde_json::Value>,
+    ) -> anyhow::Result<HashMap<String, serde_json::Value>> {
+        let query = serde_json::to_value(query)?;
+        let query = serde_json::from_value(query)
+            .map_err(|e| anyhow::anyhow!("Invalid query for data source {address}: {e}"))?;
+        let output = self.0.read(address, query).await?;
+        Ok(encode_spec(address, &output)?)
+    }
+}
@@ -0,0 +1,144 @@
+use super::ProviderCapabilities;
+use crate::plan::changed_attributes;
+use crate::types::{Change, ChangeType, Diagnostic, ReplaceOrder};
+use serde::{Deserialize, Serialize};
+
+/// Operations and attribute constraints supported by a single resource type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceTypeDescriptor {
+    pub resource_type: String,
+    #[serde(default = "default_true")]
+    pub create: bool,
+    #[serde(default = "default_true")]
+    pub read: bool,
+    #[serde(default = "default_true")]
+    pub update: bool,
+    #[serde(default = "default_true")]
+    pub delete: bool,
+    #[serde(default)]
+    pub import: bool,
+    /// Version of the resource schema; `0` when the type is unversioned.
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Spec attributes (JSON Pointers) that can never change once the resource exists.
+    #[serde(default)]
+    pub immutable_attributes: Vec<String>,
+    /// Spec attributes (JSON Pointers) whose change requires destroying and recreating
+    /// the resource.
+    #[serde(default)]
+    pub force_replace_attributes: Vec<String>,
+    /// Step order used when the planner replaces resources of this type.
+    #[serde(default)]
+    pub replace_order: ReplaceOrder,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl ResourceTypeDescriptor {
+    /// A descriptor supporting create, read, update and delete, but not import.
+    pub fn new(resource_type: &str) -> Self {
+        Self {
+            resource_type: resource_type.to_string(),
+            create: true,
+            read: true,
+            update: true,
+            delete: true,
+            import: false,
+            schema_version: 0,
+            immutable_attributes: vec![],
+            force_replace_attributes: vec![],
+            replace_order: ReplaceOrder::default(),
+        }
+    }
+
+    /// Checks a single change against this descriptor.
+    pub fn check_change(&self, change: &Change) -> Vec<Diagnostic> {
+        let error = |summary: String, code: &str| {
+            Diagnostic::error(summary)
+                .with_address(change.address.clone())
+                .with_code(code)
+        };
+        let unsupported = |operation: &str| {
+            error(
+                format!(
+                    "Resource type '{}' does not support {operation}",
+                    self.resource_type
+                ),
+                "UNSUPPORTED_OPERATION",
+            )
+        };
+
+        let operation = match change.change_type {
+            ChangeType::Create if !self.create => Some("create"),
+            ChangeType::Update if !self.update => Some("update"),
+            ChangeType::Delete if !self.delete => Some("delete"),
+            ChangeType::Replace if !(self.create && self.delete) => Some("replace"),
+            _ => None,
+        };
+        let mut diagnostics: Vec<Diagnostic> = operation.map(unsupported).into_iter().collect();
+
+        // Immutable attributes can never change; force-replace attributes may only
+        // change through a replacement.
+        let constraints = match change.change_type {
+            ChangeType::Update => vec![
+                (
+                    &self.immutable_attributes,
+                    "cannot be changed",
+                    "IMMUTABLE_ATTRIBUTE",
+                ),
+                (
+                    &self.force_replace_attributes,
+                    "requires replacing the resource when changed",
+                    "REPLACE_REQUIRED",
+                ),
+            ],
+            ChangeType::Replace => vec![(
+                &self.immutable_attributes,
+                "cannot be changed",
+                "IMMUTABLE_ATTRIBUTE",
+            )],
+            _ => vec![],
+        };
+        if let (Some(before), Some(after)) = (&change.before, &change.after) {
+            for (attributes, problem, code) in constraints {
+                for attribute in changed_attributes(attributes, before, after) {
+                    diagnostics.push(
+                        error(format!("Attribute '{attribute}' {problem}"), code)
+                            .with_attribute(format!("spec{attribute}")),
+                    );
+                }
+            }
+        }
+        diagnostics
+    }
+}
+
+impl ProviderCapabilities {
+    pub fn descriptor(&self, resource_type: &str) -> Option<&ResourceTypeDescriptor> {
+        self.resource_types
+            .iter()
+            .find(|d| d.resource_type == resource_type)
+    }
+
+    /// Checks planned changes against the advertised capabilities, so unsupported
+    /// operations are reported before the provider is asked to apply them.
+    /// Types without a descriptor are only checked against `supported_resources`.
+    pub fn check_changes(&self, changes: &[Change]) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        for change in changes {
+            let resource_type = &change.address.resource_type;
+            if !self.supported_resources.contains(resource_type) {
+                diagnostics.push(
+                    Diagnostic::error(format!("Unsupported resource type '{resource_type}'"))
+                        .with_address(change.address.clone())
+                        .with_code("UNSUPPORTED_RESOURCE_TYPE"),
+                );
+            } else if let Some(descriptor) = self.descriptor(resource_type) {
+                diagnostics.extend(descriptor.check_change(change));
+            }
+        }
+        diagnostics
+    }
+}
@@ -1,6 +1,26 @@
-use crate::types::{Change, Resource, ResourceAddress};
+pub mod builder;
+pub mod context;
+pub mod data_source;
+pub mod descriptor;
+pub mod resource;
+
+pub use builder::{HandlerProvider, ProviderBuilder};
+pub use context::{CancellationToken, Progress, ProgressReporter, ProviderContext};
+pub use data_source::DataSourceHandler;
+pub use descriptor::ResourceTypeDescriptor;
+pub use resource::{IamResource, ResourceHandler};
+
+#[cfg(feature = "derive")]
+pub use iamctl_rust_sdk_macros::IamResource;
+
+use crate::state::State;
+use crate::types::{Change, Diagnostic, Resource, ResourceAddress};
+use crate::utils::interpolate::{interpolate_spec, DATA_PREFIX};
 use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderMetadata {
@@ -16,18 +36,120 @@ pub struct ProviderCapabilities {
     pub supported_resources: Vec<String>,
     pub can_import: bool,
     pub can_validate: bool,
+    #[serde(default)]
+    pub can_refresh: bool,
+    /// JSON schema of the provider configuration. When set, operations that reach the
+    /// remote system are rejected until `configure` has succeeded.
+    #[serde(default)]
+    pub config_schema: Option<serde_json::Value>,
+    #[serde(default)]
+    pub can_discover: bool,
+    /// Per-type operations and attribute constraints. Types listed in
+    /// `supported_resources` without a descriptor are assumed to support CRUD.
+    #[serde(default)]
+    pub resource_types: Vec<ResourceTypeDescriptor>,
+    /// Read-only data sources the provider can look up.
+    #[serde(default)]
+    pub data_sources: Vec<DataSourceDescriptor>,
+}
+
+/// A data source type and the schemas of its query and result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataSourceDescriptor {
+    pub data_source_type: String,
+    #[serde(default)]
+    pub query_schema: Option<serde_json::Value>,
+    #[serde(default)]
+    pub result_schema: Option<serde_json::Value>,
+}
+
+/// Derives the JSON schema to publish as `ProviderCapabilities::config_schema`.
+pub fn config_schema<T: JsonSchema>() -> serde_json::Value {
+    serde_json::to_value(schemars::schema_for!(T)).unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigureRequest {
+    pub config: serde_json::Value,
 }
 
+impl ConfigureRequest {
+    /// Decodes the configuration into the provider's typed config struct.
+    pub fn decode<T: DeserializeOwned>(&self) -> crate::utils::Result<T> {
+        serde_json::from_value(self.config.clone()).map_err(|e| {
+            crate::utils::Error::Config(format!("Invalid provider configuration: {e}"))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigureResponse {}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlanRequest {
     pub workspace_path: String,
     pub desired_state: Vec<Resource>,
     pub current_state: Vec<Resource>,
+    /// Data sources read before planning, available as inputs to the managed resources.
+    #[serde(default)]
+    pub data_sources: Vec<DataSource>,
+}
+
+impl PlanRequest {
+    /// Looks up a data source result by its address.
+    pub fn data_source(&self, address: &ResourceAddress) -> Option<&DataSource> {
+        self.data_sources.iter().find(|d| &d.address == address)
+    }
+
+    /// Replaces `${data.<type>.<name>.<attribute>}` references in the resource's spec
+    /// strings (`${data.<type>.<namespace>/<name>.<attribute>}` for namespaced data
+    /// sources) with values of the request's data sources, following the rules of
+    /// [`Variables::interpolate`](crate::config::Variables::interpolate). Other
+    /// expressions are kept as written.
+    pub fn interpolate(&self, resource: &Resource) -> crate::utils::Result<Resource> {
+        interpolate_spec(resource, None, &|expression| {
+            let Some(reference) = expression.text.strip_prefix(DATA_PREFIX) else {
+                return Ok(None);
+            };
+            let invalid = |reason: String| crate::utils::Error::Interpolation {
+                address: Box::new(resource.address.clone()),
+                attribute: expression.attribute.to_string(),
+                reason,
+            };
+            let (data_source, attribute) = reference
+                .rsplit_once('.')
+                .filter(|(_, attribute)| !attribute.is_empty())
+                .ok_or_else(|| {
+                    invalid(format!(
+                        "expected '${{data.<type>.<name>.<attribute>}}', got '${{{}}}'",
+                        expression.text
+                    ))
+                })?;
+            let data_source: ResourceAddress = data_source.parse().map_err(|e| {
+                invalid(format!(
+                    "invalid data source in '${{{}}}': {e}",
+                    expression.text
+                ))
+            })?;
+            let value = self
+                .data_source(&data_source)
+                .ok_or_else(|| invalid(format!("data source {data_source} was not read")))?
+                .get(attribute)
+                .ok_or_else(|| {
+                    invalid(format!(
+                        "data source {data_source} has no attribute '{attribute}'"
+                    ))
+                })?;
+            Ok(Some(value.clone()))
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlanResponse {
     pub changes: Vec<Change>,
+    #[serde(default)]
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,7 +160,211 @@ pub struct ApplyRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApplyResponse {
     pub successful_addresses: Vec<ResourceAddress>,
-    pub failed_addresses: Vec<(ResourceAddress, String)>,
+    /// Errors for failed changes and warnings raised while applying.
+    #[serde(default)]
+    pub diagnostics: Vec<Diagnostic>,
+    /// Per-change outcomes, in the order of `ApplyRequest::changes`.
+    #[serde(default)]
+    pub results: Vec<ApplyResult>,
+}
+
+impl ApplyResponse {
+    /// Builds a response from per-change results, deriving the successful addresses and
+    /// the diagnostics. Skipped changes are reported as successful.
+    pub fn from_results(results: Vec<ApplyResult>) -> Self {
+        let mut successful_addresses = vec![];
+        let mut diagnostics = vec![];
+        for result in &results {
+            if result.status == ApplyStatus::Failed {
+                diagnostics.push(result.error_diagnostic());
+            } else {
+                successful_addresses.push(result.address.clone());
+            }
+            diagnostics.extend(
+                result.warnings.iter().map(|warning| {
+                    Diagnostic::warning(warning).with_address(result.address.clone())
+                }),
+            );
+        }
+        Self {
+            successful_addresses,
+            diagnostics,
+            results,
+        }
+    }
+
+    /// Addresses with at least one error diagnostic.
+    pub fn failed_addresses(&self) -> Vec<&ResourceAddress> {
+        let mut failed: Vec<&ResourceAddress> = vec![];
+        for address in self
+            .diagnostics
+            .iter()
+            .filter(|d| d.is_error())
+            .filter_map(|d| d.address.as_ref())
+        {
+            if !failed.contains(&address) {
+                failed.push(address);
+            }
+        }
+        failed
+    }
+
+    pub fn result_for(&self, address: &ResourceAddress) -> Option<&ApplyResult> {
+        self.results.iter().find(|r| &r.address == address)
+    }
+
+    /// Records the outcomes in `state`: returned resources replace their entries and
+    /// deleted ones (`ApplyResult::deleted`) are removed. Failed changes leave the state
+    /// untouched, unless they still created or deleted the remote object.
+    ///
+    /// A successful change that neither returned its resource nor reports a delete
+    /// leaves its entry alone too; a warning for each is returned.
+    pub fn update_state(&self, state: &mut State) -> Vec<Diagnostic> {
+        let mut warnings = vec![];
+        for result in &self.results {
+            let key = result.address.to_string();
+            match (&result.status, &result.resource) {
+                (_, Some(resource)) => {
+                    let _ = state.resources.insert(key, resource.clone());
+                }
+                (_, None) if result.deleted => {
+                    let _ = state.resources.remove(&key);
+                }
+                (ApplyStatus::Succeeded, None) => warnings.push(
+                    Diagnostic::warning(
+                        "Apply succeeded without returning the resource; its state was left \
+                         unchanged",
+                    )
+                    .with_address(result.address.clone()),
+                ),
+                (ApplyStatus::Failed | ApplyStatus::Skipped, None) => {}
+            }
+        }
+        warnings
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApplyStatus {
+    Succeeded,
+    Failed,
+    /// Nothing had to be done, e.g. for `ChangeType::NoOp`.
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApplyError {
+    pub message: String,
+    /// Provider-specific error code (e.g. an HTTP status or API error name).
+    #[serde(default)]
+    pub code: Option<String>,
+    /// Whether retrying the change may succeed.
+    #[serde(default)]
+    pub retryable: bool,
+}
+
+impl ApplyError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            code: None,
+            retryable: false,
+        }
+    }
+}
+
+/// Outcome of applying a single change.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApplyResult {
+    pub address: ResourceAddress,
+    pub status: ApplyStatus,
+    /// The resource as it exists after the change, with computed values populated.
+    /// `None` for deletes and for failures that created nothing; a failed replacement
+    /// that created the new object before failing to delete the old one carries it.
+    #[serde(default)]
+    pub resource: Option<Resource>,
+    /// True if the remote object was deleted: by a delete, or by a change that failed
+    /// afterwards, e.g. a destroy-before-create replacement whose create failed.
+    #[serde(default)]
+    pub deleted: bool,
+    /// Identifier of the remote object, if the provider knows it.
+    #[serde(default)]
+    pub remote_id: Option<String>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    #[serde(default)]
+    pub error: Option<ApplyError>,
+}
+
+impl ApplyResult {
+    pub fn succeeded(address: ResourceAddress, resource: Option<Resource>) -> Self {
+        Self {
+            address,
+            status: ApplyStatus::Succeeded,
+            resource,
+            remote_id: None,
+            deleted: false,
+            warnings: vec![],
+            error: None,
+        }
+    }
+
+    /// A successful delete.
+    pub fn deleted(address: ResourceAddress) -> Self {
+        Self {
+            deleted: true,
+            ..Self::succeeded(address, None)
+        }
+    }
+
+    pub fn failed(address: ResourceAddress, error: ApplyError) -> Self {
+        Self {
+            address,
+            status: ApplyStatus::Failed,
+            resource: None,
+            remote_id: None,
+            deleted: false,
+            warnings: vec![],
+            error: Some(error),
+        }
+    }
+
+    pub fn skipped(address: ResourceAddress, resource: Option<Resource>) -> Self {
+        Self {
+            address,
+            status: ApplyStatus::Skipped,
+            resource,
+            remote_id: None,
+            deleted: false,
+            warnings: vec![],
+            error: None,
+        }
+    }
+
+    pub fn with_remote_id(mut self, remote_id: impl Into<String>) -> Self {
+        self.remote_id = Some(remote_id.into());
+        self
+    }
+
+    pub fn with_warning(mut self, warning: impl Into<String>) -> Self {
+        self.warnings.push(warning.into());
+        self
+    }
+
+    fn error_diagnostic(&self) -> Diagnostic {
+        let diagnostic = match &self.error {
+            Some(error) => {
+                let diagnostic = Diagnostic::error(&error.message);
+                match &error.code {
+                    Some(code) => diagnostic.with_code(code),
+                    None => diagnostic,
+                }
+            }
+            None => Diagnostic::error("Apply failed"),
+        };
+        diagnostic.with_address(self.address.clone())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,7 +375,8 @@ pub struct ValidateRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidateResponse {
     pub valid: bool,
-    pub errors: Vec<(ResourceAddress, String)>,
+    #[serde(default)]
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,22 +390,295 @@ pub struct ImportResponse {
     pub resource: Resource,
 }
 
+/// Lists existing remote objects of one resource type, for bulk import.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoverRequest {
+    pub resource_type: String,
+    /// Provider-specific filters (e.g. `realm`, `username`).
+    #[serde(default, serialize_with = "crate::utils::sorted::serialize")]
+    pub filter: HashMap<String, String>,
+    /// Maximum number of results per page; the provider chooses when unset.
+    #[serde(default)]
+    pub page_size: Option<usize>,
+    /// Opaque token from a previous `DiscoverResponse::next_page_token`.
+    #[serde(default)]
+    pub page_token: Option<String>,
+}
+
+/// A remote object found by `discover`, with a suggested address and its remote id.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DiscoveredResource {
+    pub address: ResourceAddress,
+    pub id: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+}
+
+impl DiscoveredResource {
+    /// Builds a discovered resource, deriving an address name from the remote name.
+    pub fn new(resource_type: &str, namespace: Option<&str>, remote_name: &str, id: &str) -> Self {
+        Self {
+            address: ResourceAddress {
+                resource_type: resource_type.to_string(),
+                name: suggest_name(remote_name),
+                namespace: namespace.map(str::to_string),
+            },
+            id: id.to_string(),
+            display_name: Some(remote_name.to_string()),
+        }
+    }
+
+    pub fn to_import_request(&self) -> ImportRequest {
+        ImportRequest {
+            address: self.address.clone(),
+            id: self.id.clone(),
+        }
+    }
+}
+
+/// Turns a remote display name into an address-friendly name: lowercase ASCII
+/// alphanumerics, `-` and `_`, with any other run of characters collapsed to `-`.
+pub fn suggest_name(remote_name: &str) -> String {
+    let mut name = String::with_capacity(remote_name.len());
+    for c in remote_name.trim().chars() {
+        if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+            name.push(c.to_ascii_lowercase());
+        } else if !name.ends_with('-') {
+            name.push('-');
+        }
+    }
+    let name = name.trim_matches('-');
+    if name.is_empty() {
+        "unnamed".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoverResponse {
+    pub resources: Vec<DiscoveredResource>,
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
+impl DiscoverResponse {
+    /// Pages through an in-memory listing. Page tokens are result offsets, so the
+    /// listing must be returned in a stable order.
+    pub fn paginate(
+        all: Vec<DiscoveredResource>,
+        request: &DiscoverRequest,
+    ) -> crate::utils::Result<Self> {
+        let offset = match &request.page_token {
+            Some(token) => token.parse::<usize>().map_err(|_| {
+                crate::utils::Error::Validation(format!("Invalid page token: {token}"))
+            })?,
+            None => 0,
+        };
+        let page_size = request.page_size.unwrap_or(all.len()).max(1);
+        let end = offset.saturating_add(page_size).min(all.len());
+
+        let resources = all.get(offset..end).map(<[_]>::to_vec).unwrap_or_default();
+        let next_page_token = (end < all.len()).then(|| end.to_string());
+        Ok(Self {
+            resources,
+            next_page_token,
+        })
+    }
+
+    pub fn to_import_requests(&self) -> Vec<ImportRequest> {
+        self.resources
+            .iter()
+            .map(DiscoveredResource::to_import_request)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshRequest {
+    pub resources: Vec<Resource>,
+}
+
+/// Live state of a managed resource as read back from the remote system.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RefreshedResource {
+    /// The resource exists; `resource` is its actual remote representation.
+    Present { resource: Resource },
+    /// The resource was deleted outside of iamctl.
+    Gone { address: ResourceAddress },
+}
+
+impl RefreshedResource {
+    pub fn address(&self) -> &ResourceAddress {
+        match self {
+            Self::Present { resource } => &resource.address,
+            Self::Gone { address } => address,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshResponse {
+    pub resources: Vec<RefreshedResource>,
+}
+
+impl RefreshResponse {
+    /// Returns the resources that still exist, suitable as `PlanRequest::current_state`.
+    pub fn into_current_state(self) -> Vec<Resource> {
+        self.resources
+            .into_iter()
+            .filter_map(|r| match r {
+                RefreshedResource::Present { resource } => Some(resource),
+                RefreshedResource::Gone { .. } => None,
+            })
+            .collect()
+    }
+}
+
+/// Reads a data source. `address.resource_type` is the data source type and
+/// `address.name` the local name the workspace refers to it by.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadDataSourceRequest {
+    pub address: ResourceAddress,
+    #[serde(default, serialize_with = "crate::utils::sorted::serialize")]
+    pub query: HashMap<String, serde_json::Value>,
+}
+
+/// The attributes of an object read through a data source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataSource {
+    pub address: ResourceAddress,
+    #[serde(serialize_with = "crate::utils::sorted::serialize")]
+    pub values: HashMap<String, serde_json::Value>,
+}
+
+impl DataSource {
+    pub fn get(&self, attribute: &str) -> Option<&serde_json::Value> {
+        self.values.get(attribute)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadDataSourceResponse {
+    pub data_source: DataSource,
+}
+
 #[async_trait]
 pub trait Provider: Send + Sync {
     fn metadata(&self) -> ProviderMetadata;
     fn capabilities(&self) -> ProviderCapabilities;
 
+    /// JSON schemas per resource type, registered with the request handler on startup.
+    fn schemas(&self) -> HashMap<String, serde_json::Value> {
+        HashMap::new()
+    }
+
+    /// Receives connection settings before any other operation. The configuration has
+    /// already been validated against `ProviderCapabilities::config_schema`.
+    async fn configure(&self, _request: ConfigureRequest) -> anyhow::Result<ConfigureResponse> {
+        Ok(ConfigureResponse::default())
+    }
+
     async fn plan(&self, request: PlanRequest) -> anyhow::Result<PlanResponse>;
     async fn apply(&self, request: ApplyRequest) -> anyhow::Result<ApplyResponse>;
 
     async fn validate(&self, _request: ValidateRequest) -> anyhow::Result<ValidateResponse> {
         Ok(ValidateResponse {
             valid: true,
-            errors: vec![],
+            diagnostics: vec![],
         })
     }
 
     async fn import(&self, _request: ImportRequest) -> anyhow::Result<ImportResponse> {
         Err(anyhow::anyhow!("Import not implemented"))
     }
+
+    /// Lists existing remote objects of a resource type, filtered and paginated,
+    /// so they can be fed into `import`.
+    async fn discover(&self, _request: DiscoverRequest) -> anyhow::Result<DiscoverResponse> {
+        Err(anyhow::anyhow!("Discover not implemented"))
+    }
+
+    /// Reads the live state of resources already recorded in state, so the engine
+    /// can detect drift before planning.
+    async fn refresh(&self, _request: RefreshRequest) -> anyhow::Result<RefreshResponse> {
+        Err(anyhow::anyhow!("Refresh not implemented"))
+    }
+
+    /// Looks up an object the workspace does not manage, e.g. an existing realm's id.
+    async fn read_data_source(
+        &self,
+        _request: ReadDataSourceRequest,
+    ) -> anyhow::Result<ReadDataSourceResponse> {
+        Err(anyhow::anyhow!("Data sources not implemented"))
+    }
+
+    // Context-aware entry points. `RequestHandler` calls these with a per-request
+    // `ProviderContext`; the defaults delegate to the methods above, so providers only
+    // override the ones that need cancellation, deadlines or progress reporting.
+
+    async fn configure_with_context(
+        &self,
+        _ctx: &ProviderContext,
+        request: ConfigureRequest,
+    ) -> anyhow::Result<ConfigureResponse> {
+        self.configure(request).await
+    }
+
+    async fn plan_with_context(
+        &self,
+        _ctx: &ProviderContext,
+        request: PlanRequest,
+    ) -> anyhow::Result<PlanResponse> {
+        self.plan(request).await
+    }
+
+    async fn apply_with_context(
+        &self,
+        _ctx: &ProviderContext,
+        request: ApplyRequest,
+    ) -> anyhow::Result<ApplyResponse> {
+        self.apply(request).await
+    }
+
+    async fn validate_with_context(
+        &self,
+        _ctx: &ProviderContext,
+        request: ValidateRequest,
+    ) -> anyhow::Result<ValidateResponse> {
+        self.validate(request).await
+    }
+
+    async fn import_with_context(
+        &self,
+        _ctx: &ProviderContext,
+        request: ImportRequest,
+    ) -> anyhow::Result<ImportResponse> {
+        self.import(request).await
+    }
+
+    async fn discover_with_context(
+        &self,
+        _ctx: &ProviderContext,
+        request: DiscoverRequest,
+    ) -> anyhow::Result<DiscoverResponse> {
+        self.discover(request).await
+    }
+
+    async fn refresh_with_context(
+        &self,
+        _ctx: &ProviderContext,
+        request: RefreshRequest,
+    ) -> anyhow::Result<RefreshResponse> {
+        self.refresh(request).await
+    }
+
+    async fn read_data_source_with_context(
+        &self,
+        _ctx: &ProviderContext,
+        request: ReadDataSourceRequest,
+    ) -> anyhow::Result<ReadDataSourceResponse> {
+        self.read_data_source(request).await
+    }
 }
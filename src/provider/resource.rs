@@ -0,0 +1,196 @@
+use super::ResourceTypeDescriptor;
+use crate::plan::FORCE_NEW_KEY;
+use crate::types::{Resource, ResourceAddress};
+use crate::utils::redact::SENSITIVE_KEY;
+use crate::utils::{decode_spec, encode_spec};
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Typed CRUD operations for a single resource type.
+///
+/// Register implementations with [`ProviderBuilder`](super::ProviderBuilder); the SDK
+/// then derives `plan`, `apply`, `refresh`, the capability list and the JSON schema
+/// for the type from the registered handlers.
+#[async_trait]
+pub trait ResourceHandler: Send + Sync + 'static {
+    /// The typed resource specification, decoded from `Resource::spec`.
+    type Spec: Serialize + DeserializeOwned + JsonSchema + Send + Sync + 'static;
+
+    /// Creates the remote object and returns the resulting spec, including computed values.
+    async fn create(
+        &self,
+        address: &ResourceAddress,
+        spec: Self::Spec,
+    ) -> anyhow::Result<Self::Spec>;
+
+    /// Reads the live remote object. Returns `None` if it no longer exists.
+    async fn read(
+        &self,
+        address: &ResourceAddress,
+        spec: Self::Spec,
+    ) -> anyhow::Result<Option<Self::Spec>>;
+
+    /// Updates the remote object from `before` to `after` and returns the resulting spec.
+    async fn update(
+        &self,
+        address: &ResourceAddress,
+        before: Self::Spec,
+        after: Self::Spec,
+    ) -> anyhow::Result<Self::Spec>;
+
+    /// Deletes the remote object.
+    async fn delete(&self, address: &ResourceAddress, spec: Self::Spec) -> anyhow::Result<()>;
+
+    /// Identifier of the remote object, reported in `ApplyResult::remote_id`.
+    fn remote_id(&self, _spec: &Self::Spec) -> Option<String> {
+        None
+    }
+
+    /// Capability descriptor published for the type, e.g. to declare a schema version
+    /// or immutable attributes. Defaults to full CRUD support.
+    fn descriptor(&self, resource_type: &str) -> ResourceTypeDescriptor {
+        ResourceTypeDescriptor::new(resource_type)
+    }
+}
+
+/// Resource type metadata declared on a spec type, usually via
+/// `#[derive(IamResource)]` (`derive` feature).
+///
+/// Field lists hold top-level property names as they appear in the JSON schema.
+/// [`IamResource::resource_schema`] publishes them as schema annotations, which the
+/// planner, validator and redaction helpers read.
+pub trait IamResource: JsonSchema {
+    const RESOURCE_TYPE: &'static str;
+    const SCHEMA_VERSION: u32 = 0;
+    /// Fields set by the remote system, published as `readOnly`.
+    const COMPUTED_FIELDS: &'static [&'static str] = &[];
+    /// Fields whose values are masked, published with [`SENSITIVE_KEY`].
+    const SENSITIVE_FIELDS: &'static [&'static str] = &[];
+    /// Fields whose change requires replacement, published with [`FORCE_NEW_KEY`].
+    const FORCE_NEW_FIELDS: &'static [&'static str] = &[];
+
+    /// The derived JSON schema of the type with the field lists applied.
+    fn resource_schema() -> serde_json::Value {
+        let mut schema = serde_json::to_value(schemars::schema_for!(Self)).unwrap_or_default();
+        annotate(&mut schema, Self::COMPUTED_FIELDS, "readOnly");
+        annotate(&mut schema, Self::SENSITIVE_FIELDS, SENSITIVE_KEY);
+        annotate(&mut schema, Self::FORCE_NEW_FIELDS, FORCE_NEW_KEY);
+        schema
+    }
+}
+
+fn annotate(schema: &mut serde_json::Value, fields: &[&str], key: &str) {
+    for field in fields {
+        if let Some(property) = schema
+            .get_mut("properties")
+            .and_then(|properties| properties.get_mut(*field))
+            .and_then(serde_json::Value::as_object_mut)
+        {
+            let _ = property.insert(key.to_string(), serde_json::Value::Bool(true));
+        }
+    }
+}
+
+/// Object-safe view of a [`ResourceHandler`] operating on untyped resources.
+#[async_trait]
+pub(crate) trait ErasedHandler: Send + Sync {
+    fn schema(&self) -> serde_json::Value;
+    async fn create(&self, after: &Resource) -> anyhow::Result<Resource>;
+    async fn read(&self, current: &Resource) -> anyhow::Result<Option<Resource>>;
+    async fn update(&self, before: &Resource, after: &Resource) -> anyhow::Result<Resource>;
+    async fn delete(&self, before: &Resource) -> anyhow::Result<()>;
+    fn remote_id(&self, resource: &Resource) -> Option<String>;
+    fn descriptor(&self, resource_type: &str) -> ResourceTypeDescriptor;
+}
+
+pub(crate) struct Erased<H> {
+    handler: H,
+    schema: serde_json::Value,
+    schema_version: Option<u32>,
+}
+
+impl<H: ResourceHandler> Erased<H> {
+    pub(crate) fn new(handler: H) -> Self {
+        Self {
+            handler,
+            schema: serde_json::to_value(schemars::schema_for!(H::Spec)).unwrap_or_default(),
+            schema_version: None,
+        }
+    }
+
+    /// Publishes the schema and version declared by the spec's [`IamResource`] impl.
+    pub(crate) fn typed(handler: H) -> Self
+    where
+        H::Spec: IamResource,
+    {
+        Self {
+            handler,
+            schema: H::Spec::resource_schema(),
+            schema_version: Some(H::Spec::SCHEMA_VERSION),
+        }
+    }
+
+    fn with_spec(template: &Resource, spec: &H::Spec) -> anyhow::Result<Resource> {
+        Ok(Resource {
+            address: template.address.clone(),
+            spec: encode_spec(&template.address, spec)?,
+            metadata: template.metadata.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl<H: ResourceHandler> ErasedHandler for Erased<H> {
+    fn schema(&self) -> serde_json::Value {
+        self.schema.clone()
+    }
+
+    async fn create(&self, after: &Resource) -> anyhow::Result<Resource> {
+        let spec = self
+            .handler
+            .create(&after.address, decode_spec(after)?)
+            .await?;
+        Self::with_spec(after, &spec)
+    }
+
+    async fn read(&self, current: &Resource) -> anyhow::Result<Option<Resource>> {
+        match self
+            .handler
+            .read(&current.address, decode_spec(current)?)
+            .await?
+        {
+            Some(spec) => Ok(Some(Self::with_spec(current, &spec)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn update(&self, before: &Resource, after: &Resource) -> anyhow::Result<Resource> {
+        let spec = self
+            .handler
+            .update(&after.address, decode_spec(before)?, decode_spec(after)?)
+            .await?;
+        Self::with_spec(after, &spec)
+    }
+
+    async fn delete(&self, before: &Resource) -> anyhow::Result<()> {
+        self.handler
+            .delete(&before.address, decode_spec(before)?)
+            .await
+    }
+
+    fn remote_id(&self, resource: &Resource) -> Option<String> {
+        decode_spec(resource)
+            .ok()
+            .and_then(|spec| self.handler.remote_id(&spec))
+    }
+
+    fn descriptor(&self, resource_type: &str) -> ResourceTypeDescriptor {
+        let mut descriptor = self.handler.descriptor(resource_type);
+        if let Some(version) = self.schema_version {
+            descriptor.schema_version = version;
+        }
+        descriptor
+    }
+}
@@ -1,24 +1,47 @@
+use crate::server::recorder::{Cassette, Recorder, CLIENT_RECORD_ENV};
 use crate::server::types::{JsonRpcRequest, JsonRpcResponse};
 use crate::utils::Result;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::path::Path;
 use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, ChildStdout, Command};
 
+/// How long a provider may take to exit after its stdin is closed.
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Request ids are unique across the clients of a process, so transcripts shared by
+/// several clients pair every response with its request.
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+struct ProviderProcess {
+    child: Child,
+    stdout_reader: BufReader<ChildStdout>,
+}
+
+enum Transport {
+    Process(Box<ProviderProcess>),
+    Replay(Cassette),
+}
+
 /// A JSON-RPC client for communicating with a provider process.
 /// Useful for testing and for the engine to call providers.
 pub struct JsonRpcClient {
-    child: Child,
-    stdout_reader: BufReader<ChildStdout>,
+    transport: Transport,
+    recorder: Option<Recorder>,
 }
 
 impl JsonRpcClient {
     /// Launches a provider executable and initializes the client.
+    /// Traffic is recorded when `IAMCTL_RECORD_CLIENT` points at a transcript file.
     pub fn launch(executable_path: &str) -> Result<Self> {
         let mut child = Command::new(executable_path)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
+            .kill_on_drop(true)
             .spawn()?;
 
         let stdout = child.stdout.take().ok_or_else(|| {
@@ -28,18 +51,40 @@ impl JsonRpcClient {
         })?;
 
         Ok(Self {
-            child,
-            stdout_reader: BufReader::new(stdout),
+            transport: Transport::Process(Box::new(ProviderProcess {
+                child,
+                stdout_reader: BufReader::new(stdout),
+            })),
+            recorder: Recorder::from_env(CLIENT_RECORD_ENV)?,
         })
     }
 
+    /// Creates a client that serves responses from a recorded cassette instead of a process.
+    pub fn from_cassette(cassette: Cassette) -> Self {
+        Self {
+            transport: Transport::Replay(cassette),
+            recorder: None,
+        }
+    }
+
+    /// Loads a JSONL transcript and replays it.
+    pub async fn replay<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::from_cassette(Cassette::load(path).await?))
+    }
+
+    /// Records every request and response to the given recorder.
+    pub fn with_recorder(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
     /// Calls a method on the provider and returns the result.
     pub async fn call<P, R>(&mut self, method: &str, params: P) -> Result<R>
     where
         P: Serialize,
         R: DeserializeOwned,
     {
-        let id = serde_json::Value::Number(1.into());
+        let id = serde_json::Value::Number(NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed).into());
         let request = JsonRpcRequest {
             jsonrpc: "2.0".to_string(),
             method: method.to_string(),
@@ -47,20 +92,15 @@ impl JsonRpcClient {
             id: Some(id),
         };
 
-        let mut json = serde_json::to_string(&request)?;
-        json.push('\n');
-
-        let stdin = self.child.stdin.as_mut().ok_or_else(|| {
-            crate::utils::Error::Internal("Failed to access provider stdin".to_string())
-        })?;
-
-        stdin.write_all(json.as_bytes()).await?;
-        stdin.flush().await?;
+        if let Some(recorder) = &self.recorder {
+            recorder.record_request(&request).await?;
+        }
 
-        let mut response_line = String::new();
-        let _ = self.stdout_reader.read_line(&mut response_line).await?;
+        let response = self.exchange(&request).await?;
 
-        let response: JsonRpcResponse = serde_json::from_str(&response_line)?;
+        if let Some(recorder) = &self.recorder {
+            recorder.record_response(&response).await?;
+        }
 
         if let Some(error) = response.error {
             return Err(crate::utils::Error::Protocol(format!(
@@ -75,4 +115,56 @@ impl JsonRpcClient {
 
         Ok(serde_json::from_value(result)?)
     }
+
+    /// Closes the provider's stdin and waits for it to exit, killing it if it does not
+    /// exit within the grace period.
+    pub async fn shutdown(self) -> Result<()> {
+        let mut process = match self.transport {
+            Transport::Process(process) => process,
+            Transport::Replay(_) => return Ok(()),
+        };
+
+        drop(process.child.stdin.take());
+        match tokio::time::timeout(SHUTDOWN_GRACE_PERIOD, process.child.wait()).await {
+            Ok(status) => {
+                let _ = status?;
+            }
+            Err(_) => {
+                tracing::warn!("Provider did not exit after stdin was closed; killing it");
+                process.child.kill().await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn exchange(&mut self, request: &JsonRpcRequest) -> Result<JsonRpcResponse> {
+        let process = match &mut self.transport {
+            Transport::Process(process) => process,
+            Transport::Replay(cassette) => return cassette.respond(request),
+        };
+
+        let mut json = serde_json::to_string(request)?;
+        json.push('\n');
+
+        let stdin = process.child.stdin.as_mut().ok_or_else(|| {
+            crate::utils::Error::Internal("Failed to access provider stdin".to_string())
+        })?;
+
+        stdin.write_all(json.as_bytes()).await?;
+        stdin.flush().await?;
+
+        // Notifications (e.g. `$/progress`) may precede the response; they carry a
+        // `method` and are skipped.
+        loop {
+            let mut line = String::new();
+            let _ = process.stdout_reader.read_line(&mut line).await?;
+
+            let message: serde_json::Value = serde_json::from_str(&line)?;
+            if let Some(method) = message.get("method").and_then(|m| m.as_str()) {
+                tracing::debug!(method, params = %message["params"], "Provider notification");
+                continue;
+            }
+            return Ok(serde_json::from_value(message)?);
+        }
+    }
 }
@@ -1,21 +1,64 @@
-use crate::provider::{ApplyRequest, ImportRequest, PlanRequest, Provider, ValidateRequest};
+use crate::provider::{
+    ApplyRequest, ConfigureRequest, DiscoverRequest, IamResource, ImportRequest, PlanRequest,
+    Provider, ProviderContext, ReadDataSourceRequest, RefreshRequest, ValidateRequest,
+};
 use crate::server::types::{JsonRpcRequest, JsonRpcResponse};
+use crate::types::unknown::unresolved_unknowns;
+use crate::types::Diagnostic;
 use crate::validation::JsonSchemaValidator;
 use crate::validation::SchemaValidator;
 use crate::validation::ValidationResult;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
+use tracing::Instrument;
+
+/// Key under which the provider configuration schema is registered.
+const CONFIG_SCHEMA_KEY: &str = "provider_config";
 
 /// Handles the routing and processing of JSON-RPC requests to the provider.
 pub struct RequestHandler<P: Provider> {
     provider: Arc<P>,
     validator: JsonSchemaValidator,
+    config_validator: Option<JsonSchemaValidator>,
+    configured: AtomicBool,
+    request_timeout: Option<Duration>,
 }
 
 impl<P: Provider + 'static> RequestHandler<P> {
+    /// Creates a handler, registering any schemas the provider publishes.
     pub fn new(provider: Arc<P>) -> Self {
+        let mut validator = JsonSchemaValidator::new();
+        for (resource_type, schema) in provider.schemas() {
+            validator.add_schema(&resource_type, schema);
+        }
+
+        let config_validator = provider.capabilities().config_schema.map(|schema| {
+            let mut config_validator = JsonSchemaValidator::new();
+            config_validator.add_schema(CONFIG_SCHEMA_KEY, schema);
+            config_validator
+        });
+
         Self {
             provider,
-            validator: JsonSchemaValidator::new(),
+            validator,
+            config_validator,
+            configured: AtomicBool::new(false),
+            request_timeout: None,
+        }
+    }
+
+    /// Rejects operations that reach the remote system until `configure` has succeeded,
+    /// for providers that publish a configuration schema.
+    fn require_configured(&self, id: &serde_json::Value) -> Option<JsonRpcResponse> {
+        if self.config_validator.is_some() && !self.configured.load(Ordering::SeqCst) {
+            Some(JsonRpcResponse::error(
+                id.clone(),
+                -32001,
+                "Provider not configured: call 'configure' first".to_string(),
+            ))
+        } else {
+            None
         }
     }
 
@@ -37,6 +80,21 @@ impl<P: Provider + 'static> RequestHandler<P> {
         msg
     }
 
+    /// Runs schema validation for a resource. Returns no diagnostics when no schema is
+    /// registered for its type.
+    fn schema_diagnostics(
+        &self,
+        resource: &crate::types::Resource,
+    ) -> crate::utils::Result<Vec<Diagnostic>> {
+        let result = self.validator.validate(resource)?;
+        let is_schema_missing =
+            !result.valid && result.errors.iter().all(|e| e.code == "SCHEMA_NOT_FOUND");
+        if is_schema_missing {
+            return Ok(vec![]);
+        }
+        Ok(result.to_diagnostics(&resource.address))
+    }
+
     fn validate_resource(
         &self,
         id: serde_json::Value,
@@ -50,10 +108,12 @@ impl<P: Provider + 'static> RequestHandler<P> {
                 if is_schema_missing {
                     None
                 } else {
-                    Some(JsonRpcResponse::error(
+                    let diagnostics = result.to_diagnostics(&resource.address);
+                    Some(JsonRpcResponse::error_with_data(
                         id,
                         -32602,
                         Self::format_validation_error(result),
+                        serde_json::json!({ "diagnostics": diagnostics }),
                     ))
                 }
             }
@@ -75,16 +135,80 @@ impl<P: Provider + 'static> RequestHandler<P> {
         self.validator.add_schema(resource_type, schema);
     }
 
+    /// Registers the annotated schema of an [`IamResource`] under its resource type.
+    pub fn register_resource<T: IamResource>(&mut self) {
+        self.validator.add_resource_schema::<T>();
+    }
+
+    /// Sets the time each request may take. The deadline is exposed through the
+    /// `ProviderContext`, whose cancellation token fires when it passes.
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Builds the context for a request, applying the request timeout.
+    pub fn context_for(&self, request: &JsonRpcRequest) -> ProviderContext {
+        let id = request.id.clone().unwrap_or(serde_json::Value::Null);
+        let ctx = ProviderContext::new(id, &request.method);
+        match self.request_timeout {
+            Some(timeout) => ctx.with_timeout(timeout),
+            None => ctx,
+        }
+    }
+
     pub async fn handle(&self, request: JsonRpcRequest) -> JsonRpcResponse {
+        let ctx = self.context_for(&request);
+        self.handle_with_context(request, &ctx).await
+    }
+
+    /// Handles a request with a caller-provided context, e.g. one whose cancellation
+    /// token and progress reporter are wired to the transport.
+    pub async fn handle_with_context(
+        &self,
+        request: JsonRpcRequest,
+        ctx: &ProviderContext,
+    ) -> JsonRpcResponse {
+        let response = self.dispatch(request, ctx).instrument(ctx.span().clone());
+        let Some(deadline) = ctx.deadline() else {
+            return response.await;
+        };
+
+        // Cancellation is cooperative: once the deadline passes the token fires and
+        // the provider is expected to wind down and return.
+        tokio::pin!(response);
+        tokio::select! {
+            response = &mut response => response,
+            _ = tokio::time::sleep_until(deadline) => {
+                ctx.cancellation().cancel();
+                response.await
+            }
+        }
+    }
+
+    async fn dispatch(&self, request: JsonRpcRequest, ctx: &ProviderContext) -> JsonRpcResponse {
         let id = request.id.clone().unwrap_or(serde_json::Value::Null);
 
+        if matches!(
+            request.method.as_str(),
+            "plan" | "apply" | "import" | "refresh" | "discover" | "read_data_source"
+        ) {
+            if let Some(resp) = self.require_configured(&id) {
+                return resp;
+            }
+        }
+
         match request.method.as_str() {
             "metadata" => self.handle_metadata(id).await,
             "capabilities" => self.handle_capabilities(id).await,
-            "plan" => self.handle_plan(id, request.params).await,
-            "apply" => self.handle_apply(id, request.params).await,
-            "validate" => self.handle_validate(id, request.params).await,
-            "import" => self.handle_import(id, request.params).await,
+            "configure" => self.handle_configure(ctx, id, request.params).await,
+            "plan" => self.handle_plan(ctx, id, request.params).await,
+            "apply" => self.handle_apply(ctx, id, request.params).await,
+            "validate" => self.handle_validate(ctx, id, request.params).await,
+            "import" => self.handle_import(ctx, id, request.params).await,
+            "refresh" => self.handle_refresh(ctx, id, request.params).await,
+            "discover" => self.handle_discover(ctx, id, request.params).await,
+            "read_data_source" => self.handle_read_data_source(ctx, id, request.params).await,
             _ => {
                 JsonRpcResponse::error(id, -32601, format!("Method not found: {}", request.method))
             }
@@ -107,8 +231,51 @@ impl<P: Provider + 'static> RequestHandler<P> {
         }
     }
 
+    async fn handle_configure(
+        &self,
+        ctx: &ProviderContext,
+        id: serde_json::Value,
+        params: serde_json::Value,
+    ) -> JsonRpcResponse {
+        let request: ConfigureRequest = match serde_json::from_value(params) {
+            Ok(req) => req,
+            Err(e) => return JsonRpcResponse::error(id, -32602, format!("Invalid params: {e}")),
+        };
+
+        // A failed reconfiguration leaves the provider unconfigured.
+        self.configured.store(false, Ordering::SeqCst);
+
+        if let Some(config_validator) = &self.config_validator {
+            match config_validator.validate_value(CONFIG_SCHEMA_KEY, &request.config, "config") {
+                Ok(result) if result.valid => {}
+                Ok(result) => {
+                    return JsonRpcResponse::error(
+                        id,
+                        -32602,
+                        Self::format_validation_error(result),
+                    )
+                }
+                Err(e) => {
+                    return JsonRpcResponse::error(id, -32603, format!("Internal error: {e}"))
+                }
+            }
+        }
+
+        match self.provider.configure_with_context(ctx, request).await {
+            Ok(resp) => match serde_json::to_value(resp) {
+                Ok(val) => {
+                    self.configured.store(true, Ordering::SeqCst);
+                    JsonRpcResponse::success(id, val)
+                }
+                Err(e) => JsonRpcResponse::error(id, -32603, format!("Internal error: {e}")),
+            },
+            Err(e) => JsonRpcResponse::error(id, -32000, format!("Provider error: {e}")),
+        }
+    }
+
     async fn handle_plan(
         &self,
+        ctx: &ProviderContext,
         id: serde_json::Value,
         params: serde_json::Value,
     ) -> JsonRpcResponse {
@@ -123,7 +290,7 @@ impl<P: Provider + 'static> RequestHandler<P> {
             }
         }
 
-        match self.provider.plan(request).await {
+        match self.provider.plan_with_context(ctx, request).await {
             Ok(resp) => match serde_json::to_value(resp) {
                 Ok(val) => JsonRpcResponse::success(id, val),
                 Err(e) => JsonRpcResponse::error(id, -32603, format!("Internal error: {e}")),
@@ -134,6 +301,7 @@ impl<P: Provider + 'static> RequestHandler<P> {
 
     async fn handle_apply(
         &self,
+        ctx: &ProviderContext,
         id: serde_json::Value,
         params: serde_json::Value,
     ) -> JsonRpcResponse {
@@ -142,6 +310,20 @@ impl<P: Provider + 'static> RequestHandler<P> {
             Err(e) => return JsonRpcResponse::error(id, -32602, format!("Invalid params: {e}")),
         };
 
+        // Unknown values must be resolved by the engine before apply.
+        let unresolved = unresolved_unknowns(&request.changes);
+        if !unresolved.is_empty() {
+            return JsonRpcResponse::error_with_data(
+                id,
+                -32602,
+                format!(
+                    "Invalid params: {} value(s) are still unknown at apply",
+                    unresolved.len()
+                ),
+                serde_json::json!({ "diagnostics": unresolved }),
+            );
+        }
+
         for change in &request.changes {
             if let Some(after) = &change.after {
                 if let Some(resp) = self.validate_resource(id.clone(), after) {
@@ -150,7 +332,7 @@ impl<P: Provider + 'static> RequestHandler<P> {
             }
         }
 
-        match self.provider.apply(request).await {
+        match self.provider.apply_with_context(ctx, request).await {
             Ok(resp) => match serde_json::to_value(resp) {
                 Ok(val) => JsonRpcResponse::success(id, val),
                 Err(e) => JsonRpcResponse::error(id, -32603, format!("Internal error: {e}")),
@@ -161,6 +343,7 @@ impl<P: Provider + 'static> RequestHandler<P> {
 
     async fn handle_validate(
         &self,
+        ctx: &ProviderContext,
         id: serde_json::Value,
         params: serde_json::Value,
     ) -> JsonRpcResponse {
@@ -169,13 +352,42 @@ impl<P: Provider + 'static> RequestHandler<P> {
             Err(e) => return JsonRpcResponse::error(id, -32602, format!("Invalid params: {e}")),
         };
 
+        let mut diagnostics = vec![];
         for resource in &request.resources {
-            if let Some(resp) = self.validate_resource(id.clone(), resource) {
-                return resp;
+            match self.schema_diagnostics(resource) {
+                Ok(found) => diagnostics.extend(found),
+                Err(e) => {
+                    return JsonRpcResponse::error(id, -32603, format!("Internal error: {e}"))
+                }
             }
         }
 
-        match self.provider.validate(request).await {
+        match self.provider.validate_with_context(ctx, request).await {
+            Ok(mut resp) => {
+                diagnostics.append(&mut resp.diagnostics);
+                resp.valid = resp.valid && !Diagnostic::any_errors(&diagnostics);
+                resp.diagnostics = diagnostics;
+                match serde_json::to_value(resp) {
+                    Ok(val) => JsonRpcResponse::success(id, val),
+                    Err(e) => JsonRpcResponse::error(id, -32603, format!("Internal error: {e}")),
+                }
+            }
+            Err(e) => JsonRpcResponse::error(id, -32000, format!("Provider error: {e}")),
+        }
+    }
+
+    async fn handle_import(
+        &self,
+        ctx: &ProviderContext,
+        id: serde_json::Value,
+        params: serde_json::Value,
+    ) -> JsonRpcResponse {
+        let request: ImportRequest = match serde_json::from_value(params) {
+            Ok(req) => req,
+            Err(e) => return JsonRpcResponse::error(id, -32602, format!("Invalid params: {e}")),
+        };
+
+        match self.provider.import_with_context(ctx, request).await {
             Ok(resp) => match serde_json::to_value(resp) {
                 Ok(val) => JsonRpcResponse::success(id, val),
                 Err(e) => JsonRpcResponse::error(id, -32603, format!("Internal error: {e}")),
@@ -184,17 +396,62 @@ impl<P: Provider + 'static> RequestHandler<P> {
         }
     }
 
-    async fn handle_import(
+    async fn handle_refresh(
         &self,
+        ctx: &ProviderContext,
         id: serde_json::Value,
         params: serde_json::Value,
     ) -> JsonRpcResponse {
-        let request: ImportRequest = match serde_json::from_value(params) {
+        let request: RefreshRequest = match serde_json::from_value(params) {
+            Ok(req) => req,
+            Err(e) => return JsonRpcResponse::error(id, -32602, format!("Invalid params: {e}")),
+        };
+
+        match self.provider.refresh_with_context(ctx, request).await {
+            Ok(resp) => match serde_json::to_value(resp) {
+                Ok(val) => JsonRpcResponse::success(id, val),
+                Err(e) => JsonRpcResponse::error(id, -32603, format!("Internal error: {e}")),
+            },
+            Err(e) => JsonRpcResponse::error(id, -32000, format!("Provider error: {e}")),
+        }
+    }
+
+    async fn handle_discover(
+        &self,
+        ctx: &ProviderContext,
+        id: serde_json::Value,
+        params: serde_json::Value,
+    ) -> JsonRpcResponse {
+        let request: DiscoverRequest = match serde_json::from_value(params) {
+            Ok(req) => req,
+            Err(e) => return JsonRpcResponse::error(id, -32602, format!("Invalid params: {e}")),
+        };
+
+        match self.provider.discover_with_context(ctx, request).await {
+            Ok(resp) => match serde_json::to_value(resp) {
+                Ok(val) => JsonRpcResponse::success(id, val),
+                Err(e) => JsonRpcResponse::error(id, -32603, format!("Internal error: {e}")),
+            },
+            Err(e) => JsonRpcResponse::error(id, -32000, format!("Provider error: {e}")),
+        }
+    }
+
+    async fn handle_read_data_source(
+        &self,
+        ctx: &ProviderContext,
+        id: serde_json::Value,
+        params: serde_json::Value,
+    ) -> JsonRpcResponse {
+        let request: ReadDataSourceRequest = match serde_json::from_value(params) {
             Ok(req) => req,
             Err(e) => return JsonRpcResponse::error(id, -32602, format!("Invalid params: {e}")),
         };
 
-        match self.provider.import(request).await {
+        match self
+            .provider
+            .read_data_source_with_context(ctx, request)
+            .await
+        {
             Ok(resp) => match serde_json::to_value(resp) {
                 Ok(val) => JsonRpcResponse::success(id, val),
                 Err(e) => JsonRpcResponse::error(id, -32603, format!("Internal error: {e}")),
@@ -25,17 +25,26 @@ mod tests {
                 supported_resources: vec!["realm".to_string()],
                 can_import: false,
                 can_validate: false,
+                can_refresh: false,
+                config_schema: None,
+                can_discover: false,
+                resource_types: vec![],
+                data_sources: vec![],
             }
         }
 
         async fn plan(&self, _request: PlanRequest) -> anyhow::Result<PlanResponse> {
-            Ok(PlanResponse { changes: vec![] })
+            Ok(PlanResponse {
+                changes: vec![],
+                diagnostics: vec![],
+            })
         }
 
         async fn apply(&self, _request: ApplyRequest) -> anyhow::Result<ApplyResponse> {
             Ok(ApplyResponse {
                 successful_addresses: vec![],
-                failed_addresses: vec![],
+                diagnostics: vec![],
+                results: vec![],
             })
         }
     }
@@ -74,4 +83,39 @@ mod tests {
         assert!(response.result.is_none());
         assert_eq!(response.error.unwrap().code, -32601);
     }
+
+    #[tokio::test]
+    async fn test_refresh_not_implemented_by_default() {
+        let provider = Arc::new(TestProvider);
+        let handler = RequestHandler::new(provider);
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "refresh".to_string(),
+            params: json!({ "resources": [] }),
+            id: Some(json!(1)),
+        };
+
+        let response = handler.handle(request).await;
+        assert!(response.result.is_none());
+        assert_eq!(response.error.unwrap().code, -32000);
+    }
+
+    #[tokio::test]
+    async fn test_read_data_source_not_implemented_by_default() {
+        let provider = Arc::new(TestProvider);
+        let handler = RequestHandler::new(provider);
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "read_data_source".to_string(),
+            params: json!({
+                "address": { "resource_type": "role", "name": "admin", "namespace": null }
+            }),
+            id: Some(json!(1)),
+        };
+
+        let response = handler.handle(request).await;
+        let error = response.error.unwrap();
+        assert_eq!(error.code, -32000);
+        assert!(error.message.contains("Data sources not implemented"));
+    }
 }
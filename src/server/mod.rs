@@ -1,5 +1,7 @@
 pub mod client;
 pub mod handler;
+pub mod pool;
+pub mod recorder;
 pub mod types;
 
 #[cfg(test)]
@@ -7,25 +9,62 @@ mod handler_tests;
 
 pub use client::JsonRpcClient;
 pub use handler::RequestHandler;
+pub use pool::ProviderPool;
+pub use recorder::{Cassette, Recorder};
 pub use types::{JsonRpcError, JsonRpcRequest, JsonRpcResponse};
 
-use crate::provider::Provider;
+use crate::provider::{CancellationToken, IamResource, Progress, ProgressReporter, Provider};
+use recorder::SERVER_RECORD_ENV;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+
+/// Notification sent by the engine to cancel a request: `{"id": <request id>}`.
+pub const CANCEL_METHOD: &str = "$/cancelRequest";
+/// Notification sent by the provider while a request runs; params are a [`Progress`].
+pub const PROGRESS_METHOD: &str = "$/progress";
+
+/// The request currently being handled by the server loop.
+struct InFlight<'a> {
+    id: serde_json::Value,
+    cancellation: CancellationToken,
+    response: Pin<Box<dyn Future<Output = JsonRpcResponse> + Send + 'a>>,
+}
 
 /// JSON-RPC Server that handles communication with the iamctl engine.
 pub struct JsonRpcServer<P: Provider> {
     handler: RequestHandler<P>,
+    recorder: Option<Recorder>,
 }
 
 impl<P: Provider + 'static> JsonRpcServer<P> {
     /// Creates a new JSON-RPC server with the given provider.
+    /// Traffic is recorded when `IAMCTL_RECORD_PROVIDER` points at a transcript file.
     pub fn new(provider: P) -> Self {
+        let recorder = match Recorder::from_env(SERVER_RECORD_ENV) {
+            Ok(recorder) => recorder,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to enable protocol recording");
+                None
+            }
+        };
+
         Self {
             handler: RequestHandler::new(Arc::new(provider)),
+            recorder,
         }
     }
 
+    /// Records every request and response to the given recorder.
+    pub fn with_recorder(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
     /// Registers a JSON schema for a resource type by deriving it from a Rust type.
     pub fn register_type_schema<T: schemars::JsonSchema>(&mut self, resource_type: &str) {
         self.handler.register_type_schema::<T>(resource_type);
@@ -36,43 +75,144 @@ impl<P: Provider + 'static> JsonRpcServer<P> {
         self.handler.register_schema(resource_type, schema);
     }
 
+    /// Registers the annotated schema of an [`IamResource`] under its resource type.
+    pub fn register_resource<T: IamResource>(&mut self) {
+        self.handler.register_resource::<T>();
+    }
+
     /// Alias for serve() to provide a more standard 'run' method.
     pub async fn run(&self) -> crate::utils::Result<()> {
         self.serve().await
     }
 
+    /// Sets the time each request may take; see [`RequestHandler::with_request_timeout`].
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.handler = self.handler.with_request_timeout(timeout);
+        self
+    }
+
     /// Starts the server and listens for requests on stdin.
+    ///
+    /// Requests are handled one at a time, in order. While a request runs the server
+    /// keeps reading: `$/cancelRequest` notifications cancel the running or a queued
+    /// request, and progress reported by the provider is forwarded as `$/progress`
+    /// notifications before the response.
     pub async fn serve(&self) -> crate::utils::Result<()> {
         let stdin = io::stdin();
         let mut reader = BufReader::new(stdin).lines();
         let mut stdout = io::stdout();
+        let (progress_tx, mut progress_rx) = mpsc::unbounded_channel::<Progress>();
+
+        let mut queue: VecDeque<JsonRpcRequest> = VecDeque::new();
+        let mut running: Option<InFlight<'_>> = None;
+        let mut input_open = true;
 
-        while let Some(line) = reader.next_line().await? {
-            let request: JsonRpcRequest = match serde_json::from_str(&line) {
-                Ok(req) => req,
-                Err(e) => {
-                    let err_resp = JsonRpcResponse::error(
-                        serde_json::Value::Null,
-                        -32700,
-                        format!("Parse error: {e}"),
-                    );
-                    self.send_response(&mut stdout, err_resp).await?;
-                    continue;
+        loop {
+            if running.is_none() {
+                if let Some(request) = queue.pop_front() {
+                    let id = request.id.clone().unwrap_or_default();
+                    let ctx = self
+                        .handler
+                        .context_for(&request)
+                        .with_progress(ProgressReporter::new(id.clone(), progress_tx.clone()));
+                    running = Some(InFlight {
+                        id,
+                        cancellation: ctx.cancellation().clone(),
+                        response: Box::pin(async move {
+                            self.handler.handle_with_context(request, &ctx).await
+                        }),
+                    });
+                } else if !input_open {
+                    break;
                 }
-            };
+            }
 
-            let response = self.handler.handle(request).await;
-            self.send_response(&mut stdout, response).await?;
+            tokio::select! {
+                line = reader.next_line(), if input_open => {
+                    let Some(line) = line? else {
+                        input_open = false;
+                        continue;
+                    };
+                    let request: JsonRpcRequest = match serde_json::from_str(&line) {
+                        Ok(req) => req,
+                        Err(e) => {
+                            let err_resp = JsonRpcResponse::error(
+                                serde_json::Value::Null,
+                                -32700,
+                                format!("Parse error: {e}"),
+                            );
+                            self.send_response(&mut stdout, err_resp).await?;
+                            continue;
+                        }
+                    };
+
+                    if let Some(recorder) = &self.recorder {
+                        recorder.record_request(&request).await?;
+                    }
+
+                    if request.method == CANCEL_METHOD {
+                        let target = request.params.get("id").cloned().unwrap_or_default();
+                        if let Some(running) = running.as_ref().filter(|r| r.id == target) {
+                            running.cancellation.cancel();
+                        } else if let Some(pos) =
+                            queue.iter().position(|r| r.id.as_ref() == Some(&target))
+                        {
+                            let _ = queue.remove(pos);
+                            let cancelled = JsonRpcResponse::error(
+                                target,
+                                -32800,
+                                "Request cancelled".to_string(),
+                            );
+                            self.send_response(&mut stdout, cancelled).await?;
+                        }
+                    } else {
+                        queue.push_back(request);
+                    }
+                }
+                Some(progress) = progress_rx.recv() => {
+                    self.send_progress(&mut stdout, progress).await?;
+                }
+                response = async { running.as_mut().expect("guarded").response.as_mut().await },
+                    if running.is_some() => {
+                    running = None;
+                    while let Ok(progress) = progress_rx.try_recv() {
+                        self.send_progress(&mut stdout, progress).await?;
+                    }
+                    self.send_response(&mut stdout, response).await?;
+                }
+            }
         }
 
         Ok(())
     }
 
+    async fn send_progress(
+        &self,
+        stdout: &mut io::Stdout,
+        progress: Progress,
+    ) -> crate::utils::Result<()> {
+        let notification = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: PROGRESS_METHOD.to_string(),
+            params: serde_json::to_value(progress)?,
+            id: None,
+        };
+        let mut json = serde_json::to_string(&notification)?;
+        json.push('\n');
+        stdout.write_all(json.as_bytes()).await?;
+        stdout.flush().await?;
+        Ok(())
+    }
+
     async fn send_response(
         &self,
         stdout: &mut io::Stdout,
         response: JsonRpcResponse,
     ) -> crate::utils::Result<()> {
+        if let Some(recorder) = &self.recorder {
+            recorder.record_response(&response).await?;
+        }
+
         let mut json = serde_json::to_string(&response)?;
         json.push('\n');
         stdout.write_all(json.as_bytes()).await?;
@@ -0,0 +1,301 @@
+use crate::provider::{
+    ApplyError, ApplyRequest, ApplyResponse, ApplyResult, DiscoverRequest, DiscoverResponse,
+    PlanRequest, PlanResponse, ProviderCapabilities, ReadDataSourceRequest, ReadDataSourceResponse,
+    RefreshRequest, RefreshResponse,
+};
+use crate::server::client::JsonRpcClient;
+use crate::types::{Change, Diagnostic, ResourceAddress};
+use crate::utils::{Error, Result};
+use futures::future::try_join_all;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use tokio::sync::Mutex;
+
+struct PooledProvider {
+    client: Mutex<JsonRpcClient>,
+    capabilities: ProviderCapabilities,
+}
+
+/// Manages several provider processes and routes calls to them by resource type,
+/// using the `supported_resources` each provider advertises.
+#[derive(Default)]
+pub struct ProviderPool {
+    providers: BTreeMap<String, PooledProvider>,
+    routes: HashMap<String, String>,
+}
+
+impl ProviderPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Launches a provider executable and registers it under the given name.
+    pub async fn launch(&mut self, name: &str, executable_path: &str) -> Result<()> {
+        let client = JsonRpcClient::launch(executable_path)?;
+        self.register(name, client).await
+    }
+
+    /// Registers an already connected client (for example a replayed cassette).
+    /// The provider's capabilities are queried to build the routing table.
+    pub async fn register(&mut self, name: &str, mut client: JsonRpcClient) -> Result<()> {
+        if self.providers.contains_key(name) {
+            return Err(Error::Config(format!(
+                "Provider '{name}' is already registered"
+            )));
+        }
+
+        let capabilities: ProviderCapabilities = client.call("capabilities", ()).await?;
+        for resource_type in &capabilities.supported_resources {
+            if let Some(existing) = self.routes.get(resource_type) {
+                return Err(Error::Config(format!(
+                    "Resource type '{resource_type}' is supported by both '{existing}' and '{name}'"
+                )));
+            }
+        }
+
+        for resource_type in &capabilities.supported_resources {
+            let _ = self.routes.insert(resource_type.clone(), name.to_string());
+        }
+        let _ = self.providers.insert(
+            name.to_string(),
+            PooledProvider {
+                client: Mutex::new(client),
+                capabilities,
+            },
+        );
+        Ok(())
+    }
+
+    /// Names of all registered providers, in sorted order.
+    pub fn provider_names(&self) -> Vec<&str> {
+        self.providers.keys().map(String::as_str).collect()
+    }
+
+    pub fn capabilities(&self, provider: &str) -> Option<&ProviderCapabilities> {
+        self.providers.get(provider).map(|p| &p.capabilities)
+    }
+
+    /// Returns the name of the provider that handles the given resource type.
+    pub fn provider_for(&self, resource_type: &str) -> Option<&str> {
+        self.routes.get(resource_type).map(String::as_str)
+    }
+
+    /// Checks changes against the capabilities of the providers that handle them.
+    /// Changes for resource types no provider supports are reported as errors.
+    pub fn check_changes(&self, changes: &[Change]) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        for change in changes {
+            match self
+                .provider_for(&change.address.resource_type)
+                .and_then(|provider| self.capabilities(provider))
+            {
+                Some(capabilities) => {
+                    diagnostics.extend(capabilities.check_changes(std::slice::from_ref(change)))
+                }
+                None => diagnostics.push(
+                    Diagnostic::error(format!(
+                        "No provider registered for resource type '{}'",
+                        change.address.resource_type
+                    ))
+                    .with_address(change.address.clone())
+                    .with_code("UNSUPPORTED_RESOURCE_TYPE"),
+                ),
+            }
+        }
+        diagnostics
+    }
+
+    fn route(&self, address: &ResourceAddress) -> Result<&str> {
+        self.provider_for(&address.resource_type).ok_or_else(|| {
+            Error::Config(format!(
+                "No provider registered for resource type '{}' ({address})",
+                address.resource_type
+            ))
+        })
+    }
+
+    /// Calls a method on the named provider.
+    pub async fn call<P, R>(&self, provider: &str, method: &str, params: P) -> Result<R>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        let pooled = self
+            .providers
+            .get(provider)
+            .ok_or_else(|| Error::Config(format!("Unknown provider: {provider}")))?;
+        pooled.client.lock().await.call(method, params).await
+    }
+
+    /// Calls a method on the provider that handles the address' resource type.
+    pub async fn call_for<P, R>(
+        &self,
+        address: &ResourceAddress,
+        method: &str,
+        params: P,
+    ) -> Result<R>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        let provider = self.route(address)?;
+        self.call(provider, method, params).await
+    }
+
+    /// Splits the plan by provider, plans each part concurrently and merges the changes.
+    /// Every part receives all data sources of the request.
+    pub async fn plan(&self, request: PlanRequest) -> Result<PlanResponse> {
+        let mut parts: BTreeMap<&str, PlanRequest> = BTreeMap::new();
+        let empty_part = || PlanRequest {
+            workspace_path: request.workspace_path.clone(),
+            desired_state: vec![],
+            current_state: vec![],
+            data_sources: request.data_sources.clone(),
+        };
+
+        for resource in &request.desired_state {
+            let provider = self.route(&resource.address)?;
+            parts
+                .entry(provider)
+                .or_insert_with(empty_part)
+                .desired_state
+                .push(resource.clone());
+        }
+        for resource in &request.current_state {
+            let provider = self.route(&resource.address)?;
+            parts
+                .entry(provider)
+                .or_insert_with(empty_part)
+                .current_state
+                .push(resource.clone());
+        }
+
+        let responses = try_join_all(parts.into_iter().map(|(provider, part)| async move {
+            self.call::<_, PlanResponse>(provider, "plan", part).await
+        }))
+        .await?;
+
+        let mut merged = PlanResponse {
+            changes: vec![],
+            diagnostics: vec![],
+        };
+        for response in responses {
+            merged.changes.extend(response.changes);
+            merged.diagnostics.extend(response.diagnostics);
+        }
+        Ok(merged)
+    }
+
+    /// Applies the changes in order, sending each run of consecutive changes handled by
+    /// the same provider as one request, so a change is only applied once the changes
+    /// before it are. Results keep the order of `request.changes`; if a provider call
+    /// fails, its changes are reported as failed and the remaining runs still apply.
+    pub async fn apply(&self, request: ApplyRequest) -> Result<ApplyResponse> {
+        let mut runs: Vec<(&str, Vec<Change>)> = vec![];
+        for change in request.changes {
+            let provider = self.route(&change.address)?;
+            match runs.last_mut() {
+                Some((last, changes)) if *last == provider => changes.push(change),
+                _ => runs.push((provider, vec![change])),
+            }
+        }
+
+        let mut merged = ApplyResponse {
+            successful_addresses: vec![],
+            diagnostics: vec![],
+            results: vec![],
+        };
+        for (provider, changes) in runs {
+            let addresses: Vec<ResourceAddress> =
+                changes.iter().map(|c| c.address.clone()).collect();
+            let response = match self
+                .call::<_, ApplyResponse>(provider, "apply", ApplyRequest { changes })
+                .await
+            {
+                Ok(response) => response,
+                Err(e) => ApplyResponse::from_results(
+                    addresses
+                        .into_iter()
+                        .map(|address| {
+                            let error = format!("Provider '{provider}' failed to apply: {e}");
+                            ApplyResult::failed(address, ApplyError::new(error))
+                        })
+                        .collect(),
+                ),
+            };
+            merged
+                .successful_addresses
+                .extend(response.successful_addresses);
+            merged.diagnostics.extend(response.diagnostics);
+            merged.results.extend(response.results);
+        }
+        Ok(merged)
+    }
+
+    /// Reads a data source from the provider that advertises its type.
+    pub async fn read_data_source(
+        &self,
+        request: ReadDataSourceRequest,
+    ) -> Result<ReadDataSourceResponse> {
+        let data_source_type = &request.address.resource_type;
+        let provider = self
+            .providers
+            .iter()
+            .find(|(_, pooled)| {
+                pooled
+                    .capabilities
+                    .data_sources
+                    .iter()
+                    .any(|d| &d.data_source_type == data_source_type)
+            })
+            .map(|(name, _)| name.as_str())
+            .ok_or_else(|| {
+                Error::Config(format!(
+                    "No provider registered for data source type '{data_source_type}'"
+                ))
+            })?;
+        self.call(provider, "read_data_source", request).await
+    }
+
+    /// Splits the resources by provider, refreshes each part concurrently and merges the results.
+    pub async fn refresh(&self, request: RefreshRequest) -> Result<RefreshResponse> {
+        let mut parts: BTreeMap<&str, Vec<_>> = BTreeMap::new();
+        for resource in request.resources {
+            let provider = self.route(&resource.address)?;
+            parts.entry(provider).or_default().push(resource);
+        }
+
+        let responses = try_join_all(parts.into_iter().map(|(provider, resources)| async move {
+            self.call::<_, RefreshResponse>(provider, "refresh", RefreshRequest { resources })
+                .await
+        }))
+        .await?;
+
+        Ok(RefreshResponse {
+            resources: responses.into_iter().flat_map(|r| r.resources).collect(),
+        })
+    }
+
+    /// Lists remote objects using the provider that handles the requested resource type.
+    pub async fn discover(&self, request: DiscoverRequest) -> Result<DiscoverResponse> {
+        let provider = self.provider_for(&request.resource_type).ok_or_else(|| {
+            Error::Config(format!(
+                "No provider registered for resource type '{}'",
+                request.resource_type
+            ))
+        })?;
+        self.call(provider, "discover", request).await
+    }
+
+    /// Shuts every provider down, returning the first error encountered.
+    pub async fn shutdown(self) -> Result<()> {
+        let results = futures::future::join_all(
+            self.providers
+                .into_values()
+                .map(|p| p.client.into_inner().shutdown()),
+        )
+        .await;
+        results.into_iter().collect()
+    }
+}
@@ -0,0 +1,225 @@
+use crate::server::types::{JsonRpcRequest, JsonRpcResponse};
+use crate::utils::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, Weak};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// Environment variable that enables recording on `JsonRpcServer` (provider side).
+pub const SERVER_RECORD_ENV: &str = "IAMCTL_RECORD_PROVIDER";
+
+/// Environment variable that enables recording on `JsonRpcClient` (engine side).
+pub const CLIENT_RECORD_ENV: &str = "IAMCTL_RECORD_CLIENT";
+
+/// Direction of a recorded JSON-RPC message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Request,
+    Response,
+}
+
+/// A single line of a JSONL protocol transcript.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedMessage {
+    pub timestamp: DateTime<Utc>,
+    pub direction: Direction,
+    pub message: serde_json::Value,
+}
+
+type Transcript = Arc<Mutex<tokio::fs::File>>;
+
+/// Transcripts open in this process by canonical path, so every recorder of a file
+/// shares one handle and its lock.
+static TRANSCRIPTS: OnceLock<std::sync::Mutex<HashMap<PathBuf, Weak<Mutex<tokio::fs::File>>>>> =
+    OnceLock::new();
+
+/// Appends every JSON-RPC request and response to a JSONL transcript.
+#[derive(Clone)]
+pub struct Recorder {
+    path: PathBuf,
+    file: Transcript,
+}
+
+impl Recorder {
+    /// Opens (or creates) a transcript file in append mode. Recorders created for the
+    /// same file, e.g. by every client of a `ProviderPool`, share it and never
+    /// interleave lines.
+    pub fn create<P: Into<PathBuf>>(path: P) -> Result<Self> {
+        let path = path.into();
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| {
+                Error::Internal(format!("Failed to open transcript {}: {e}", path.display()))
+            })?;
+
+        let key = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        let mut transcripts = TRANSCRIPTS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        transcripts.retain(|_, transcript| transcript.strong_count() > 0);
+        let file = match transcripts.get(&key).and_then(Weak::upgrade) {
+            Some(shared) => shared,
+            None => {
+                let opened = Arc::new(Mutex::new(tokio::fs::File::from_std(file)));
+                let _ = transcripts.insert(key, Arc::downgrade(&opened));
+                opened
+            }
+        };
+        Ok(Self { path, file })
+    }
+
+    /// Creates a recorder from the given environment variable, if it is set.
+    pub fn from_env(var: &str) -> Result<Option<Self>> {
+        match std::env::var_os(var) {
+            Some(path) if !path.is_empty() => Self::create(PathBuf::from(path)).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub async fn record_request(&self, request: &JsonRpcRequest) -> Result<()> {
+        self.record(Direction::Request, serde_json::to_value(request)?)
+            .await
+    }
+
+    pub async fn record_response(&self, response: &JsonRpcResponse) -> Result<()> {
+        self.record(Direction::Response, serde_json::to_value(response)?)
+            .await
+    }
+
+    async fn record(&self, direction: Direction, message: serde_json::Value) -> Result<()> {
+        let entry = RecordedMessage {
+            timestamp: Utc::now(),
+            direction,
+            message,
+        };
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+
+        // Hold the lock across the write so concurrent writers never interleave lines.
+        let mut file = self.file.lock().await;
+        file.write_all(line.as_bytes()).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+/// A recorded request together with the response the provider sent for it.
+#[derive(Debug, Clone)]
+pub struct Interaction {
+    pub request: JsonRpcRequest,
+    pub response: JsonRpcResponse,
+}
+
+/// Recorded interactions that can be served back in place of a provider process.
+#[derive(Debug, Clone, Default)]
+pub struct Cassette {
+    interactions: VecDeque<Interaction>,
+}
+
+impl Cassette {
+    /// Loads a cassette from a JSONL transcript written by [`Recorder`].
+    pub async fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = tokio::fs::read_to_string(path).await.map_err(|e| {
+            Error::Internal(format!("Failed to read cassette {}: {e}", path.display()))
+        })?;
+        Self::parse(&content)
+    }
+
+    /// Parses a JSONL transcript, pairing each request with the response carrying its id.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut messages = vec![];
+        for (idx, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let message: RecordedMessage = serde_json::from_str(line).map_err(|e| {
+                Error::Protocol(format!("Invalid transcript entry on line {}: {e}", idx + 1))
+            })?;
+            messages.push(message);
+        }
+        Self::from_messages(messages)
+    }
+
+    /// Pairs recorded requests with their responses by JSON-RPC id, so pipelined
+    /// requests answered later are still matched. Notifications (requests without an
+    /// id, e.g. `$/cancelRequest`) get no response and are skipped. Interactions are
+    /// kept in the order of their responses.
+    pub fn from_messages(messages: Vec<RecordedMessage>) -> Result<Self> {
+        let mut interactions = VecDeque::new();
+        let mut pending: Vec<JsonRpcRequest> = vec![];
+
+        for message in messages {
+            match message.direction {
+                Direction::Request => {
+                    let request: JsonRpcRequest = serde_json::from_value(message.message)?;
+                    if request.id.is_some() {
+                        pending.push(request);
+                    }
+                }
+                Direction::Response => {
+                    let response: JsonRpcResponse = serde_json::from_value(message.message)?;
+                    // Responses to no recorded request (e.g. parse errors) cannot be replayed.
+                    if let Some(position) = pending
+                        .iter()
+                        .position(|r| r.id.as_ref() == Some(&response.id))
+                    {
+                        interactions.push_back(Interaction {
+                            request: pending.remove(position),
+                            response,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(Self { interactions })
+    }
+
+    pub fn push(&mut self, request: JsonRpcRequest, response: JsonRpcResponse) {
+        self.interactions
+            .push_back(Interaction { request, response });
+    }
+
+    pub fn len(&self) -> usize {
+        self.interactions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.interactions.is_empty()
+    }
+
+    /// Serves the first unused interaction whose method and params match the request.
+    /// The response id is rewritten to the id of the incoming request.
+    pub fn respond(&mut self, request: &JsonRpcRequest) -> Result<JsonRpcResponse> {
+        let position = self
+            .interactions
+            .iter()
+            .position(|i| i.request.method == request.method && i.request.params == request.params)
+            .ok_or_else(|| {
+                Error::Protocol(format!(
+                    "No recorded interaction for method '{}' with matching params",
+                    request.method
+                ))
+            })?;
+
+        let mut response = self
+            .interactions
+            .remove(position)
+            .map(|i| i.response)
+            .ok_or_else(|| Error::Internal("Cassette interaction vanished".to_string()))?;
+        response.id = request.id.clone().unwrap_or(serde_json::Value::Null);
+        Ok(response)
+    }
+}
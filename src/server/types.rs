@@ -48,4 +48,18 @@ impl JsonRpcResponse {
             id,
         }
     }
+
+    /// Builds an error response carrying structured `data`, e.g. `{"diagnostics": [...]}`.
+    pub fn error_with_data(
+        id: serde_json::Value,
+        code: i32,
+        message: String,
+        data: serde_json::Value,
+    ) -> Self {
+        let mut response = Self::error(id, code, message);
+        if let Some(error) = response.error.as_mut() {
+            error.data = Some(data);
+        }
+        response
+    }
 }
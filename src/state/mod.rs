@@ -12,9 +12,10 @@ pub struct State {
     /// Version of the state schema.
     pub version: u32,
     /// Map of resource addresses to resource data.
+    #[serde(serialize_with = "crate::utils::sorted::serialize")]
     pub resources: HashMap<String, Resource>,
     /// Optional metadata about the state.
-    #[serde(default)]
+    #[serde(default, serialize_with = "crate::utils::sorted::serialize")]
     pub metadata: HashMap<String, String>,
 }
 
@@ -0,0 +1,207 @@
+use crate::utils::Error;
+use schemars::JsonSchema;
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Represents a stable address for a resource in the workspace.
+///
+/// The string form is `type.name` or `type.namespace/name` (e.g. `realm.demo`,
+/// `client.demo/my-app`):
+///
+/// ```text
+/// address   = type "." [ namespace "/" ] name
+/// ```
+///
+/// The type ends at the first unescaped `.`, and the namespace at the first unescaped
+/// `/`. A backslash escapes the next character: `\.` in the type, `\/` in the namespace
+/// or name, and `\\`. `Display` escapes exactly these characters, so every valid address
+/// round-trips through [`FromStr`]. Components must be non-empty and free of control
+/// characters.
+///
+/// Addresses deserialize from either the object form (the wire format) or the string
+/// form; see [`compact`] to also serialize the string form.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, JsonSchema)]
+pub struct ResourceAddress {
+    pub resource_type: String,
+    pub name: String,
+    pub namespace: Option<String>,
+}
+
+impl ResourceAddress {
+    /// Checks that the components are non-empty and free of control characters.
+    pub fn validate(&self) -> Result<(), Error> {
+        let invalid = |reason: String| Error::InvalidAddress {
+            input: self.to_string(),
+            reason,
+        };
+        let components = [
+            ("resource type", Some(&self.resource_type)),
+            ("namespace", self.namespace.as_ref()),
+            ("name", Some(&self.name)),
+        ];
+        for (component, value) in components {
+            let Some(value) = value else {
+                continue;
+            };
+            if value.is_empty() {
+                return Err(invalid(format!("the {component} is empty")));
+            }
+            if value.chars().any(char::is_control) {
+                return Err(invalid(format!(
+                    "the {component} contains a control character"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ResourceAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_escaped(f, &self.resource_type, '.')?;
+        f.write_str(".")?;
+        if let Some(ref ns) = self.namespace {
+            write_escaped(f, ns, '/')?;
+            f.write_str("/")?;
+        }
+        write_escaped(f, &self.name, '/')
+    }
+}
+
+fn write_escaped(f: &mut fmt::Formatter<'_>, value: &str, separator: char) -> fmt::Result {
+    for c in value.chars() {
+        if c == '\\' || c == separator {
+            f.write_str("\\")?;
+        }
+        write!(f, "{c}")?;
+    }
+    Ok(())
+}
+
+impl FromStr for ResourceAddress {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| Error::InvalidAddress {
+            input: input.to_string(),
+            reason: reason.to_string(),
+        };
+
+        // Unescape while remembering which characters were escaped.
+        let mut chars = vec![];
+        let mut iter = input.chars();
+        while let Some(c) = iter.next() {
+            if c != '\\' {
+                chars.push((c, false));
+                continue;
+            }
+            match iter.next() {
+                Some(escaped @ ('\\' | '.' | '/')) => chars.push((escaped, true)),
+                Some(_) => return Err(invalid("only `\\\\`, `\\.` and `\\/` can be escaped")),
+                None => return Err(invalid("trailing backslash")),
+            }
+        }
+
+        let collect = |chars: &[(char, bool)]| chars.iter().map(|(c, _)| c).collect::<String>();
+        let separator = |sep: char| move |&(c, escaped): &(char, bool)| c == sep && !escaped;
+
+        let dot = chars
+            .iter()
+            .position(separator('.'))
+            .ok_or_else(|| invalid("expected `type.name` or `type.namespace/name`"))?;
+        let rest = &chars[dot + 1..];
+        let slashes: Vec<_> = rest
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| separator('/')(c))
+            .map(|(i, _)| i)
+            .collect();
+        let (namespace, name) = match slashes.as_slice() {
+            [] => (None, collect(rest)),
+            [slash] => (Some(collect(&rest[..*slash])), collect(&rest[slash + 1..])),
+            _ => return Err(invalid("more than one `/`; escape `/` in names as `\\/`")),
+        };
+
+        let address = Self {
+            resource_type: collect(&chars[..dot]),
+            name,
+            namespace,
+        };
+        address.validate().map_err(|e| match e {
+            Error::InvalidAddress { reason, .. } => invalid(&reason),
+            other => other,
+        })?;
+        Ok(address)
+    }
+}
+
+impl TryFrom<&str> for ResourceAddress {
+    type Error = Error;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        input.parse()
+    }
+}
+
+/// The object form, kept as the derived representation.
+#[derive(Deserialize)]
+struct AddressFields {
+    resource_type: String,
+    name: String,
+    #[serde(default)]
+    namespace: Option<String>,
+}
+
+impl<'de> Deserialize<'de> for ResourceAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AddressVisitor;
+
+        impl<'de> Visitor<'de> for AddressVisitor {
+            type Value = ResourceAddress;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a resource address object or string")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                value.parse().map_err(E::custom)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                let fields =
+                    AddressFields::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                let address = ResourceAddress {
+                    resource_type: fields.resource_type,
+                    name: fields.name,
+                    namespace: fields.namespace,
+                };
+                address.validate().map_err(de::Error::custom)?;
+                Ok(address)
+            }
+        }
+
+        deserializer.deserialize_any(AddressVisitor)
+    }
+}
+
+/// Serializes a [`ResourceAddress`] in its string form:
+/// `#[serde(with = "iamctl_rust_sdk::types::address::compact")]`.
+pub mod compact {
+    use super::ResourceAddress;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        address: &ResourceAddress,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(address)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ResourceAddress, D::Error> {
+        ResourceAddress::deserialize(deserializer)
+    }
+}
@@ -3,31 +3,21 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
 
-/// Represents a stable address for a resource in the workspace.
-/// Address format: type.name (e.g., realm.demo, client.demo/my-app)
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
-pub struct ResourceAddress {
-    pub resource_type: String,
-    pub name: String,
-    pub namespace: Option<String>,
-}
+pub mod address;
+pub mod patch;
+pub mod unknown;
 
-impl fmt::Display for ResourceAddress {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Some(ref ns) = self.namespace {
-            write!(f, "{}.{}/{}", self.resource_type, ns, self.name)
-        } else {
-            write!(f, "{}.{}", self.resource_type, self.name)
-        }
-    }
-}
+pub use address::ResourceAddress;
+pub use patch::PatchOperation;
+pub use unknown::MaybeUnknown;
 
 /// Represents the desired state of a resource as defined in configuration.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct Resource {
     pub address: ResourceAddress,
+    #[serde(serialize_with = "crate::utils::sorted::serialize")]
     pub spec: HashMap<String, serde_json::Value>,
-    #[serde(default)]
+    #[serde(default, serialize_with = "crate::utils::sorted::serialize")]
     pub metadata: HashMap<String, String>,
 }
 
@@ -38,6 +28,19 @@ pub enum ChangeType {
     Update,
     Delete,
     NoOp,
+    /// Destroys and recreates the resource, e.g. because a force-new attribute changed.
+    Replace,
+}
+
+/// Order of the two steps of a `ChangeType::Replace`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplaceOrder {
+    /// Deletes the existing object first; required when names must be unique.
+    #[default]
+    DestroyBeforeCreate,
+    /// Creates the new object first, so dependents never see it missing.
+    CreateBeforeDestroy,
 }
 
 /// Represents a calculated diff between desired and current state.
@@ -47,6 +50,139 @@ pub struct Change {
     pub change_type: ChangeType,
     pub before: Option<Resource>,
     pub after: Option<Resource>,
+    /// Attributes (JSON Pointers into the spec) the provider fills in.
     #[serde(default)]
     pub computed_fields: Vec<String>,
+    /// Step order for `ChangeType::Replace`; `None` means the default order.
+    #[serde(default)]
+    pub replace_order: Option<ReplaceOrder>,
+    /// Per-attribute changes in path order, as computed by the planner.
+    #[serde(default)]
+    pub attribute_changes: Vec<AttributeChange>,
+    /// JSON Patch (RFC 6902) from `before` to `after` for `ChangeType::Update`, for
+    /// providers sending minimal updates upstream.
+    #[serde(default)]
+    pub patch: Option<Vec<PatchOperation>>,
+}
+
+impl Change {
+    /// The JSON Merge Patch (RFC 7396) view of [`Change::patch`]; see [`patch::merge_patch`].
+    pub fn merge_patch(&self) -> Option<serde_json::Value> {
+        self.patch.as_deref().and_then(patch::merge_patch)
+    }
+}
+
+/// A change to one attribute of a resource spec.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct AttributeChange {
+    /// JSON Pointer into the spec, e.g. `/settings/theme`.
+    pub path: String,
+    /// Value before the change; `None` if the attribute is added.
+    pub before: Option<serde_json::Value>,
+    /// Value after the change; `None` if the attribute is removed.
+    pub after: Option<serde_json::Value>,
+    /// True if changing this attribute requires replacing the resource.
+    #[serde(default)]
+    pub force_new: bool,
+}
+
+/// Severity of a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem reported by validate, plan or apply, pointing at the resource and
+/// attribute it concerns where known.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// One-line description of the problem.
+    pub summary: String,
+    #[serde(default)]
+    pub detail: Option<String>,
+    #[serde(default)]
+    pub address: Option<ResourceAddress>,
+    /// Path of the offending attribute, e.g. `spec/credentials/secret`.
+    #[serde(default)]
+    pub attribute: Option<String>,
+    /// Machine-readable code, e.g. `SCHEMA_VALIDATION_ERROR`.
+    #[serde(default)]
+    pub code: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn error(summary: impl Into<String>) -> Self {
+        Self::new(Severity::Error, summary)
+    }
+
+    pub fn warning(summary: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, summary)
+    }
+
+    fn new(severity: Severity, summary: impl Into<String>) -> Self {
+        Self {
+            severity,
+            summary: summary.into(),
+            detail: None,
+            address: None,
+            attribute: None,
+            code: None,
+        }
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    pub fn with_address(mut self, address: ResourceAddress) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    pub fn with_attribute(mut self, attribute: impl Into<String>) -> Self {
+        self.attribute = Some(attribute.into());
+        self
+    }
+
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Returns true if any of the diagnostics is an error.
+    pub fn any_errors(diagnostics: &[Diagnostic]) -> bool {
+        diagnostics.iter().any(Diagnostic::is_error)
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{severity}")?;
+        if let Some(address) = &self.address {
+            write!(f, " [{address}")?;
+            if let Some(attribute) = &self.attribute {
+                write!(f, " {attribute}")?;
+            }
+            write!(f, "]")?;
+        } else if let Some(attribute) = &self.attribute {
+            write!(f, " [{attribute}]")?;
+        }
+        write!(f, ": {}", self.summary)?;
+        if let Some(detail) = &self.detail {
+            write!(f, " ({detail})")?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,293 @@
+use super::{AttributeChange, Resource};
+use crate::utils::Error;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// One operation of a JSON Patch (RFC 6902). Paths are JSON Pointers (RFC 6901) into
+/// the resource spec, e.g. `/settings/locale`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+impl PatchOperation {
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Add { path, .. }
+            | Self::Remove { path }
+            | Self::Replace { path, .. }
+            | Self::Move { path, .. }
+            | Self::Copy { path, .. }
+            | Self::Test { path, .. } => path,
+        }
+    }
+}
+
+/// Builds a JSON Pointer (RFC 6901) from reference tokens, escaping `~` and `/`:
+/// `["settings", "locale"]` becomes `/settings/locale`.
+pub fn pointer<S: AsRef<str>>(tokens: &[S]) -> String {
+    tokens
+        .iter()
+        .map(|token| child("", token.as_ref()))
+        .collect()
+}
+
+/// Appends a reference token to a JSON Pointer.
+pub(crate) fn child(pointer: &str, token: &str) -> String {
+    format!("{pointer}/{}", token.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Splits a JSON Pointer into its unescaped reference tokens.
+pub(crate) fn tokens(pointer: &str) -> Result<Vec<String>, String> {
+    if pointer.is_empty() {
+        return Ok(vec![]);
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err("a JSON Pointer must start with `/`".to_string());
+    };
+    Ok(rest
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// Builds the JSON Patch turning `before` into the desired spec, given the attribute
+/// changes between them (see [`crate::plan::Differ`]).
+///
+/// Attributes the diff leaves alone, such as computed or ignored ones, are left alone
+/// by the patch too. Values missing from `before` are added, creating parent objects
+/// as needed, so the patch always applies to `before`.
+pub fn json_patch(before: &Resource, changes: &[AttributeChange]) -> Vec<PatchOperation> {
+    let mut working = Value::Object(before.spec.clone().into_iter().collect());
+    let mut patch = vec![];
+    for change in changes {
+        let keys = match tokens(&change.path) {
+            Ok(keys) if !keys.is_empty() => keys,
+            _ => continue,
+        };
+        let path = change.path.clone();
+        let present = working.pointer(&path).is_some_and(|v| !v.is_null());
+        let operation = match &change.after {
+            None if present => PatchOperation::Remove { path },
+            None => continue,
+            Some(value) if present => PatchOperation::Replace {
+                path,
+                value: value.clone(),
+            },
+            Some(value) => add_with_parents(&working, &keys, value.clone()),
+        };
+        // The operation was built against `working`, so it always applies.
+        let _ = apply_operation(&mut working, &operation);
+        patch.push(operation);
+    }
+    patch
+}
+
+/// Adds `value` at the path made of `keys`, wrapping it in objects for each missing
+/// parent.
+fn add_with_parents(working: &Value, keys: &[String], value: Value) -> PatchOperation {
+    // The deepest ancestor that is an object; everything below it is created.
+    let mut depth = 0;
+    let mut current = working;
+    while depth < keys.len() - 1 {
+        match current.get(&keys[depth]) {
+            Some(next @ Value::Object(_)) => {
+                current = next;
+                depth += 1;
+            }
+            _ => break,
+        }
+    }
+    let value = keys[depth + 1..].iter().rev().fold(value, |value, key| {
+        Value::Object(Map::from_iter([(key.clone(), value)]))
+    });
+    let path = pointer(&keys[..=depth]);
+    match current.get(&keys[depth]) {
+        Some(Value::Null) => PatchOperation::Replace { path, value },
+        _ => PatchOperation::Add { path, value },
+    }
+}
+
+/// Builds the JSON Merge Patch (RFC 7396) equivalent of a JSON Patch made of `add`,
+/// `replace` and `remove` operations on object members, as produced by [`json_patch`].
+/// Returns `None` if the patch uses other operations or array indices.
+pub fn merge_patch(patch: &[PatchOperation]) -> Option<Value> {
+    let mut merge = Value::Object(Map::new());
+    for operation in patch {
+        let (path, value) = match operation {
+            PatchOperation::Add { path, value } | PatchOperation::Replace { path, value } => {
+                (path, value.clone())
+            }
+            PatchOperation::Remove { path } => (path, Value::Null),
+            _ => return None,
+        };
+        let tokens = tokens(path).ok()?;
+        let (last, parents) = tokens.split_last()?;
+        let mut current = &mut merge;
+        for key in parents {
+            let map = current.as_object_mut()?;
+            let entry = map
+                .entry(key.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if !entry.is_object() {
+                return None;
+            }
+            current = entry;
+        }
+        // `json_patch` only sets objects where the spec holds no object, so merging
+        // them is equivalent to replacing.
+        let _ = current.as_object_mut()?.insert(last.clone(), value);
+    }
+    Some(merge)
+}
+
+/// Applies a JSON Patch to the spec of a resource. The patch applies atomically: on
+/// error, no change is made.
+pub fn apply_patch(resource: &Resource, patch: &[PatchOperation]) -> Result<Resource, Error> {
+    let mut spec = Value::Object(resource.spec.clone().into_iter().collect());
+    for operation in patch {
+        apply_operation(&mut spec, operation).map_err(|reason| Error::InvalidPatch {
+            path: operation.path().to_string(),
+            reason,
+        })?;
+    }
+    with_spec(resource, spec)
+}
+
+/// Applies a JSON Merge Patch to the spec of a resource: `null` members are removed,
+/// objects are merged recursively and other values replace the existing ones.
+pub fn apply_merge_patch(resource: &Resource, patch: &Value) -> Result<Resource, Error> {
+    let mut spec = Value::Object(resource.spec.clone().into_iter().collect());
+    merge(&mut spec, patch);
+    with_spec(resource, spec)
+}
+
+fn merge(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(target) = target else {
+        unreachable!("target was just made an object")
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            let _ = target.remove(key);
+        } else {
+            merge(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+fn with_spec(resource: &Resource, spec: Value) -> Result<Resource, Error> {
+    let Value::Object(spec) = spec else {
+        return Err(Error::InvalidPatch {
+            path: String::new(),
+            reason: "the patched spec is not an object".to_string(),
+        });
+    };
+    Ok(Resource {
+        address: resource.address.clone(),
+        spec: spec.into_iter().collect(),
+        metadata: resource.metadata.clone(),
+    })
+}
+
+fn apply_operation(target: &mut Value, operation: &PatchOperation) -> Result<(), String> {
+    match operation {
+        PatchOperation::Add { path, value } => add(target, path, value.clone()),
+        PatchOperation::Remove { path } => remove(target, path).map(|_| ()),
+        PatchOperation::Replace { path, value } => {
+            let slot = target
+                .pointer_mut(path)
+                .ok_or_else(|| "the target does not exist".to_string())?;
+            *slot = value.clone();
+            Ok(())
+        }
+        PatchOperation::Move { from, path } => {
+            if path.starts_with(&format!("{from}/")) {
+                return Err("cannot move a value into itself".to_string());
+            }
+            let value = remove(target, from)?;
+            add(target, path, value)
+        }
+        PatchOperation::Copy { from, path } => {
+            let value = target
+                .pointer(from)
+                .cloned()
+                .ok_or_else(|| format!("the source '{from}' does not exist"))?;
+            add(target, path, value)
+        }
+        PatchOperation::Test { path, value } => match target.pointer(path) {
+            Some(actual) if actual == value => Ok(()),
+            Some(actual) => Err(format!("expected {value}, found {actual}")),
+            None => Err("the target does not exist".to_string()),
+        },
+    }
+}
+
+/// Splits a pointer into the parent value and the last reference token.
+fn parent<'a>(target: &'a mut Value, path: &str) -> Result<(&'a mut Value, String), String> {
+    let mut tokens = tokens(path)?;
+    let last = tokens
+        .pop()
+        .ok_or_else(|| "the whole document cannot be added or removed".to_string())?;
+    let parent_path = pointer(&tokens);
+    let parent = target
+        .pointer_mut(&parent_path)
+        .ok_or_else(|| "the parent does not exist".to_string())?;
+    Ok((parent, last))
+}
+
+fn add(target: &mut Value, path: &str, value: Value) -> Result<(), String> {
+    let (parent, key) = parent(target, path)?;
+    match parent {
+        Value::Object(map) => {
+            let _ = map.insert(key, value);
+            Ok(())
+        }
+        Value::Array(items) => {
+            let index = match key.as_str() {
+                "-" => items.len(),
+                _ => array_index(&key, items.len() + 1)?,
+            };
+            items.insert(index, value);
+            Ok(())
+        }
+        _ => Err("the parent is not an object or array".to_string()),
+    }
+}
+
+fn remove(target: &mut Value, path: &str) -> Result<Value, String> {
+    let (parent, key) = parent(target, path)?;
+    match parent {
+        Value::Object(map) => map
+            .remove(&key)
+            .ok_or_else(|| "the target does not exist".to_string()),
+        Value::Array(items) => {
+            let index = array_index(&key, items.len())?;
+            Ok(items.remove(index))
+        }
+        _ => Err("the parent is not an object or array".to_string()),
+    }
+}
+
+fn array_index(token: &str, len: usize) -> Result<usize, String> {
+    let valid = !token.is_empty()
+        && token.chars().all(|c| c.is_ascii_digit())
+        && (token == "0" || !token.starts_with('0'));
+    match token.parse::<usize>() {
+        Ok(index) if valid && index < len => Ok(index),
+        _ => Err(format!("'{token}' is not a valid array index")),
+    }
+}
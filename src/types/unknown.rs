@@ -0,0 +1,172 @@
+use super::patch::child;
+use super::{Change, ChangeType, Diagnostic};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Key of the marker object standing for a value only known after apply:
+/// `{"$iamctl_unknown": true}`.
+pub const UNKNOWN_KEY: &str = "$iamctl_unknown";
+
+/// Diagnostic code for unknown values reaching apply.
+pub const UNRESOLVED_UNKNOWN_CODE: &str = "UNRESOLVED_UNKNOWN_VALUE";
+
+/// The marker for a value only known after apply, e.g. the ID of a resource not yet
+/// created that another resource refers to.
+pub fn unknown() -> Value {
+    Value::Object(Map::from_iter([(
+        UNKNOWN_KEY.to_string(),
+        Value::Bool(true),
+    )]))
+}
+
+pub fn is_unknown(value: &Value) -> bool {
+    matches!(value, Value::Object(map) if map.len() == 1 && map.get(UNKNOWN_KEY) == Some(&Value::Bool(true)))
+}
+
+/// Returns the JSON Pointers of the unknown values in a spec, in path order. Array
+/// elements are addressed by index, e.g. `/members/0`.
+pub fn unknown_paths(spec: &HashMap<String, Value>) -> Vec<String> {
+    let mut keys: Vec<_> = spec.keys().collect();
+    keys.sort();
+    let mut paths = vec![];
+    for key in keys {
+        collect_unknown(&spec[key], &child("", key), &mut paths);
+    }
+    paths
+}
+
+fn collect_unknown(value: &Value, path: &str, paths: &mut Vec<String>) {
+    match value {
+        _ if is_unknown(value) => paths.push(path.to_string()),
+        Value::Object(map) => {
+            for (key, nested) in map {
+                collect_unknown(nested, &child(path, key), paths);
+            }
+        }
+        Value::Array(items) => {
+            for (index, nested) in items.iter().enumerate() {
+                collect_unknown(nested, &child(path, &index.to_string()), paths);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// True if the value at the JSON Pointer, or one of its parents, is unknown.
+pub(crate) fn is_unknown_at(value: &Value, pointer: &str) -> bool {
+    let mut current = value;
+    if is_unknown(current) {
+        return true;
+    }
+    for token in pointer.split('/').skip(1) {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        let next = match current {
+            Value::Object(map) => map.get(&token),
+            Value::Array(items) => token.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        };
+        match next {
+            Some(next) if is_unknown(next) => return true,
+            Some(next) => current = next,
+            None => return false,
+        }
+    }
+    false
+}
+
+/// Reports every unknown value left in the `after` resources of changes to apply. The
+/// engine must resolve unknowns before apply; providers never see them.
+pub fn unresolved_unknowns(changes: &[Change]) -> Vec<Diagnostic> {
+    changes
+        .iter()
+        .filter(|change| change.change_type != ChangeType::Delete)
+        .filter_map(|change| change.after.as_ref())
+        .flat_map(|after| {
+            unknown_paths(&after.spec).into_iter().map(|path| {
+                Diagnostic::error(format!(
+                    "Attribute '{path}' of {} is still unknown",
+                    after.address
+                ))
+                .with_address(after.address.clone())
+                .with_attribute(format!("spec{path}"))
+                .with_code(UNRESOLVED_UNKNOWN_CODE)
+            })
+        })
+        .collect()
+}
+
+/// A typed spec field that may be unknown until apply, serialized as the value itself
+/// or as the [`unknown`] marker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MaybeUnknown<T> {
+    Known(T),
+    Unknown,
+}
+
+impl<T> MaybeUnknown<T> {
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown)
+    }
+
+    /// The value, if known.
+    pub fn known(self) -> Option<T> {
+        match self {
+            Self::Known(value) => Some(value),
+            Self::Unknown => None,
+        }
+    }
+
+    pub fn as_known(&self) -> Option<&T> {
+        match self {
+            Self::Known(value) => Some(value),
+            Self::Unknown => None,
+        }
+    }
+}
+
+impl<T> From<T> for MaybeUnknown<T> {
+    fn from(value: T) -> Self {
+        Self::Known(value)
+    }
+}
+
+impl<T: Serialize> Serialize for MaybeUnknown<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Known(value) => value.serialize(serializer),
+            Self::Unknown => unknown().serialize(serializer),
+        }
+    }
+}
+
+impl<'de, T: serde::de::DeserializeOwned> Deserialize<'de> for MaybeUnknown<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        if is_unknown(&value) {
+            return Ok(Self::Unknown);
+        }
+        serde_json::from_value(value)
+            .map(Self::Known)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for MaybeUnknown<T> {
+    fn schema_name() -> String {
+        format!("MaybeUnknown_{}", T::schema_name())
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        // Unknown values are accepted wherever the value is, so validation of specs
+        // holding them (see `is_unknown_at`) needs no special schema.
+        T::json_schema(gen)
+    }
+
+    fn is_referenceable() -> bool {
+        false
+    }
+}
@@ -1,3 +1,4 @@
+use crate::config::SourceLocation;
 use crate::types::ResourceAddress;
 use thiserror::Error;
 
@@ -10,6 +11,18 @@ pub enum Error {
     #[error("Resource not found: {0}")]
     ResourceNotFound(ResourceAddress),
 
+    #[error("Invalid resource address '{input}': {reason}")]
+    InvalidAddress { input: String, reason: String },
+
+    #[error("Cannot apply patch at '{path}': {reason}")]
+    InvalidPatch { path: String, reason: String },
+
+    #[error("Invalid plan file: {0}")]
+    InvalidPlanFile(String),
+
+    #[error("Stale plan: the state changed since the plan was created (planned against {planned}, now {current})")]
+    StalePlan { planned: String, current: String },
+
     #[error("Validation failed: {0}")]
     Validation(String),
 
@@ -25,6 +38,42 @@ pub enum Error {
     #[error("Configuration error: {0}")]
     Config(String),
 
+    #[error("Invalid configuration at {location}: {reason}")]
+    InvalidConfig {
+        location: SourceLocation,
+        reason: String,
+    },
+
+    /// `attribute` is a JSON Pointer into the spec, as are those of the errors below.
+    #[error("Undefined variable 'var.{name}' in {address} at '{attribute}'")]
+    UndefinedVariable {
+        address: Box<ResourceAddress>,
+        attribute: String,
+        name: String,
+    },
+
+    #[error("Variable 'var.{name}' in {address} at '{attribute}' {reason}")]
+    InvalidVariable {
+        address: Box<ResourceAddress>,
+        attribute: String,
+        name: String,
+        reason: String,
+    },
+
+    #[error("Invalid interpolation in {address} at '{attribute}': {reason}")]
+    Interpolation {
+        address: Box<ResourceAddress>,
+        attribute: String,
+        reason: String,
+    },
+
+    #[error("Duplicate resource {address} at {duplicate} (first defined at {first})")]
+    DuplicateResource {
+        address: String,
+        first: SourceLocation,
+        duplicate: SourceLocation,
+    },
+
     #[error("Internal error: {0}")]
     Internal(String),
 
@@ -0,0 +1,157 @@
+use crate::types::patch::child;
+use crate::types::{Resource, ResourceAddress};
+use crate::utils::{Error, Result};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Prefix of expressions referencing data sources, e.g. `${data.role.admin.id}`; they are
+/// resolved by providers from `PlanRequest::data_sources`.
+pub(crate) const DATA_PREFIX: &str = "data.";
+
+/// A `${...}` expression of a spec string.
+pub(crate) struct Expression<'a> {
+    /// The text between `${` and `}`, trimmed.
+    pub text: &'a str,
+    /// The attribute holding the string, as a JSON Pointer into the spec.
+    pub attribute: &'a str,
+    /// True if the expression is part of a longer string, so its value is inserted as text.
+    pub embedded: bool,
+}
+
+/// Replaces the `${...}` expressions of the resource's spec strings with the values
+/// `resolve` gives them; expressions it resolves to `None` are kept as written. A string
+/// that is a single expression takes the value, whatever its type; otherwise the value
+/// is inserted into the string. `$${` stands for a literal `${`, except before
+/// expressions starting with `deferred`: those are left to a later pass, which needs the
+/// escape to tell them apart from references, so it is kept.
+pub(crate) fn interpolate_spec(
+    resource: &Resource,
+    deferred: Option<&str>,
+    resolve: &dyn Fn(&Expression) -> Result<Option<Value>>,
+) -> Result<Resource> {
+    let interpolator = Interpolator {
+        address: &resource.address,
+        deferred,
+        resolve,
+    };
+    // Attributes are visited in order, so the error reported first is stable.
+    let attributes: BTreeMap<_, _> = resource.spec.iter().collect();
+    let spec = attributes
+        .into_iter()
+        .map(|(key, value)| Ok((key.clone(), interpolator.value(value, &child("", key))?)))
+        .collect::<Result<_>>()?;
+    Ok(Resource {
+        address: resource.address.clone(),
+        spec,
+        metadata: resource.metadata.clone(),
+    })
+}
+
+/// True if the value can be inserted into a string: a string, number or bool.
+pub(crate) fn insertable(value: &Value) -> bool {
+    matches!(value, Value::String(_) | Value::Number(_) | Value::Bool(_))
+}
+
+/// The kind of a value, for error messages.
+pub(crate) fn describe(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a bool",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "a list",
+        Value::Object(_) => "a map",
+    }
+}
+
+struct Interpolator<'a> {
+    address: &'a ResourceAddress,
+    deferred: Option<&'a str>,
+    resolve: &'a dyn Fn(&Expression) -> Result<Option<Value>>,
+}
+
+impl Interpolator<'_> {
+    fn value(&self, value: &Value, attribute: &str) -> Result<Value> {
+        match value {
+            Value::String(text) => self.string(text, attribute),
+            Value::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| self.value(item, &child(attribute, &index.to_string())))
+                .collect::<Result<_>>()
+                .map(Value::Array),
+            Value::Object(map) => map
+                .iter()
+                .map(|(key, nested)| Ok((key.clone(), self.value(nested, &child(attribute, key))?)))
+                .collect::<Result<Map<_, _>>>()
+                .map(Value::Object),
+            other => Ok(other.clone()),
+        }
+    }
+
+    fn string(&self, text: &str, attribute: &str) -> Result<Value> {
+        let whole = text
+            .strip_prefix("${")
+            .and_then(|rest| rest.strip_suffix('}'))
+            .filter(|inner| !inner.contains('}'));
+        if let Some(inner) = whole {
+            let expression = Expression {
+                text: inner.trim(),
+                attribute,
+                embedded: false,
+            };
+            if let Some(value) = (self.resolve)(&expression)? {
+                return Ok(value);
+            }
+            return Ok(Value::String(text.to_string()));
+        }
+
+        let invalid = |reason: String| Error::Interpolation {
+            address: Box::new(self.address.clone()),
+            attribute: attribute.to_string(),
+            reason,
+        };
+        let mut out = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("${") {
+            if rest[..start].ends_with('$') {
+                let kept = self
+                    .deferred
+                    .is_some_and(|prefix| rest[start + 2..].trim_start().starts_with(prefix));
+                out.push_str(&rest[..start - usize::from(!kept)]);
+                out.push_str("${");
+                rest = &rest[start + 2..];
+                continue;
+            }
+            out.push_str(&rest[..start]);
+            let Some(length) = rest[start + 2..].find('}') else {
+                return Err(invalid(format!("unclosed '${{' in \"{text}\"")));
+            };
+            let inner = &rest[start + 2..start + 2 + length];
+            let expression = Expression {
+                text: inner.trim(),
+                attribute,
+                embedded: true,
+            };
+            match (self.resolve)(&expression)? {
+                Some(Value::String(value)) => out.push_str(&value),
+                Some(value) if insertable(&value) => out.push_str(&value.to_string()),
+                Some(value) => {
+                    return Err(invalid(format!(
+                        "'${{{}}}' is {} and cannot be inserted into a string",
+                        expression.text,
+                        describe(&value)
+                    )))
+                }
+                None => {
+                    out.push_str("${");
+                    out.push_str(inner);
+                    out.push('}');
+                }
+            }
+            rest = &rest[start + 2 + length + 1..];
+        }
+        out.push_str(rest);
+        Ok(Value::String(out))
+    }
+}
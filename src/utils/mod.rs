@@ -1,8 +1,12 @@
 pub mod error;
+pub(crate) mod interpolate;
 pub mod logging;
 pub mod provider_source;
+pub mod redact;
+pub(crate) mod schema;
+pub mod sorted;
 pub mod spec_decode;
 
 pub use error::{Error, Result};
 pub use logging::init_logging;
-pub use spec_decode::decode_spec;
+pub use spec_decode::{decode_spec, encode_spec};
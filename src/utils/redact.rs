@@ -0,0 +1,51 @@
+use crate::types::patch::tokens;
+use crate::types::Resource;
+use crate::utils::schema::flagged_attributes;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde_json::Value;
+
+/// Schema extension marking a property whose value must not be displayed or logged.
+pub const SENSITIVE_KEY: &str = "x-iamctl-sensitive";
+
+/// Placeholder replacing sensitive values.
+pub const REDACTED: &str = "(sensitive)";
+
+/// `schema_with` helper marking a field as sensitive:
+/// `#[schemars(schema_with = "iamctl_rust_sdk::utils::redact::sensitive::<String>")]`.
+pub fn sensitive<T: JsonSchema>(gen: &mut SchemaGenerator) -> Schema {
+    let mut schema = gen.subschema_for::<T>().into_object();
+    let _ = schema
+        .extensions
+        .insert(SENSITIVE_KEY.to_string(), Value::Bool(true));
+    Schema::Object(schema)
+}
+
+/// Returns the JSON Pointers of properties marked with [`SENSITIVE_KEY`], following
+/// `$ref`s into nested objects.
+pub fn sensitive_attributes(schema: &Value) -> Vec<String> {
+    flagged_attributes(schema, SENSITIVE_KEY)
+}
+
+/// Returns a copy of the resource with the given attributes (JSON Pointers into the
+/// spec) replaced by [`REDACTED`]. Attributes that are absent or null are left as is.
+pub fn redact(resource: &Resource, attributes: &[String]) -> Resource {
+    let mut redacted = resource.clone();
+    for attribute in attributes {
+        let Ok(segments) = tokens(attribute) else {
+            continue;
+        };
+        let Some((first, rest)) = segments.split_first() else {
+            continue;
+        };
+        let mut value = redacted.spec.get_mut(first);
+        for segment in rest {
+            value = value.and_then(|v| v.get_mut(segment));
+        }
+        if let Some(value) = value.filter(|v| !v.is_null()) {
+            *value = Value::String(REDACTED.to_string());
+        }
+    }
+    redacted
+}
@@ -0,0 +1,84 @@
+use crate::types::patch::child;
+use serde_json::Value;
+
+/// Follows `$ref` (directly or through a single-element `allOf`, as schemars emits for
+/// nested structs) into the root schema's definitions.
+pub(crate) fn resolve_ref<'a>(schema: &'a Value, root: &'a Value) -> &'a Value {
+    let reference = schema.get("$ref").or_else(|| {
+        schema
+            .get("allOf")
+            .and_then(Value::as_array)
+            .filter(|all_of| all_of.len() == 1)
+            .and_then(|all_of| all_of[0].get("$ref"))
+    });
+
+    reference
+        .and_then(Value::as_str)
+        .and_then(|r| r.strip_prefix("#/"))
+        .and_then(|pointer| root.pointer(&format!("/{pointer}")))
+        .unwrap_or(schema)
+}
+
+/// Nesting limit guarding against recursive schemas.
+const MAX_SCHEMA_DEPTH: usize = 16;
+
+/// Returns the JSON Pointers of properties whose `key` is `true`, following `$ref`s into
+/// nested objects. Properties below a flagged one are not reported.
+pub(crate) fn flagged_attributes(schema: &Value, key: &str) -> Vec<String> {
+    let mut attributes = vec![];
+    visit_properties(schema, schema, "", 0, &mut |path, property| {
+        let flagged = property.get(key).and_then(Value::as_bool) == Some(true);
+        if flagged {
+            attributes.push(path.to_string());
+        }
+        flagged
+    });
+    attributes
+}
+
+/// Returns the JSON Pointers and values of properties declaring a non-null `default`.
+/// Properties below one with a default are not reported.
+pub(crate) fn default_values(schema: &Value) -> Vec<(String, Value)> {
+    let mut defaults = vec![];
+    visit_properties(
+        schema,
+        schema,
+        "",
+        0,
+        &mut |path, property| match property.get("default") {
+            Some(Value::Null) | None => false,
+            Some(default) => {
+                defaults.push((path.to_string(), default.clone()));
+                true
+            }
+        },
+    );
+    defaults
+}
+
+/// Calls `visit` with the JSON Pointer of every property, descending into nested
+/// objects unless `visit` returns `true`.
+fn visit_properties(
+    schema: &Value,
+    root: &Value,
+    prefix: &str,
+    depth: usize,
+    visit: &mut dyn FnMut(&str, &Value) -> bool,
+) {
+    if depth > MAX_SCHEMA_DEPTH {
+        return;
+    }
+    let Some(properties) = resolve_ref(schema, root)
+        .get("properties")
+        .and_then(Value::as_object)
+    else {
+        return;
+    };
+
+    for (name, property) in properties {
+        let path = child(prefix, name);
+        if !visit(&path, property) {
+            visit_properties(property, root, &path, depth + 1, visit);
+        }
+    }
+}
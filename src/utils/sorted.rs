@@ -0,0 +1,14 @@
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+
+/// Serializes a `HashMap` with its keys in sorted order, so identical content always
+/// serializes to identical bytes:
+/// `#[serde(serialize_with = "iamctl_rust_sdk::utils::sorted::serialize")]`.
+pub fn serialize<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Ord + Serialize,
+    V: Serialize,
+    S: Serializer,
+{
+    serializer.collect_map(map.iter().collect::<BTreeMap<_, _>>())
+}
@@ -1,6 +1,8 @@
 use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
 
-use crate::types::Resource;
+use crate::types::{Resource, ResourceAddress};
 use crate::utils::{Error, Result};
 
 pub fn decode_spec<T: DeserializeOwned>(resource: &Resource) -> Result<T> {
@@ -15,3 +17,18 @@ pub fn decode_spec<T: DeserializeOwned>(resource: &Resource) -> Result<T> {
         ))
     })
 }
+
+/// Inverse of [`decode_spec`]: converts a typed spec back into a resource spec map.
+/// The spec must serialize to a JSON object.
+pub fn encode_spec<T: Serialize>(
+    address: &ResourceAddress,
+    spec: &T,
+) -> Result<HashMap<String, serde_json::Value>> {
+    match serde_json::to_value(spec).map_err(Error::Serialization)? {
+        serde_json::Value::Object(map) => Ok(map.into_iter().collect()),
+        other => Err(Error::Config(format!(
+            "Spec for {} (type={}) must serialize to an object, got: {}",
+            address, address.resource_type, other
+        ))),
+    }
+}
@@ -1,4 +1,6 @@
-use crate::types::Resource;
+use crate::provider::IamResource;
+use crate::types::unknown::is_unknown_at;
+use crate::types::{Diagnostic, Resource, ResourceAddress};
 use crate::utils::Result;
 use jsonschema::JSONSchema;
 use schemars::JsonSchema;
@@ -53,6 +55,29 @@ impl ValidationResult {
         self.warnings = warnings;
         self
     }
+
+    /// Converts errors and warnings into diagnostics for the resource at `address`.
+    pub fn to_diagnostics(&self, address: &ResourceAddress) -> Vec<Diagnostic> {
+        let errors = self
+            .errors
+            .iter()
+            .map(|e| (Diagnostic::error(&e.message), e));
+        let warnings = self
+            .warnings
+            .iter()
+            .map(|w| (Diagnostic::warning(&w.message), w));
+        errors
+            .chain(warnings)
+            .map(|(diagnostic, e)| {
+                let diagnostic = diagnostic.with_address(address.clone()).with_code(&e.code);
+                if e.path.is_empty() {
+                    diagnostic
+                } else {
+                    diagnostic.with_attribute(&e.path)
+                }
+            })
+            .collect()
+    }
 }
 
 /// Schema validator for resource specifications
@@ -93,6 +118,69 @@ impl JsonSchemaValidator {
         }
     }
 
+    /// Adds the annotated schema of an [`IamResource`] under its resource type.
+    pub fn add_resource_schema<T: IamResource>(&mut self) {
+        self.add_schema(T::RESOURCE_TYPE, T::resource_schema());
+    }
+
+    /// Validates an arbitrary JSON value (e.g. provider configuration) against the schema
+    /// registered under `schema_key`. Error paths are prefixed with `path_prefix`.
+    pub fn validate_value(
+        &self,
+        schema_key: &str,
+        value: &Value,
+        path_prefix: &str,
+    ) -> Result<ValidationResult> {
+        let schema_value = match self.schemas.get(schema_key) {
+            Some(schema) => schema,
+            None => {
+                return Ok(ValidationResult::invalid(vec![ValidationError::new(
+                    "",
+                    &format!("No schema found for: {schema_key}"),
+                    "SCHEMA_NOT_FOUND",
+                )]));
+            }
+        };
+
+        match Self::schema_errors(schema_value, value, path_prefix) {
+            Ok(errors) if errors.is_empty() => Ok(ValidationResult::valid()),
+            Ok(errors) => Ok(ValidationResult::invalid(errors)),
+            Err(e) => Ok(ValidationResult::invalid(vec![e])),
+        }
+    }
+
+    /// Compiles the schema and collects validation errors for `value`.
+    /// Returns `Err` if the schema itself is invalid.
+    fn schema_errors(
+        schema_value: &Value,
+        value: &Value,
+        path_prefix: &str,
+    ) -> std::result::Result<Vec<ValidationError>, ValidationError> {
+        let compiled = JSONSchema::compile(schema_value).map_err(|e| {
+            ValidationError::new(
+                "",
+                &format!("Invalid JSON Schema: {e}"),
+                "INVALID_SCHEMA_DEFINITION",
+            )
+        })?;
+
+        let mut errors = vec![];
+        if let Err(schema_errors) = compiled.validate(value) {
+            for error in schema_errors {
+                // Unknown values are only resolved at apply, so they pass at plan time.
+                if is_unknown_at(value, &error.instance_path.to_string()) {
+                    continue;
+                }
+                errors.push(ValidationError::new(
+                    &format!("{path_prefix}{}", error.instance_path),
+                    &error.to_string(),
+                    "SCHEMA_VALIDATION_ERROR",
+                ));
+            }
+        }
+        Ok(errors)
+    }
+
     fn validate_against_schema(&self, resource: &Resource) -> Result<ValidationResult> {
         let schema_value = match self.schemas.get(&resource.address.resource_type) {
             Some(schema) => schema,
@@ -120,17 +208,6 @@ impl JsonSchemaValidator {
         }
 
         // 2. Full JSON Schema validation
-        let compiled = match JSONSchema::compile(schema_value) {
-            Ok(s) => s,
-            Err(e) => {
-                return Ok(ValidationResult::invalid(vec![ValidationError::new(
-                    "",
-                    &format!("Invalid JSON Schema: {e}"),
-                    "INVALID_SCHEMA_DEFINITION",
-                )]));
-            }
-        };
-
         let spec_value = match serde_json::to_value(&resource.spec) {
             Ok(v) => v,
             Err(e) => {
@@ -142,14 +219,9 @@ impl JsonSchemaValidator {
                 return Err(e.into());
             }
         };
-        if let Err(schema_errors) = compiled.validate(&spec_value) {
-            for error in schema_errors {
-                errors.push(ValidationError::new(
-                    &format!("spec{}", error.instance_path),
-                    &error.to_string(),
-                    "SCHEMA_VALIDATION_ERROR",
-                ));
-            }
+        match Self::schema_errors(schema_value, &spec_value, "spec") {
+            Ok(schema_errors) => errors.extend(schema_errors),
+            Err(e) => return Ok(ValidationResult::invalid(vec![e])),
         }
 
         if errors.is_empty() {
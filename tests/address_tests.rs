@@ -0,0 +1,135 @@
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::utils::Error;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+fn address(resource_type: &str, namespace: Option<&str>, name: &str) -> ResourceAddress {
+    ResourceAddress {
+        resource_type: resource_type.to_string(),
+        name: name.to_string(),
+        namespace: namespace.map(str::to_string),
+    }
+}
+
+fn reason(input: &str) -> String {
+    match input.parse::<ResourceAddress>() {
+        Err(Error::InvalidAddress { reason, .. }) => reason,
+        other => panic!("expected an invalid address error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_parse_plain_addresses() {
+    assert_eq!(
+        "realm.demo".parse::<ResourceAddress>().unwrap(),
+        address("realm", None, "demo")
+    );
+    assert_eq!(
+        ResourceAddress::try_from("client.demo/my-app").unwrap(),
+        address("client", Some("demo"), "my-app")
+    );
+    // Only the first `.` separates the type.
+    assert_eq!(
+        "user.prod/john.doe".parse::<ResourceAddress>().unwrap(),
+        address("user", Some("prod"), "john.doe")
+    );
+}
+
+#[test]
+fn test_display_escapes_ambiguous_characters() {
+    let cases = [
+        (address("user", None, "a/b"), r"user.a\/b"),
+        (
+            address("aws.user", Some("eu/west"), "alice"),
+            r"aws\.user.eu\/west/alice",
+        ),
+        (address("user", None, r"dom\alice"), r"user.dom\\alice"),
+        (
+            address("user", Some("prod"), "john.doe"),
+            "user.prod/john.doe",
+        ),
+    ];
+    for (address, expected) in cases {
+        assert_eq!(address.to_string(), expected);
+        assert_eq!(expected.parse::<ResourceAddress>().unwrap(), address);
+    }
+}
+
+#[test]
+fn test_parse_errors() {
+    assert!(reason("user").contains("expected `type.name`"));
+    assert!(reason("user.a/b/c").contains("more than one `/`"));
+    assert!(reason(r"user.a\b").contains("can be escaped"));
+    assert!(reason(r"user.alice\").contains("trailing backslash"));
+    assert_eq!(reason(".alice"), "the resource type is empty");
+    assert_eq!(reason("user./alice"), "the namespace is empty");
+    assert_eq!(reason("user.prod/"), "the name is empty");
+    assert_eq!(
+        reason("user.al\nice"),
+        "the name contains a control character"
+    );
+
+    let err = "user".parse::<ResourceAddress>().unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("Invalid resource address 'user'"));
+}
+
+#[test]
+fn test_validate_struct_addresses() {
+    assert!(address("user", Some("prod"), "alice").validate().is_ok());
+    assert!(matches!(
+        address("user", None, "").validate(),
+        Err(Error::InvalidAddress { .. })
+    ));
+}
+
+#[test]
+fn test_deserialize_object_or_string() {
+    let from_object: ResourceAddress = serde_json::from_value(json!({
+        "resource_type": "user",
+        "name": "alice",
+        "namespace": "prod"
+    }))
+    .unwrap();
+    let from_string: ResourceAddress = serde_json::from_value(json!("user.prod/alice")).unwrap();
+    assert_eq!(from_object, from_string);
+
+    // The wire format is unchanged.
+    assert_eq!(
+        serde_json::to_value(&from_object).unwrap(),
+        json!({ "resource_type": "user", "name": "alice", "namespace": "prod" })
+    );
+
+    let err = serde_json::from_value::<ResourceAddress>(json!("user")).unwrap_err();
+    assert!(err.to_string().contains("Invalid resource address"));
+
+    // Objects are validated like strings.
+    for invalid in [
+        json!({ "resource_type": "user", "name": "" }),
+        json!({ "resource_type": "user", "name": "alice", "namespace": "" }),
+        json!({ "resource_type": "user", "name": "al\nice" }),
+    ] {
+        let err = serde_json::from_value::<ResourceAddress>(invalid).unwrap_err();
+        assert!(
+            err.to_string().contains("Invalid resource address"),
+            "{err}"
+        );
+    }
+}
+
+#[test]
+fn test_compact_serde() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Entry {
+        #[serde(with = "iamctl_rust_sdk::types::address::compact")]
+        address: ResourceAddress,
+    }
+
+    let entry = Entry {
+        address: address("user", None, "a/b"),
+    };
+    let value = serde_json::to_value(&entry).unwrap();
+    assert_eq!(value, json!({ "address": r"user.a\/b" }));
+    assert_eq!(serde_json::from_value::<Entry>(value).unwrap(), entry);
+}
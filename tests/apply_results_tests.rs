@@ -0,0 +1,239 @@
+mod common;
+
+use async_trait::async_trait;
+use common::resource;
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::provider::{ApplyResult, ApplyStatus, HandlerProvider};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+struct GroupSpec {
+    name: String,
+    #[serde(default)]
+    id: Option<String>,
+}
+
+struct GroupHandler;
+
+#[async_trait]
+impl ResourceHandler for GroupHandler {
+    type Spec = GroupSpec;
+
+    async fn create(
+        &self,
+        address: &ResourceAddress,
+        spec: GroupSpec,
+    ) -> anyhow::Result<GroupSpec> {
+        if spec.name.is_empty() {
+            anyhow::bail!("group name must not be empty");
+        }
+        Ok(GroupSpec {
+            id: Some(format!("gid-{}", address.name)),
+            ..spec
+        })
+    }
+
+    async fn read(
+        &self,
+        _address: &ResourceAddress,
+        spec: GroupSpec,
+    ) -> anyhow::Result<Option<GroupSpec>> {
+        Ok(Some(spec))
+    }
+
+    async fn update(
+        &self,
+        _address: &ResourceAddress,
+        before: GroupSpec,
+        after: GroupSpec,
+    ) -> anyhow::Result<GroupSpec> {
+        Ok(GroupSpec {
+            id: before.id,
+            ..after
+        })
+    }
+
+    async fn delete(&self, _address: &ResourceAddress, _spec: GroupSpec) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn remote_id(&self, spec: &GroupSpec) -> Option<String> {
+        spec.id.clone()
+    }
+}
+
+fn change(change_type: ChangeType, before: Option<Resource>, after: Option<Resource>) -> Change {
+    Change {
+        address: before.as_ref().or(after.as_ref()).unwrap().address.clone(),
+        change_type,
+        before,
+        after,
+        computed_fields: vec![],
+        replace_order: None,
+        attribute_changes: vec![],
+        patch: None,
+    }
+}
+
+fn provider() -> HandlerProvider {
+    ProviderBuilder::new(ProviderMetadata {
+        name: "groups".to_string(),
+        version: "0.1.0".to_string(),
+        author: None,
+        repository: None,
+        description: None,
+    })
+    .resource("group", GroupHandler)
+    .build()
+}
+
+#[tokio::test]
+async fn test_apply_results_carry_state_and_remote_ids() {
+    let existing = resource("group.ops", json!({ "name": "ops", "id": "gid-ops" }));
+    let changes = vec![
+        change(
+            ChangeType::Create,
+            None,
+            Some(resource("group.devs", json!({ "name": "devs" }))),
+        ),
+        change(
+            ChangeType::Update,
+            Some(existing.clone()),
+            Some(resource("group.ops", json!({ "name": "operations" }))),
+        ),
+        change(
+            ChangeType::Delete,
+            Some(resource(
+                "group.old",
+                json!({ "name": "old", "id": "gid-old" }),
+            )),
+            None,
+        ),
+        change(ChangeType::NoOp, Some(existing.clone()), Some(existing)),
+        change(
+            ChangeType::Create,
+            None,
+            Some(resource("group.empty", json!({ "name": "" }))),
+        ),
+    ];
+
+    let response = provider().apply(ApplyRequest { changes }).await.unwrap();
+    let statuses: Vec<_> = response.results.iter().map(|r| r.status).collect();
+    assert_eq!(
+        statuses,
+        vec![
+            ApplyStatus::Succeeded,
+            ApplyStatus::Succeeded,
+            ApplyStatus::Succeeded,
+            ApplyStatus::Skipped,
+            ApplyStatus::Failed,
+        ]
+    );
+
+    let created = &response.results[0];
+    assert_eq!(created.remote_id.as_deref(), Some("gid-devs"));
+    assert_eq!(
+        created.resource.as_ref().unwrap().spec["id"],
+        json!("gid-devs")
+    );
+
+    let updated = &response.results[1];
+    assert_eq!(updated.remote_id.as_deref(), Some("gid-ops"));
+    assert_eq!(
+        updated.resource.as_ref().unwrap().spec["name"],
+        json!("operations")
+    );
+
+    let deleted = &response.results[2];
+    assert!(deleted.resource.is_none());
+    assert_eq!(deleted.remote_id.as_deref(), Some("gid-old"));
+
+    let failed = &response.results[4];
+    assert!(failed
+        .error
+        .as_ref()
+        .unwrap()
+        .message
+        .contains("must not be empty"));
+
+    // The legacy address lists are derived from the results.
+    assert_eq!(response.successful_addresses.len(), 4);
+    assert_eq!(response.failed_addresses().len(), 1);
+    assert_eq!(response.failed_addresses()[0].name, "empty");
+}
+
+#[tokio::test]
+async fn test_update_state_from_results() {
+    let mut state = State::default();
+    for name in ["old", "ops"] {
+        let resource = resource(&format!("group.{name}"), json!({ "name": name }));
+        let _ = state
+            .resources
+            .insert(resource.address.to_string(), resource);
+    }
+
+    let changes = vec![
+        change(
+            ChangeType::Create,
+            None,
+            Some(resource("group.devs", json!({ "name": "devs" }))),
+        ),
+        change(
+            ChangeType::Delete,
+            Some(resource("group.old", json!({ "name": "old" }))),
+            None,
+        ),
+        change(
+            ChangeType::Update,
+            Some(resource("group.ops", json!({ "name": "ops" }))),
+            Some(resource("group.ops", json!({ "name": "" }))),
+        ),
+    ];
+    let response = provider().apply(ApplyRequest { changes }).await.unwrap();
+    assert!(response.results[1].deleted);
+    assert!(response.update_state(&mut state).is_empty());
+
+    let mut keys: Vec<_> = state.resources.keys().cloned().collect();
+    keys.sort();
+    assert_eq!(
+        keys,
+        vec!["group.devs".to_string(), "group.ops".to_string()]
+    );
+    assert_eq!(state.resources["group.devs"].spec["id"], json!("gid-devs"));
+
+    // A success that returns no resource and reports no delete keeps the entry.
+    let devs = state.resources["group.devs"].address.clone();
+    let response = ApplyResponse::from_results(vec![ApplyResult::succeeded(devs.clone(), None)]);
+    let warnings = response.update_state(&mut state);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].address, Some(devs));
+    assert!(state.resources.contains_key("group.devs"));
+
+    let response = ApplyResponse::from_results(vec![ApplyResult::deleted(
+        state.resources["group.ops"].address.clone(),
+    )]);
+    assert!(response.update_state(&mut state).is_empty());
+    assert!(!state.resources.contains_key("group.ops"));
+}
+
+#[test]
+fn test_apply_response_without_results_deserializes() {
+    let response: ApplyResponse = serde_json::from_value(json!({
+        "successful_addresses": [
+            { "resource_type": "group", "name": "devs", "namespace": null }
+        ]
+    }))
+    .unwrap();
+    assert!(response.results.is_empty());
+
+    let result = ApplyResult::skipped(resource("group.devs", json!({})).address, None)
+        .with_warning("nothing to do");
+    let encoded = serde_json::to_value(&result).unwrap();
+    assert_eq!(encoded["status"], "skipped");
+    assert_eq!(
+        serde_json::from_value::<ApplyResult>(encoded).unwrap(),
+        result
+    );
+}
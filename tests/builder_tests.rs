@@ -0,0 +1,162 @@
+mod common;
+
+use common::{resource, user, UserHandler};
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::provider::{RefreshRequest, RefreshedResource};
+use iamctl_rust_sdk::server::{JsonRpcRequest, RequestHandler};
+use serde_json::json;
+use std::sync::Arc;
+
+fn metadata() -> ProviderMetadata {
+    ProviderMetadata {
+        name: "handler-provider".to_string(),
+        version: "0.1.0".to_string(),
+        author: None,
+        repository: None,
+        description: None,
+    }
+}
+
+#[tokio::test]
+async fn test_builder_capabilities_and_schemas() {
+    let provider = ProviderBuilder::new(metadata())
+        .resource("user", UserHandler::default())
+        .build();
+
+    let capabilities = provider.capabilities();
+    assert_eq!(capabilities.supported_resources, vec!["user".to_string()]);
+    assert!(capabilities.can_refresh);
+
+    let schemas = provider.schemas();
+    assert_eq!(schemas["user"]["properties"]["username"]["type"], "string");
+}
+
+#[tokio::test]
+async fn test_builder_plan_dispatch() {
+    let provider = ProviderBuilder::new(metadata())
+        .resource("user", UserHandler::default())
+        .build();
+
+    let plan = provider
+        .plan(PlanRequest {
+            workspace_path: ".".to_string(),
+            desired_state: vec![
+                user("alice", json!({ "username": "alice" })),
+                user("bob", json!({ "username": "robert" })),
+            ],
+            current_state: vec![
+                user("bob", json!({ "username": "bob" })),
+                user("carol", json!({ "username": "carol" })),
+            ],
+            data_sources: vec![],
+        })
+        .await
+        .unwrap();
+
+    let kinds: Vec<_> = plan
+        .changes
+        .iter()
+        .map(|c| (c.address.name.as_str(), c.change_type.clone()))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ("alice", ChangeType::Create),
+            ("bob", ChangeType::Update),
+            ("carol", ChangeType::Delete),
+        ]
+    );
+
+    let unsupported = provider
+        .plan(PlanRequest {
+            workspace_path: ".".to_string(),
+            desired_state: vec![resource("group.devs", json!({}))],
+            current_state: vec![],
+            data_sources: vec![],
+        })
+        .await;
+    assert!(unsupported.is_err());
+}
+
+#[tokio::test]
+async fn test_builder_apply_and_refresh() {
+    let provider = ProviderBuilder::new(metadata())
+        .resource("user", UserHandler::default())
+        .build();
+
+    let plan = provider
+        .plan(PlanRequest {
+            workspace_path: ".".to_string(),
+            desired_state: vec![user("alice", json!({ "username": "alice" }))],
+            current_state: vec![user("ghost", json!({ "username": "ghost" }))],
+            data_sources: vec![],
+        })
+        .await
+        .unwrap();
+
+    // Updating a user that was never created fails in the handler.
+    let mut changes = plan.changes;
+    changes.push(Change {
+        address: user("ghost", json!({ "username": "ghost" })).address,
+        change_type: ChangeType::Update,
+        before: Some(user("ghost", json!({ "username": "ghost" }))),
+        after: Some(user("ghost", json!({ "username": "casper" }))),
+        computed_fields: vec![],
+        replace_order: None,
+        attribute_changes: vec![],
+        patch: None,
+    });
+
+    let applied = provider.apply(ApplyRequest { changes }).await.unwrap();
+    assert_eq!(applied.successful_addresses.len(), 2);
+    assert_eq!(applied.failed_addresses().len(), 1);
+    assert!(applied.diagnostics[0].summary.contains("does not exist"));
+
+    let refreshed = provider
+        .refresh(RefreshRequest {
+            resources: vec![
+                user("alice", json!({ "username": "alice" })),
+                user("ghost", json!({ "username": "ghost" })),
+            ],
+        })
+        .await
+        .unwrap();
+    match &refreshed.resources[0] {
+        RefreshedResource::Present { resource } => {
+            assert_eq!(resource.spec["id"], json!("id-alice"));
+        }
+        other => panic!("expected alice to be present, got {other:?}"),
+    }
+    assert!(matches!(
+        refreshed.resources[1],
+        RefreshedResource::Gone { .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_handler_registers_builder_schemas() {
+    let provider = ProviderBuilder::new(metadata())
+        .resource("user", UserHandler::default())
+        .build();
+    let handler = RequestHandler::new(Arc::new(provider));
+
+    let response = handler
+        .handle(JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "plan".to_string(),
+            params: json!({
+                "workspace_path": ".",
+                "desired_state": [{
+                    "address": { "resource_type": "user", "name": "alice", "namespace": null },
+                    "spec": { "username": 42 }
+                }],
+                "current_state": []
+            }),
+            id: Some(json!(1)),
+        })
+        .await;
+
+    let error = response.error.unwrap();
+    assert_eq!(error.code, -32602);
+    assert!(error.message.contains("Schema validation failed"));
+}
@@ -0,0 +1,84 @@
+//! Fixtures shared by the integration tests; each test crate uses a subset of them.
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+use iamctl_rust_sdk::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// A resource at the given address (e.g. `user.demo/alice`) with the given spec.
+pub fn resource(address: &str, spec: serde_json::Value) -> Resource {
+    Resource {
+        address: address.parse().unwrap(),
+        spec: serde_json::from_value(spec).unwrap(),
+        metadata: HashMap::new(),
+    }
+}
+
+/// The user `name`, outside any namespace.
+pub fn user(name: &str, spec: serde_json::Value) -> Resource {
+    resource(&format!("user.{name}"), spec)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct UserSpec {
+    pub username: String,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+/// Keeps users in memory by name, assigning `id-<name>` ids on creation.
+#[derive(Default)]
+pub struct UserHandler {
+    pub users: Mutex<HashMap<String, UserSpec>>,
+}
+
+#[async_trait]
+impl ResourceHandler for UserHandler {
+    type Spec = UserSpec;
+
+    async fn create(&self, address: &ResourceAddress, spec: UserSpec) -> anyhow::Result<UserSpec> {
+        let created = UserSpec {
+            id: Some(format!("id-{}", address.name)),
+            ..spec
+        };
+        let _ = self
+            .users
+            .lock()
+            .unwrap()
+            .insert(address.name.clone(), created.clone());
+        Ok(created)
+    }
+
+    async fn read(
+        &self,
+        address: &ResourceAddress,
+        _spec: UserSpec,
+    ) -> anyhow::Result<Option<UserSpec>> {
+        Ok(self.users.lock().unwrap().get(&address.name).cloned())
+    }
+
+    async fn update(
+        &self,
+        address: &ResourceAddress,
+        _before: UserSpec,
+        after: UserSpec,
+    ) -> anyhow::Result<UserSpec> {
+        let mut users = self.users.lock().unwrap();
+        let existing = users
+            .get_mut(&address.name)
+            .ok_or_else(|| anyhow::anyhow!("user {} does not exist", address.name))?;
+        existing.username = after.username;
+        existing.email = after.email;
+        Ok(existing.clone())
+    }
+
+    async fn delete(&self, address: &ResourceAddress, _spec: UserSpec) -> anyhow::Result<()> {
+        let _ = self.users.lock().unwrap().remove(&address.name);
+        Ok(())
+    }
+}
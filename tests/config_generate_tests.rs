@@ -0,0 +1,154 @@
+mod common;
+
+use common::{resource, user};
+use iamctl_rust_sdk::config::{
+    ConfigFormat, ConfigGenerator, GroupBy, ResourceDocument, Workspace,
+};
+use iamctl_rust_sdk::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+#[derive(Default, Serialize, Deserialize, JsonSchema)]
+struct Attributes {
+    #[serde(default)]
+    locale: Option<String>,
+    #[serde(default)]
+    verified: bool,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct ImportedUserSpec {
+    username: String,
+    #[serde(default)]
+    is_admin: bool,
+    #[serde(default, skip_deserializing)]
+    id: Option<String>,
+    #[serde(default)]
+    attributes: Attributes,
+}
+
+fn generator(format: ConfigFormat, group_by: GroupBy) -> ConfigGenerator {
+    ConfigGenerator::new(format, group_by)
+        .schema(
+            "user",
+            serde_json::to_value(schemars::schema_for!(ImportedUserSpec)).unwrap(),
+        )
+        .computed_fields("user", &["/created_timestamp"])
+}
+
+#[test]
+fn test_canonicalize_strips_computed_and_defaults() {
+    let alice = user(
+        "alice",
+        json!({
+            "username": "alice",
+            "is_admin": false,
+            "id": "uuid-1",
+            "created_timestamp": 1700000000,
+            "attributes": { "locale": "en", "verified": false }
+        }),
+    );
+
+    let canonical = generator(ConfigFormat::Yaml, GroupBy::ResourceType).canonicalize(&alice);
+    assert_eq!(
+        serde_json::to_value(&canonical.spec).unwrap(),
+        json!({ "username": "alice", "attributes": { "locale": "en" } })
+    );
+
+    // A nested object equal to its default disappears entirely.
+    let plain = user(
+        "bob",
+        json!({ "username": "bob", "attributes": { "locale": null, "verified": false } }),
+    );
+    let canonical = generator(ConfigFormat::Yaml, GroupBy::ResourceType).canonicalize(&plain);
+    assert_eq!(
+        serde_json::to_value(&canonical.spec).unwrap(),
+        json!({ "username": "bob" })
+    );
+}
+
+#[test]
+fn test_generate_yaml_grouped_by_type() {
+    let resources = vec![
+        user("bob", json!({ "username": "bob", "is_admin": true })),
+        user("alice", json!({ "username": "alice", "id": "uuid-1" })),
+    ];
+
+    let files = generator(ConfigFormat::Yaml, GroupBy::ResourceType)
+        .generate(&resources)
+        .unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path, PathBuf::from("user.yaml"));
+    assert_eq!(
+        files[0].contents,
+        "resource_type: user\nname: alice\nspec:\n  username: alice\n---\n\
+         resource_type: user\nname: bob\nspec:\n  is_admin: true\n  username: bob\n"
+    );
+}
+
+#[test]
+fn test_generate_json_grouped_by_namespace_round_trips() {
+    let resources = vec![
+        resource("user.prod/alice", json!({ "username": "alice" })),
+        user("carol", json!({ "username": "carol" })),
+    ];
+
+    let files = generator(ConfigFormat::Json, GroupBy::Namespace)
+        .generate(&resources)
+        .unwrap();
+    let paths: Vec<_> = files.iter().map(|f| f.path.clone()).collect();
+    assert_eq!(
+        paths,
+        vec![PathBuf::from("default.json"), PathBuf::from("prod.json")]
+    );
+
+    let documents: Vec<ResourceDocument> = serde_json::from_str(&files[1].contents).unwrap();
+    let restored: Resource = documents[0].clone().into();
+    assert_eq!(restored, resources[0]);
+}
+
+#[tokio::test]
+async fn test_write_to_directory() {
+    let dir = tempdir().unwrap();
+    let target = dir.path().join("workspace");
+    let resources = vec![user("alice", json!({ "username": "alice" }))];
+
+    let written = generator(ConfigFormat::Yaml, GroupBy::ResourceType)
+        .write_to(&target, &resources)
+        .await
+        .unwrap();
+    assert_eq!(written, vec![target.join("user.yaml")]);
+
+    let content = std::fs::read_to_string(&written[0]).unwrap();
+    let document: ResourceDocument = serde_yaml::from_str(&content).unwrap();
+    assert_eq!(document.name, "alice");
+}
+
+#[tokio::test]
+async fn test_generated_config_resolves_to_the_imported_spec() {
+    let dir = tempdir().unwrap();
+    let client = resource(
+        "client.demo/account",
+        json!({
+            "name": "${client_account}",
+            "description": "Managed by ${realm} ($${escaped})",
+            "roles": ["${role_admin}", "viewer"],
+            "attributes": { "login_theme": "${theme}" },
+        }),
+    );
+
+    for format in [ConfigFormat::Yaml, ConfigFormat::Json] {
+        let target = dir.path().join(format.extension());
+        let _ = ConfigGenerator::new(format, GroupBy::ResourceType)
+            .write_to(&target, std::slice::from_ref(&client))
+            .await
+            .unwrap();
+
+        let workspace = Workspace::load(&target).await.unwrap();
+        let resolved = workspace.resolve(&workspace.variable_values()).unwrap();
+        assert_eq!(resolved, vec![client.clone()]);
+    }
+}
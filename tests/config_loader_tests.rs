@@ -0,0 +1,230 @@
+use iamctl_rust_sdk::config::{load_resources, SourceLocation, Workspace};
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::types::Diagnostic;
+use iamctl_rust_sdk::utils::Error;
+use serde_json::json;
+use std::path::Path;
+
+const USERS: &str = "\
+# Users of the demo realm
+resource_type: user
+name: alice
+namespace: demo
+spec:
+  email: alice@example.com
+  groups:
+    - devs
+    - ops
+  profile: { title: Engineer }
+---
+resource_type: user
+name: bob
+spec:
+  description: |
+    Bob: on call
+    every: week
+  roles:
+  - name: admin
+    scope: realm
+  - name: viewer
+...
+---
+# An empty document is skipped.
+";
+
+const CLIENTS: &str = r#"[
+  {
+    "resource_type": "client",
+    "name": "app",
+    "spec": {
+      "client_id": "app",
+      "redirect_uris": ["https://app", "https://app/cb"]
+    }
+  },
+  { "resource_type": "client", "name": "cli", "spec": { "public": true } }
+]"#;
+
+#[tokio::test]
+async fn test_load_workspace_from_yaml_and_json() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("clients")).unwrap();
+    std::fs::write(dir.path().join("users.yaml"), USERS).unwrap();
+    std::fs::write(dir.path().join("clients/all.json"), CLIENTS).unwrap();
+    std::fs::write(dir.path().join("README.md"), "not configuration").unwrap();
+    // Hidden and build directories, and var files, are not part of the workspace.
+    for skipped in [".github/workflows", "target/debug"] {
+        std::fs::create_dir_all(dir.path().join(skipped)).unwrap();
+        std::fs::write(dir.path().join(skipped).join("ci.yml"), "on: push\n").unwrap();
+    }
+    std::fs::write(dir.path().join(".hidden.yaml"), "not: a resource\n").unwrap();
+    std::fs::write(dir.path().join("prod.vars.yaml"), "realm: prod\n").unwrap();
+
+    let workspace = Workspace::load(dir.path()).await.unwrap();
+    let addresses: Vec<String> = workspace
+        .resources()
+        .iter()
+        .map(|loaded| loaded.resource.address.to_string())
+        .collect();
+    assert_eq!(
+        addresses,
+        ["client.app", "client.cli", "user.demo/alice", "user.bob"]
+    );
+
+    let bob = workspace.get(&"user.bob".parse().unwrap()).unwrap();
+    assert_eq!(
+        bob.resource.spec["description"],
+        json!("Bob: on call\nevery: week\n")
+    );
+    assert_eq!(bob.resource.spec["roles"][1], json!({ "name": "viewer" }));
+
+    let resources = load_resources(dir.path()).await.unwrap();
+    assert_eq!(resources, workspace.to_resources());
+}
+
+#[test]
+fn test_yaml_locations() {
+    let file = Path::new("users.yaml");
+    let workspace = Workspace::parse(file, USERS).unwrap();
+    let resources = workspace.resources();
+    assert_eq!(resources.len(), 2);
+    let (alice, bob) = (&resources[0], &resources[1]);
+    let at = |line, column| SourceLocation::new(file, line, column);
+
+    assert_eq!(alice.location, at(2, 1));
+    assert_eq!(alice.attribute_location("/email"), &at(6, 3));
+    assert_eq!(alice.attribute_location("/groups/1"), &at(9, 5));
+    // Members of inline maps resolve to the map.
+    assert_eq!(alice.attribute_location("/profile/title"), &at(10, 3));
+    assert_eq!(alice.attribute_location("/missing"), &at(5, 1));
+
+    assert_eq!(bob.location, at(12, 1));
+    // Block scalar lines are not keys.
+    assert_eq!(bob.attribute_location("/every"), &at(14, 1));
+    assert_eq!(bob.attribute_location("/roles/1/name"), &at(21, 5));
+    assert_eq!(bob.attribute_location("/roles/0/scope"), &at(20, 5));
+    assert_eq!(bob.attribute_location("/roles/1"), &at(21, 3));
+}
+
+#[test]
+fn test_yaml_locations_skip_flow_collections_block_scalars_and_aliases() {
+    let file = Path::new("clients.yaml");
+    let contents = "\
+resource_type: client
+name: app
+spec:
+  defaults: &defaults
+    theme: dark
+  settings: *defaults
+  mappers: {
+    email: { claim: mail },
+    name: [
+      given, family ] }
+  notes:
+    - |
+      owner: ops
+    - >-
+      second: note
+  banner: &banner >
+    title: Welcome
+  footer: !!str |
+    title: Bye
+  after: true
+";
+    let workspace = Workspace::parse(file, contents).unwrap();
+    let client = &workspace.resources()[0];
+    let at = |line, column| SourceLocation::new(file, line, column);
+
+    assert_eq!(client.resource.spec["settings"], json!({ "theme": "dark" }));
+    assert_eq!(client.attribute_location("/defaults/theme"), &at(5, 5));
+    // Aliased values resolve to the alias.
+    assert_eq!(client.attribute_location("/settings/theme"), &at(6, 3));
+    // Lines inside flow collections and block scalars are not keys.
+    assert_eq!(client.attribute_location("/mappers/email"), &at(7, 3));
+    assert_eq!(client.attribute_location("/mappers/name"), &at(7, 3));
+    assert_eq!(client.attribute_location("/notes/0/owner"), &at(12, 5));
+    assert_eq!(client.attribute_location("/notes/1"), &at(14, 5));
+    assert_eq!(client.attribute_location("/banner/title"), &at(16, 3));
+    assert_eq!(client.attribute_location("/footer/title"), &at(18, 3));
+    assert_eq!(client.attribute_location("/after"), &at(20, 3));
+}
+
+#[test]
+fn test_json_locations() {
+    let file = Path::new("clients.json");
+    let workspace = Workspace::parse(file, CLIENTS).unwrap();
+    let resources = workspace.resources();
+    let at = |line, column| SourceLocation::new(file, line, column);
+
+    assert_eq!(resources[0].location, at(2, 3));
+    assert_eq!(resources[0].attribute_location("/client_id"), &at(6, 7));
+    assert_eq!(
+        resources[0].attribute_location("/redirect_uris/1"),
+        &at(7, 40)
+    );
+    assert_eq!(resources[1].location, at(10, 3));
+    assert_eq!(resources[1].attribute_location("/public"), &at(10, 57));
+
+    let single = r#"{ "resource_type": "client", "name": "solo" }"#;
+    let workspace = Workspace::parse(file, single).unwrap();
+    let resources = workspace.resources();
+    assert_eq!(resources[0].resource.address.name, "solo");
+    assert_eq!(resources[0].location, at(1, 1));
+}
+
+#[test]
+fn test_diagnostics_point_at_config() {
+    let file = Path::new("users.yaml");
+    let workspace = Workspace::parse(file, USERS).unwrap();
+    let alice: ResourceAddress = "user.demo/alice".parse().unwrap();
+
+    let diagnostic = Diagnostic::error("invalid group")
+        .with_address(alice.clone())
+        .with_attribute("spec/groups/0");
+    assert_eq!(
+        workspace.locate(&diagnostic).unwrap().to_string(),
+        "users.yaml:8:5"
+    );
+    let whole = Diagnostic::error("unsupported").with_address(alice);
+    assert_eq!(workspace.locate(&whole).unwrap().line, 2);
+    assert!(workspace.locate(&Diagnostic::error("global")).is_none());
+}
+
+#[test]
+fn test_invalid_configuration_is_reported_with_location() {
+    let duplicate = "resource_type: user\nname: alice\n---\nresource_type: user\nname: alice\n";
+    match Workspace::parse("users.yml", duplicate) {
+        Err(Error::DuplicateResource {
+            address,
+            first,
+            duplicate,
+        }) => {
+            assert_eq!(address, "user.alice");
+            assert_eq!((first.line, duplicate.line), (1, 4));
+        }
+        other => panic!("expected a duplicate resource, got {other:?}"),
+    }
+
+    let malformed = "resource_type: user\nname: bob\n---\nresource_type: user\nspec: [unclosed\n";
+    match Workspace::parse("users.yaml", malformed) {
+        Err(Error::InvalidConfig { location, reason }) => {
+            assert_eq!(location.line, 6, "{reason}");
+            assert!(reason.contains("flow sequence"), "{reason}");
+        }
+        other => panic!("expected invalid configuration, got {other:?}"),
+    }
+
+    let missing_name = "---\nresource_type: user\nspec: {}\n";
+    match Workspace::parse("users.yaml", missing_name) {
+        Err(Error::InvalidConfig { location, reason }) => {
+            assert_eq!(location.line, 2);
+            assert!(reason.contains("missing field `name`"), "{reason}");
+        }
+        other => panic!("expected invalid configuration, got {other:?}"),
+    }
+
+    match Workspace::parse("clients.json", r#"[{ "resource_type": 1 }]"#) {
+        Err(Error::InvalidConfig { location, .. }) => assert_eq!(location.column, 2),
+        other => panic!("expected invalid configuration, got {other:?}"),
+    }
+    assert!(Workspace::parse("users.toml", "").is_err());
+}
@@ -0,0 +1,226 @@
+use iamctl_rust_sdk::config::{load_resources, VariableType, Variables, Workspace};
+use iamctl_rust_sdk::utils::Error;
+use serde_json::json;
+
+const WORKSPACE: &str = "\
+variable: realm
+type: string
+default: demo
+---
+variable: port
+type: number
+---
+variable: redirect_uris
+type: list
+default: []
+---
+variable: admin
+---
+resource_type: client
+name: app
+namespace: ${var.realm}
+spec:
+  realm: ${var.realm}
+  base_url: https://${var.realm}.example.com:${ var.port }/
+  redirect_uris: ${var.redirect_uris}
+  literal: $${var.realm}
+  admin_role: ${data.role.admin.id}
+  settings:
+    port: ${var.port}
+";
+
+fn workspace() -> Workspace {
+    Workspace::parse("workspace.yaml", WORKSPACE).unwrap()
+}
+
+#[test]
+fn test_variables_are_interpolated() {
+    let workspace = workspace();
+    let names: Vec<_> = workspace.variables().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, ["realm", "port", "redirect_uris", "admin"]);
+    assert_eq!(
+        workspace.variables().nth(3).unwrap().variable_type,
+        VariableType::Any
+    );
+    assert_eq!(workspace.variable_location("port").unwrap().line, 5);
+
+    let variables = workspace
+        .variable_values()
+        .value("port", json!(8443))
+        .unwrap()
+        .value("redirect_uris", json!(["https://app/cb"]))
+        .unwrap();
+    let resources = workspace.resolve(&variables).unwrap();
+    let spec = &resources[0].spec;
+    assert_eq!(spec["realm"], json!("demo"));
+    assert_eq!(spec["base_url"], json!("https://demo.example.com:8443/"));
+    assert_eq!(spec["redirect_uris"], json!(["https://app/cb"]));
+    assert_eq!(spec["literal"], json!("${var.realm}"));
+    // Data source references are resolved by the provider when planning.
+    assert_eq!(spec["admin_role"], json!("${data.role.admin.id}"));
+    // A whole-string reference keeps the variable's type.
+    assert_eq!(spec["settings"]["port"], json!(8443));
+    // Only specs are interpolated.
+    assert_eq!(
+        resources[0].address.namespace.as_deref(),
+        Some("${var.realm}")
+    );
+}
+
+#[tokio::test]
+async fn test_values_from_var_files_and_environment() {
+    let workspace = workspace();
+    let env = [
+        ("IAMCTL_VAR_realm", "staging"),
+        ("IAMCTL_VAR_port", "8080"),
+        ("IAMCTL_VAR_undeclared", "ignored"),
+        ("HOME", "/root"),
+    ]
+    .map(|(key, value)| (key.to_string(), value.to_string()));
+    let from_env = workspace.variable_values().env_vars(env.clone());
+    assert_eq!(from_env.get("realm"), Some(&json!("staging")));
+    assert_eq!(from_env.get("port"), Some(&json!(8080)));
+    assert_eq!(from_env.get("redirect_uris"), Some(&json!([])));
+
+    let dir = tempfile::tempdir().unwrap();
+    let var_file = dir.path().join("prod.yaml");
+    std::fs::write(
+        &var_file,
+        "realm: prod\nredirect_uris:\n  - https://prod/cb\n",
+    )
+    .unwrap();
+    let variables = workspace
+        .variable_values()
+        .env_vars(env)
+        .var_file(&var_file)
+        .await
+        .unwrap();
+    let resources = workspace.resolve(&variables).unwrap();
+    assert_eq!(
+        resources[0].spec["base_url"],
+        json!("https://prod.example.com:8080/")
+    );
+
+    let json_vars = workspace
+        .variable_values()
+        .parse_var_file("vars.json", r#"{ "port": 1, "admin": { "name": "root" } }"#)
+        .unwrap();
+    assert_eq!(json_vars.get("admin"), Some(&json!({ "name": "root" })));
+
+    let workspace_dir = dir.path().join("workspace");
+    std::fs::create_dir(&workspace_dir).unwrap();
+    std::fs::write(workspace_dir.join("workspace.yml"), WORKSPACE).unwrap();
+    std::env::set_var("IAMCTL_VAR_port", "9000");
+    let loaded = load_resources(&workspace_dir).await;
+    std::env::remove_var("IAMCTL_VAR_port");
+    assert_eq!(
+        loaded.unwrap()[0].spec["base_url"],
+        json!("https://demo.example.com:9000/")
+    );
+}
+
+#[test]
+fn test_variable_errors_carry_the_address() {
+    let workspace = workspace();
+
+    match workspace.resolve(&workspace.variable_values()) {
+        Err(Error::InvalidVariable {
+            address,
+            attribute,
+            name,
+            reason,
+        }) => {
+            assert_eq!(*address, "client.${var.realm}/app".parse().unwrap());
+            assert_eq!((attribute.as_str(), name.as_str()), ("/base_url", "port"));
+            assert!(reason.contains("IAMCTL_VAR_port"), "{reason}");
+        }
+        other => panic!("expected a missing value, got {other:?}"),
+    }
+
+    let mistyped = workspace
+        .variable_values()
+        .env_vars([("IAMCTL_VAR_port".to_string(), "eighty".to_string())]);
+    let error = workspace.resolve(&mistyped).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Variable 'var.port' in client.${var.realm}/app at '/base_url' expects a number, got a string"
+    );
+
+    let list_in_string = "variable: hosts\ntype: list\ndefault: [a]\n---\nresource_type: user\nname: bob\nspec:\n  motd: hello ${var.hosts}\n";
+    let list_workspace = Workspace::parse("list.yaml", list_in_string).unwrap();
+    match list_workspace.resolve(&list_workspace.variable_values()) {
+        Err(Error::InvalidVariable { reason, .. }) => {
+            assert_eq!(reason, "is a list and cannot be inserted into a string")
+        }
+        other => panic!("expected an invalid variable, got {other:?}"),
+    }
+
+    let undefined = "resource_type: user\nname: bob\nspec:\n  groups: [\"${var.team}\"]\n";
+    let undefined = Workspace::parse("user.yaml", undefined).unwrap();
+    match undefined.resolve(&Variables::default()) {
+        Err(Error::UndefinedVariable {
+            address,
+            attribute,
+            name,
+        }) => {
+            assert_eq!(*address, "user.bob".parse().unwrap());
+            assert_eq!((attribute.as_str(), name.as_str()), ("/groups/0", "team"));
+        }
+        other => panic!("expected an undefined variable, got {other:?}"),
+    }
+
+    let unsupported =
+        "resource_type: user\nname: bob\nspec:\n  motd: \"${env.HOME} and ${var.x\"\n";
+    let unsupported = Workspace::parse("user.yaml", unsupported).unwrap();
+    assert!(matches!(
+        unsupported.resolve(&Variables::default()),
+        Err(Error::Interpolation { .. })
+    ));
+
+    // Attributes are JSON Pointers, so keys containing dots stay unambiguous and the
+    // error can be located in the file.
+    let dotted = "resource_type: client\nname: app\nspec:\n  attributes:\n    pkce.code.challenge.method: ${var.method}\n";
+    let dotted = Workspace::parse("client.yaml", dotted).unwrap();
+    match dotted.resolve(&Variables::default()) {
+        Err(Error::UndefinedVariable {
+            address, attribute, ..
+        }) => {
+            assert_eq!(attribute, "/attributes/pkce.code.challenge.method");
+            let loaded = dotted.get(&address).unwrap();
+            assert_eq!(loaded.attribute_location(&attribute).line, 5);
+        }
+        other => panic!("expected an undefined variable, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_invalid_declarations_and_var_files() {
+    let duplicate = "variable: realm\n---\nvariable: realm\n";
+    match Workspace::parse("vars.yaml", duplicate) {
+        Err(Error::InvalidConfig { location, reason }) => {
+            assert_eq!(location.line, 3);
+            assert!(
+                reason.contains("already declared at vars.yaml:1:1"),
+                "{reason}"
+            );
+        }
+        other => panic!("expected a duplicate variable, got {other:?}"),
+    }
+    assert!(matches!(
+        Workspace::parse("vars.yaml", "variable: realm\ntype: text\n"),
+        Err(Error::InvalidConfig { .. })
+    ));
+
+    let variables = workspace().variable_values();
+    match variables
+        .clone()
+        .parse_var_file("prod.yaml", "realm: prod\nregion: eu\n")
+    {
+        Err(Error::InvalidConfig { location, reason }) => {
+            assert_eq!((location.line, location.column), (2, 1));
+            assert!(reason.contains("undeclared variable 'region'"), "{reason}");
+        }
+        other => panic!("expected an undeclared variable, got {other:?}"),
+    }
+    assert!(variables.value("region", json!("eu")).is_err());
+}
@@ -0,0 +1,210 @@
+use async_trait::async_trait;
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::provider::{config_schema, ConfigureRequest, ConfigureResponse};
+use iamctl_rust_sdk::server::{JsonRpcRequest, JsonRpcResponse, RequestHandler};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct KeycloakConfig {
+    url: String,
+    realm: String,
+}
+
+#[derive(Default)]
+struct ConfigurableProvider {
+    realm: Mutex<Option<String>>,
+}
+
+#[async_trait]
+impl Provider for ConfigurableProvider {
+    fn metadata(&self) -> ProviderMetadata {
+        ProviderMetadata {
+            name: "keycloak".to_string(),
+            version: "0.1.0".to_string(),
+            author: None,
+            repository: None,
+            description: None,
+        }
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            supported_resources: vec!["user".to_string()],
+            can_import: false,
+            can_validate: false,
+            can_refresh: false,
+            config_schema: Some(config_schema::<KeycloakConfig>()),
+            can_discover: false,
+            resource_types: vec![],
+            data_sources: vec![],
+        }
+    }
+
+    async fn configure(&self, request: ConfigureRequest) -> anyhow::Result<ConfigureResponse> {
+        let config: KeycloakConfig = request.decode()?;
+        if config.realm == "forbidden" {
+            anyhow::bail!("cannot connect to realm {}", config.realm);
+        }
+        *self.realm.lock().unwrap() = Some(config.realm);
+        Ok(ConfigureResponse::default())
+    }
+
+    async fn plan(&self, _request: PlanRequest) -> anyhow::Result<PlanResponse> {
+        Ok(PlanResponse {
+            changes: vec![],
+            diagnostics: vec![],
+        })
+    }
+
+    async fn apply(&self, _request: ApplyRequest) -> anyhow::Result<ApplyResponse> {
+        Ok(ApplyResponse {
+            successful_addresses: vec![],
+            diagnostics: vec![],
+            results: vec![],
+        })
+    }
+}
+
+async fn call<P: Provider + 'static>(
+    handler: &RequestHandler<P>,
+    method: &str,
+    params: serde_json::Value,
+) -> JsonRpcResponse {
+    handler
+        .handle(JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+            id: Some(json!(1)),
+        })
+        .await
+}
+
+fn empty_plan() -> serde_json::Value {
+    json!({ "workspace_path": ".", "desired_state": [], "current_state": [] })
+}
+
+#[tokio::test]
+async fn test_plan_rejected_until_configured() {
+    let provider = Arc::new(ConfigurableProvider::default());
+    let handler = RequestHandler::new(provider.clone());
+
+    let resp = call(&handler, "plan", empty_plan()).await;
+    assert_eq!(resp.error.unwrap().code, -32001);
+    let resp = call(&handler, "apply", json!({ "changes": [] })).await;
+    assert_eq!(resp.error.unwrap().code, -32001);
+
+    // Metadata and capabilities remain available, and publish the config schema.
+    let resp = call(&handler, "capabilities", json!({})).await;
+    let caps = resp.result.unwrap();
+    assert_eq!(caps["config_schema"]["properties"]["url"]["type"], "string");
+
+    let resp = call(
+        &handler,
+        "configure",
+        json!({ "config": { "url": "https://kc", "realm": "demo" } }),
+    )
+    .await;
+    assert!(resp.error.is_none());
+    assert_eq!(provider.realm.lock().unwrap().as_deref(), Some("demo"));
+
+    let resp = call(&handler, "plan", empty_plan()).await;
+    assert!(resp.result.is_some());
+}
+
+#[tokio::test]
+async fn test_configure_validates_against_schema() {
+    let handler = RequestHandler::new(Arc::new(ConfigurableProvider::default()));
+
+    let resp = call(
+        &handler,
+        "configure",
+        json!({ "config": { "url": "https://kc" } }),
+    )
+    .await;
+    let error = resp.error.unwrap();
+    assert_eq!(error.code, -32602);
+    assert!(error.message.contains("realm"));
+
+    let resp = call(&handler, "plan", empty_plan()).await;
+    assert_eq!(resp.error.unwrap().code, -32001);
+}
+
+#[tokio::test]
+async fn test_failed_reconfiguration_unconfigures() {
+    let handler = RequestHandler::new(Arc::new(ConfigurableProvider::default()));
+
+    let ok = json!({ "config": { "url": "https://kc", "realm": "demo" } });
+    assert!(call(&handler, "configure", ok).await.error.is_none());
+
+    let bad = json!({ "config": { "url": "https://kc", "realm": "forbidden" } });
+    assert_eq!(
+        call(&handler, "configure", bad).await.error.unwrap().code,
+        -32000
+    );
+    assert_eq!(
+        call(&handler, "plan", empty_plan())
+            .await
+            .error
+            .unwrap()
+            .code,
+        -32001
+    );
+}
+
+#[tokio::test]
+async fn test_providers_without_config_schema_need_no_configure() {
+    let provider = ProviderBuilder::new(ProviderMetadata {
+        name: "plain".to_string(),
+        version: "0.1.0".to_string(),
+        author: None,
+        repository: None,
+        description: None,
+    })
+    .build();
+    let handler = RequestHandler::new(Arc::new(provider));
+
+    assert!(call(&handler, "plan", empty_plan()).await.result.is_some());
+}
+
+#[tokio::test]
+async fn test_builder_typed_configure_hook() {
+    let seen = Arc::new(Mutex::new(None));
+    let sink = seen.clone();
+    let provider = ProviderBuilder::new(ProviderMetadata {
+        name: "keycloak".to_string(),
+        version: "0.1.0".to_string(),
+        author: None,
+        repository: None,
+        description: None,
+    })
+    .configure(move |config: KeycloakConfig| {
+        *sink.lock().unwrap() = Some(config.url);
+        Ok(())
+    })
+    .build();
+    assert!(provider.capabilities().config_schema.is_some());
+
+    let handler = RequestHandler::new(Arc::new(provider));
+    assert_eq!(
+        call(&handler, "plan", empty_plan())
+            .await
+            .error
+            .unwrap()
+            .code,
+        -32001
+    );
+
+    let resp = call(
+        &handler,
+        "configure",
+        json!({ "config": { "url": "https://kc", "realm": "demo" } }),
+    )
+    .await;
+    assert!(resp.error.is_none());
+    assert_eq!(seen.lock().unwrap().as_deref(), Some("https://kc"));
+    assert!(call(&handler, "plan", empty_plan()).await.result.is_some());
+}
@@ -0,0 +1,233 @@
+mod common;
+
+use async_trait::async_trait;
+use common::{user, UserHandler};
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::provider::{
+    ApplyStatus, CancellationToken, HandlerProvider, ProgressReporter, ProviderContext,
+};
+use iamctl_rust_sdk::server::{JsonRpcRequest, RequestHandler};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+struct ContextProvider;
+
+#[async_trait]
+impl Provider for ContextProvider {
+    fn metadata(&self) -> ProviderMetadata {
+        ProviderMetadata {
+            name: "context-provider".to_string(),
+            version: "0.1.0".to_string(),
+            author: None,
+            repository: None,
+            description: None,
+        }
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            supported_resources: vec!["user".to_string()],
+            can_import: false,
+            can_validate: false,
+            can_refresh: false,
+            config_schema: None,
+            can_discover: false,
+            resource_types: vec![],
+            data_sources: vec![],
+        }
+    }
+
+    async fn plan(&self, _request: PlanRequest) -> anyhow::Result<PlanResponse> {
+        anyhow::bail!("plan_with_context should be called instead")
+    }
+
+    async fn plan_with_context(
+        &self,
+        ctx: &ProviderContext,
+        _request: PlanRequest,
+    ) -> anyhow::Result<PlanResponse> {
+        Ok(PlanResponse {
+            changes: vec![],
+            diagnostics: vec![Diagnostic::warning(format!(
+                "{} {}",
+                ctx.method(),
+                ctx.request_id()
+            ))],
+        })
+    }
+
+    async fn apply(&self, _request: ApplyRequest) -> anyhow::Result<ApplyResponse> {
+        anyhow::bail!("apply_with_context should be called instead")
+    }
+
+    async fn apply_with_context(
+        &self,
+        ctx: &ProviderContext,
+        _request: ApplyRequest,
+    ) -> anyhow::Result<ApplyResponse> {
+        // Wait for the engine or the deadline to cancel the request.
+        ctx.cancellation().cancelled().await;
+        ctx.check_cancelled()?;
+        unreachable!("check_cancelled fails once the token is cancelled")
+    }
+}
+
+fn request(method: &str, params: serde_json::Value) -> JsonRpcRequest {
+    JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        method: method.to_string(),
+        params,
+        id: Some(json!(7)),
+    }
+}
+
+fn users_provider() -> HandlerProvider {
+    ProviderBuilder::new(ProviderMetadata {
+        name: "users".to_string(),
+        version: "0.1.0".to_string(),
+        author: None,
+        repository: None,
+        description: None,
+    })
+    .resource("user", UserHandler::default())
+    .build()
+}
+
+fn create(name: &str) -> Change {
+    let after = user(name, json!({ "username": name }));
+    Change {
+        address: after.address.clone(),
+        change_type: ChangeType::Create,
+        before: None,
+        after: Some(after),
+        computed_fields: vec![],
+        replace_order: None,
+        attribute_changes: vec![],
+        patch: None,
+    }
+}
+
+#[tokio::test]
+async fn test_cancellation_token_wakes_waiters() {
+    let token = CancellationToken::new();
+    let waiter = tokio::spawn({
+        let token = token.clone();
+        async move { token.cancelled().await }
+    });
+
+    assert!(!token.is_cancelled());
+    token.cancel();
+    waiter.await.unwrap();
+    assert!(token.is_cancelled());
+}
+
+#[tokio::test]
+async fn test_context_deadline_counts_as_cancellation() {
+    let ctx = ProviderContext::new(json!(1), "apply");
+    assert!(ctx.remaining().is_none());
+    assert!(ctx.check_cancelled().is_ok());
+
+    let ctx = ctx.with_timeout(Duration::from_millis(10));
+    assert!(ctx.remaining().unwrap() <= Duration::from_millis(10));
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(ctx.is_cancelled());
+    let err = ctx.check_cancelled().unwrap_err();
+    assert!(err.to_string().contains("(apply) was cancelled"));
+}
+
+#[tokio::test]
+async fn test_handler_passes_context_to_provider() {
+    let handler = RequestHandler::new(Arc::new(ContextProvider));
+    let response = handler
+        .handle(request(
+            "plan",
+            json!({ "workspace_path": ".", "desired_state": [], "current_state": [] }),
+        ))
+        .await;
+
+    let result = response.result.unwrap();
+    assert_eq!(result["diagnostics"][0]["summary"], "plan 7");
+}
+
+#[tokio::test]
+async fn test_request_timeout_cancels_running_request() {
+    let handler = RequestHandler::new(Arc::new(ContextProvider))
+        .with_request_timeout(Duration::from_millis(20));
+    let response = handler
+        .handle(request("apply", json!({ "changes": [] })))
+        .await;
+
+    let error = response.error.unwrap();
+    assert_eq!(error.code, -32000);
+    assert!(error.message.contains("was cancelled"));
+}
+
+#[tokio::test]
+async fn test_apply_reports_progress_and_skips_after_cancel() {
+    let provider = users_provider();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let ctx =
+        ProviderContext::new(json!(3), "apply").with_progress(ProgressReporter::new(json!(3), tx));
+
+    let response = provider
+        .apply_with_context(
+            &ctx,
+            ApplyRequest {
+                changes: vec![create("alice"), create("bob")],
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.successful_addresses.len(), 2);
+
+    let first = rx.recv().await.unwrap();
+    assert_eq!(first.request_id, json!(3));
+    assert_eq!(first.message, "Applied user.alice (1/2)");
+    assert_eq!(first.percentage, Some(50));
+    assert_eq!(rx.recv().await.unwrap().percentage, Some(100));
+
+    ctx.cancellation().cancel();
+    let response = provider
+        .apply_with_context(
+            &ctx,
+            ApplyRequest {
+                changes: vec![create("carol")],
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.results[0].status, ApplyStatus::Skipped);
+    assert_eq!(
+        response.diagnostics[0].summary,
+        "Skipped: request was cancelled"
+    );
+    assert!(rx.try_recv().is_err());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_client_skips_notifications() {
+    use iamctl_rust_sdk::server::JsonRpcClient;
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("provider.sh");
+    std::fs::write(
+        &script,
+        concat!(
+            "#!/bin/sh\n",
+            "read -r _line\n",
+            "echo '{\"jsonrpc\":\"2.0\",\"method\":\"$/progress\",",
+            "\"params\":{\"request_id\":1,\"message\":\"working\"}}'\n",
+            "echo '{\"jsonrpc\":\"2.0\",\"result\":{\"ok\":true},\"id\":1}'\n",
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let mut client = JsonRpcClient::launch(script.to_str().unwrap()).unwrap();
+    let result: serde_json::Value = client.call("metadata", json!({})).await.unwrap();
+    assert_eq!(result, json!({ "ok": true }));
+}
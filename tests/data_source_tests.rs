@@ -0,0 +1,326 @@
+mod common;
+
+use async_trait::async_trait;
+use common::{resource, UserHandler};
+use iamctl_rust_sdk::config::Workspace;
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::provider::{
+    DataSource, DataSourceHandler, HandlerProvider, ReadDataSourceRequest, ReadDataSourceResponse,
+};
+use iamctl_rust_sdk::server::{
+    Cassette, JsonRpcClient, JsonRpcRequest, JsonRpcResponse, ProviderPool, RequestHandler,
+};
+use iamctl_rust_sdk::utils::Error;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Deserialize, JsonSchema)]
+struct RoleQuery {
+    realm: String,
+    name: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct Role {
+    id: String,
+    composite: bool,
+}
+
+struct RoleLookup;
+
+#[async_trait]
+impl DataSourceHandler for RoleLookup {
+    type Query = RoleQuery;
+    type Output = Role;
+
+    async fn read(&self, _address: &ResourceAddress, query: RoleQuery) -> anyhow::Result<Role> {
+        if query.name != "admin" {
+            anyhow::bail!("role {} not found in realm {}", query.name, query.realm);
+        }
+        Ok(Role {
+            id: format!("{}-{}", query.realm, query.name),
+            composite: true,
+        })
+    }
+}
+
+fn provider() -> HandlerProvider {
+    ProviderBuilder::new(ProviderMetadata {
+        name: "roles".to_string(),
+        version: "0.1.0".to_string(),
+        author: None,
+        repository: None,
+        description: None,
+    })
+    .data_source("role", RoleLookup)
+    .build()
+}
+
+fn address(name: &str) -> ResourceAddress {
+    ResourceAddress {
+        resource_type: "role".to_string(),
+        name: name.to_string(),
+        namespace: None,
+    }
+}
+
+fn request(method: &str, params: serde_json::Value) -> JsonRpcRequest {
+    JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        method: method.to_string(),
+        params,
+        id: Some(json!(1)),
+    }
+}
+
+#[test]
+fn test_capabilities_publish_data_source_schemas() {
+    let capabilities = provider().capabilities();
+    assert!(capabilities.supported_resources.is_empty());
+    assert_eq!(capabilities.data_sources.len(), 1);
+
+    let descriptor = &capabilities.data_sources[0];
+    assert_eq!(descriptor.data_source_type, "role");
+    let query_schema = descriptor.query_schema.as_ref().unwrap();
+    assert_eq!(query_schema["properties"]["realm"]["type"], "string");
+    let result_schema = descriptor.result_schema.as_ref().unwrap();
+    assert_eq!(result_schema["properties"]["composite"]["type"], "boolean");
+}
+
+#[tokio::test]
+async fn test_read_data_source_over_json_rpc() {
+    let handler = RequestHandler::new(Arc::new(provider()));
+
+    let resp = handler
+        .handle(request(
+            "read_data_source",
+            json!({
+                "address": address("admin"),
+                "query": { "realm": "demo", "name": "admin" }
+            }),
+        ))
+        .await;
+    let response: ReadDataSourceResponse = serde_json::from_value(resp.result.unwrap()).unwrap();
+    assert_eq!(response.data_source.address, address("admin"));
+    assert_eq!(response.data_source.get("id"), Some(&json!("demo-admin")));
+
+    let resp = handler
+        .handle(request(
+            "read_data_source",
+            json!({ "address": address("viewer"), "query": { "realm": "demo", "name": "viewer" } }),
+        ))
+        .await;
+    let error = resp.error.unwrap();
+    assert_eq!(error.code, -32000);
+    assert!(error.message.contains("not found"));
+
+    let resp = handler
+        .handle(request(
+            "read_data_source",
+            json!({ "address": address("admin"), "query": { "realm": "demo" } }),
+        ))
+        .await;
+    assert!(resp.error.unwrap().message.contains("Invalid query"));
+}
+
+#[tokio::test]
+async fn test_data_sources_are_plan_inputs() {
+    let data_source = provider()
+        .read_data_source(ReadDataSourceRequest {
+            address: address("admin"),
+            query: HashMap::from([
+                ("realm".to_string(), json!("demo")),
+                ("name".to_string(), json!("admin")),
+            ]),
+        })
+        .await
+        .unwrap()
+        .data_source;
+
+    let plan: PlanRequest = serde_json::from_value(json!({
+        "workspace_path": ".",
+        "desired_state": [],
+        "current_state": [],
+        "data_sources": [data_source],
+    }))
+    .unwrap();
+    let role = plan.data_source(&address("admin")).unwrap();
+    assert_eq!(role.get("composite"), Some(&json!(true)));
+    assert!(plan.data_source(&address("viewer")).is_none());
+    // Data sources are looked up by their full address, namespace included.
+    assert!(plan
+        .data_source(&"role.demo/admin".parse().unwrap())
+        .is_none());
+
+    // Requests from older engines carry no data sources.
+    let legacy: PlanRequest = serde_json::from_value(
+        json!({ "workspace_path": ".", "desired_state": [], "current_state": [] }),
+    )
+    .unwrap();
+    assert!(legacy.data_sources.is_empty());
+}
+
+#[tokio::test]
+async fn test_pool_routes_data_sources_by_type() {
+    let data_source = DataSource {
+        address: address("admin"),
+        values: HashMap::from([("id".to_string(), json!("demo-admin"))]),
+    };
+    let read = ReadDataSourceRequest {
+        address: address("admin"),
+        query: HashMap::new(),
+    };
+
+    let mut cassette = Cassette::default();
+    cassette.push(
+        request("capabilities", json!(null)),
+        JsonRpcResponse::success(
+            json!(1),
+            serde_json::to_value(provider().capabilities()).unwrap(),
+        ),
+    );
+    cassette.push(
+        request("read_data_source", serde_json::to_value(&read).unwrap()),
+        JsonRpcResponse::success(
+            json!(1),
+            serde_json::to_value(ReadDataSourceResponse {
+                data_source: data_source.clone(),
+            })
+            .unwrap(),
+        ),
+    );
+
+    let mut pool = ProviderPool::new();
+    pool.register("roles", JsonRpcClient::from_cassette(cassette))
+        .await
+        .unwrap();
+
+    let response = pool.read_data_source(read).await.unwrap();
+    assert_eq!(response.data_source, data_source);
+
+    let missing = pool
+        .read_data_source(ReadDataSourceRequest {
+            address: ResourceAddress {
+                resource_type: "group".to_string(),
+                name: "devs".to_string(),
+                namespace: None,
+            },
+            query: HashMap::new(),
+        })
+        .await;
+    assert!(missing.is_err());
+}
+
+#[test]
+fn test_plan_request_interpolates_data_references() {
+    let data_source = |address: &str, id: &str| DataSource {
+        address: address.parse().unwrap(),
+        values: HashMap::from([
+            ("id".to_string(), json!(id)),
+            ("scopes".to_string(), json!(["read", "write"])),
+        ]),
+    };
+    let plan = PlanRequest {
+        workspace_path: ".".to_string(),
+        desired_state: vec![],
+        current_state: vec![],
+        data_sources: vec![
+            data_source("role.admin", "global-admin"),
+            data_source("role.demo/admin", "demo-admin"),
+        ],
+    };
+    let binding = |spec| resource("binding.demo/alice", spec);
+
+    let resolved = plan
+        .interpolate(&binding(json!({
+            "role": "${data.role.demo/admin.id}",
+            "label": "role ${data.role.admin.id} for ${var.user}",
+            "scopes": ["${data.role.admin.scopes}"],
+        })))
+        .unwrap();
+    assert_eq!(resolved.spec["role"], json!("demo-admin"));
+    // Other expressions are left for whoever resolves them.
+    assert_eq!(
+        resolved.spec["label"],
+        json!("role global-admin for ${var.user}")
+    );
+    assert_eq!(resolved.spec["scopes"], json!([["read", "write"]]));
+
+    for (reference, reason) in [
+        (
+            "${data.role.viewer.id}",
+            "data source role.viewer was not read",
+        ),
+        (
+            "${data.role.admin.name}",
+            "data source role.admin has no attribute 'name'",
+        ),
+        (
+            "x ${data.role.admin.scopes}",
+            "is a list and cannot be inserted",
+        ),
+        (
+            "${data.role}",
+            "expected '${data.<type>.<name>.<attribute>}'",
+        ),
+    ] {
+        match plan.interpolate(&binding(json!({ "role": reference }))) {
+            Err(Error::Interpolation { reason: error, .. }) => {
+                assert!(error.contains(reason), "{error}")
+            }
+            other => panic!("expected an interpolation error for {reference}, got {other:?}"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_escaped_data_references_survive_planning() {
+    let workspace = Workspace::parse(
+        "users.yaml",
+        "\
+resource_type: user
+name: alice
+spec:
+  username: ${data.role.admin.id}
+  email: $${data.role.admin.id}@example.com
+",
+    )
+    .unwrap();
+    let desired_state = workspace.resolve(&workspace.variable_values()).unwrap();
+    // The escape is kept for the provider's pass.
+    assert_eq!(
+        desired_state[0].spec["email"],
+        json!("$${data.role.admin.id}@example.com")
+    );
+
+    let provider = ProviderBuilder::new(ProviderMetadata {
+        name: "users".to_string(),
+        version: "0.1.0".to_string(),
+        author: None,
+        repository: None,
+        description: None,
+    })
+    .resource("user", UserHandler::default())
+    .build();
+    let response = provider
+        .plan(PlanRequest {
+            workspace_path: ".".to_string(),
+            desired_state,
+            current_state: vec![],
+            data_sources: vec![DataSource {
+                address: "role.admin".parse().unwrap(),
+                values: HashMap::from([("id".to_string(), json!("global-admin"))]),
+            }],
+        })
+        .await
+        .unwrap();
+    let after = response.changes[0].after.as_ref().unwrap();
+    assert_eq!(after.spec["username"], json!("global-admin"));
+    assert_eq!(
+        after.spec["email"],
+        json!("${data.role.admin.id}@example.com")
+    );
+}
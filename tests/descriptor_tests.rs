@@ -0,0 +1,253 @@
+mod common;
+
+use async_trait::async_trait;
+use common::resource;
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::provider::{HandlerProvider, ResourceTypeDescriptor};
+use iamctl_rust_sdk::server::{
+    Cassette, JsonRpcClient, JsonRpcRequest, JsonRpcResponse, ProviderPool,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+struct RealmSpec {
+    name: String,
+    #[serde(default)]
+    settings: HashMap<String, serde_json::Value>,
+}
+
+struct RealmHandler;
+
+#[async_trait]
+impl ResourceHandler for RealmHandler {
+    type Spec = RealmSpec;
+
+    async fn create(
+        &self,
+        _address: &ResourceAddress,
+        spec: RealmSpec,
+    ) -> anyhow::Result<RealmSpec> {
+        Ok(spec)
+    }
+
+    async fn read(
+        &self,
+        _address: &ResourceAddress,
+        spec: RealmSpec,
+    ) -> anyhow::Result<Option<RealmSpec>> {
+        Ok(Some(spec))
+    }
+
+    async fn update(
+        &self,
+        _address: &ResourceAddress,
+        _before: RealmSpec,
+        after: RealmSpec,
+    ) -> anyhow::Result<RealmSpec> {
+        Ok(after)
+    }
+
+    async fn delete(&self, _address: &ResourceAddress, _spec: RealmSpec) -> anyhow::Result<()> {
+        anyhow::bail!("realms cannot be deleted")
+    }
+
+    fn descriptor(&self, resource_type: &str) -> ResourceTypeDescriptor {
+        ResourceTypeDescriptor {
+            delete: false,
+            schema_version: 2,
+            immutable_attributes: vec!["/name".to_string()],
+            force_replace_attributes: vec!["/settings/storage".to_string()],
+            ..ResourceTypeDescriptor::new(resource_type)
+        }
+    }
+}
+
+fn provider() -> HandlerProvider {
+    ProviderBuilder::new(ProviderMetadata {
+        name: "realms".to_string(),
+        version: "0.1.0".to_string(),
+        author: None,
+        repository: None,
+        description: None,
+    })
+    .resource("realm", RealmHandler)
+    .build()
+}
+
+fn codes(diagnostics: &[Diagnostic]) -> Vec<&str> {
+    diagnostics
+        .iter()
+        .map(|d| d.code.as_deref().unwrap_or_default())
+        .collect()
+}
+
+#[test]
+fn test_descriptor_defaults_and_legacy_capabilities() {
+    let descriptor: ResourceTypeDescriptor =
+        serde_json::from_value(json!({ "resource_type": "user" })).unwrap();
+    assert_eq!(descriptor, ResourceTypeDescriptor::new("user"));
+    assert!(descriptor.create && descriptor.read && descriptor.update && descriptor.delete);
+    assert!(!descriptor.import);
+
+    let capabilities: ProviderCapabilities = serde_json::from_value(json!({
+        "supported_resources": ["user"],
+        "can_import": false,
+        "can_validate": false
+    }))
+    .unwrap();
+    assert!(capabilities.resource_types.is_empty());
+    assert!(capabilities.descriptor("user").is_none());
+}
+
+#[tokio::test]
+async fn test_builder_publishes_descriptors() {
+    let capabilities = provider().capabilities();
+    let descriptor = capabilities.descriptor("realm").unwrap();
+    assert!(!descriptor.delete);
+    assert_eq!(descriptor.schema_version, 2);
+}
+
+#[tokio::test]
+async fn test_plan_reports_constraint_violations() {
+    let current = vec![
+        resource(
+            "realm.main",
+            json!({ "name": "main", "settings": { "storage": "ldap" } }),
+        ),
+        resource("realm.legacy", json!({ "name": "legacy" })),
+    ];
+    let desired = vec![resource(
+        "realm.main",
+        json!({ "name": "primary", "settings": { "storage": "jdbc" } }),
+    )];
+
+    let plan = provider()
+        .plan(PlanRequest {
+            workspace_path: ".".to_string(),
+            desired_state: desired,
+            current_state: current,
+            data_sources: vec![],
+        })
+        .await
+        .unwrap();
+
+    // The force-replace attribute turns the update into a replacement, which needs
+    // delete support.
+    assert_eq!(plan.changes[0].change_type, ChangeType::Replace);
+    assert_eq!(
+        codes(&plan.diagnostics),
+        vec![
+            "UNSUPPORTED_OPERATION",
+            "IMMUTABLE_ATTRIBUTE",
+            "UNSUPPORTED_OPERATION"
+        ]
+    );
+    assert_eq!(plan.diagnostics[1].attribute.as_deref(), Some("spec/name"));
+    assert_eq!(plan.diagnostics[2].address.as_ref().unwrap().name, "legacy");
+}
+
+#[test]
+fn test_updates_of_force_replace_attributes_are_rejected() {
+    let before = resource(
+        "realm.main",
+        json!({ "name": "main", "settings": { "storage": "ldap" } }),
+    );
+    let after = resource(
+        "realm.main",
+        json!({ "name": "main", "settings": { "storage": "jdbc" } }),
+    );
+    let update = Change {
+        address: before.address.clone(),
+        change_type: ChangeType::Update,
+        before: Some(before),
+        after: Some(after),
+        computed_fields: vec![],
+        replace_order: None,
+        attribute_changes: vec![],
+        patch: None,
+    };
+
+    let capabilities = provider().capabilities();
+    let diagnostics = capabilities.check_changes(&[update]);
+    assert_eq!(codes(&diagnostics), vec!["REPLACE_REQUIRED"]);
+    assert_eq!(
+        diagnostics[0].attribute.as_deref(),
+        Some("spec/settings/storage")
+    );
+}
+
+#[tokio::test]
+async fn test_unchanged_constrained_attributes_pass() {
+    let current = vec![resource("realm.main", json!({ "name": "main" }))];
+    let desired = vec![resource(
+        "realm.main",
+        json!({ "name": "main", "settings": { "theme": "dark" } }),
+    )];
+
+    let plan = provider()
+        .plan(PlanRequest {
+            workspace_path: ".".to_string(),
+            desired_state: desired,
+            current_state: current,
+            data_sources: vec![],
+        })
+        .await
+        .unwrap();
+    assert_eq!(plan.changes[0].change_type, ChangeType::Update);
+    assert!(plan.diagnostics.is_empty());
+}
+
+#[tokio::test]
+async fn test_pool_checks_changes_before_calling_providers() {
+    let mut cassette = Cassette::default();
+    cassette.push(
+        JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "capabilities".to_string(),
+            params: json!(null),
+            id: Some(json!(1)),
+        },
+        JsonRpcResponse::success(
+            json!(1),
+            serde_json::to_value(provider().capabilities()).unwrap(),
+        ),
+    );
+    let mut pool = ProviderPool::new();
+    pool.register("realms", JsonRpcClient::from_cassette(cassette))
+        .await
+        .unwrap();
+
+    let legacy = resource("realm.legacy", json!({ "name": "legacy" }));
+    let mut group = legacy.clone();
+    group.address.resource_type = "group".to_string();
+    let changes = vec![
+        Change {
+            address: legacy.address.clone(),
+            change_type: ChangeType::Delete,
+            before: Some(legacy),
+            after: None,
+            computed_fields: vec![],
+            replace_order: None,
+            attribute_changes: vec![],
+            patch: None,
+        },
+        Change {
+            address: group.address.clone(),
+            change_type: ChangeType::Create,
+            before: None,
+            after: Some(group),
+            computed_fields: vec![],
+            replace_order: None,
+            attribute_changes: vec![],
+            patch: None,
+        },
+    ];
+
+    assert_eq!(
+        codes(&pool.check_changes(&changes)),
+        vec!["UNSUPPORTED_OPERATION", "UNSUPPORTED_RESOURCE_TYPE"]
+    );
+}
@@ -0,0 +1,187 @@
+mod common;
+
+use async_trait::async_trait;
+use common::{user, UserSpec};
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::provider::{ApplyError, ApplyResult, ValidateRequest, ValidateResponse};
+use iamctl_rust_sdk::server::{JsonRpcRequest, JsonRpcResponse, RequestHandler};
+use iamctl_rust_sdk::types::{Diagnostic, Severity};
+use serde_json::json;
+use std::sync::Arc;
+
+struct LintingProvider;
+
+#[async_trait]
+impl Provider for LintingProvider {
+    fn metadata(&self) -> ProviderMetadata {
+        ProviderMetadata {
+            name: "linting".to_string(),
+            version: "0.1.0".to_string(),
+            author: None,
+            repository: None,
+            description: None,
+        }
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            supported_resources: vec!["user".to_string()],
+            can_import: false,
+            can_validate: true,
+            can_refresh: false,
+            config_schema: None,
+            can_discover: false,
+            resource_types: vec![],
+            data_sources: vec![],
+        }
+    }
+
+    fn schemas(&self) -> std::collections::HashMap<String, serde_json::Value> {
+        std::collections::HashMap::from([(
+            "user".to_string(),
+            serde_json::to_value(schemars::schema_for!(UserSpec)).unwrap(),
+        )])
+    }
+
+    async fn plan(&self, _request: PlanRequest) -> anyhow::Result<PlanResponse> {
+        Ok(PlanResponse {
+            changes: vec![],
+            diagnostics: vec![],
+        })
+    }
+
+    async fn apply(&self, _request: ApplyRequest) -> anyhow::Result<ApplyResponse> {
+        Ok(ApplyResponse::from_results(vec![]))
+    }
+
+    async fn validate(&self, request: ValidateRequest) -> anyhow::Result<ValidateResponse> {
+        let diagnostics = request
+            .resources
+            .iter()
+            .filter(|r| !r.spec.contains_key("email"))
+            .map(|r| {
+                Diagnostic::warning("User has no email address")
+                    .with_address(r.address.clone())
+                    .with_attribute("spec/email")
+            })
+            .collect();
+        Ok(ValidateResponse {
+            valid: true,
+            diagnostics,
+        })
+    }
+}
+
+async fn call(method: &str, params: serde_json::Value) -> JsonRpcResponse {
+    RequestHandler::new(Arc::new(LintingProvider))
+        .handle(JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+            id: Some(json!(1)),
+        })
+        .await
+}
+
+#[tokio::test]
+async fn test_validate_reports_schema_and_provider_diagnostics() {
+    let resp = call(
+        "validate",
+        json!({ "resources": [
+            user("alice", json!({ "username": 42 })),
+            user("bob", json!({ "username": "bob", "email": "bob@example.com" })),
+        ] }),
+    )
+    .await;
+    let response: ValidateResponse = serde_json::from_value(resp.result.unwrap()).unwrap();
+
+    assert!(!response.valid);
+    let error = response
+        .diagnostics
+        .iter()
+        .find(|d| d.severity == Severity::Error)
+        .unwrap();
+    assert_eq!(error.address.as_ref().unwrap().name, "alice");
+    assert_eq!(error.attribute.as_deref(), Some("spec/username"));
+    assert_eq!(error.code.as_deref(), Some("SCHEMA_VALIDATION_ERROR"));
+
+    let warning = response
+        .diagnostics
+        .iter()
+        .find(|d| d.severity == Severity::Warning)
+        .unwrap();
+    assert_eq!(warning.address.as_ref().unwrap().name, "alice");
+}
+
+#[tokio::test]
+async fn test_validate_warnings_keep_resources_valid() {
+    let resp = call(
+        "validate",
+        json!({ "resources": [user("alice", json!({ "username": "alice" }))] }),
+    )
+    .await;
+    let response: ValidateResponse = serde_json::from_value(resp.result.unwrap()).unwrap();
+
+    assert!(response.valid);
+    assert_eq!(response.diagnostics.len(), 1);
+    assert!(!Diagnostic::any_errors(&response.diagnostics));
+}
+
+#[tokio::test]
+async fn test_plan_schema_errors_carry_diagnostics() {
+    let resp = call(
+        "plan",
+        json!({
+            "workspace_path": ".",
+            "desired_state": [user("alice", json!({ "username": 42 }))],
+            "current_state": [],
+        }),
+    )
+    .await;
+    let error = resp.error.unwrap();
+    assert_eq!(error.code, -32602);
+
+    let diagnostics: Vec<Diagnostic> =
+        serde_json::from_value(error.data.unwrap()["diagnostics"].clone()).unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].attribute.as_deref(), Some("spec/username"));
+}
+
+#[test]
+fn test_apply_failures_become_diagnostics() {
+    let address = ResourceAddress {
+        resource_type: "user".to_string(),
+        name: "alice".to_string(),
+        namespace: Some("prod".to_string()),
+    };
+    let response = ApplyResponse::from_results(vec![
+        ApplyResult::failed(
+            address.clone(),
+            ApplyError {
+                message: "conflict".to_string(),
+                code: Some("409".to_string()),
+                retryable: true,
+            },
+        ),
+        ApplyResult::succeeded(address.clone(), None).with_warning("slow API"),
+    ]);
+
+    assert_eq!(response.failed_addresses(), vec![&address]);
+    assert_eq!(
+        response.diagnostics[0].to_string(),
+        "error [user.prod/alice]: conflict"
+    );
+    assert_eq!(response.diagnostics[0].code.as_deref(), Some("409"));
+    assert_eq!(response.diagnostics[1].severity, Severity::Warning);
+}
+
+#[test]
+fn test_diagnostic_display() {
+    let diagnostic = Diagnostic::error("must be a string")
+        .with_attribute("spec/username")
+        .with_detail("got 42");
+    assert_eq!(
+        diagnostic.to_string(),
+        "error [spec/username]: must be a string (got 42)"
+    );
+}
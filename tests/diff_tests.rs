@@ -0,0 +1,318 @@
+mod common;
+
+use common::{resource, user};
+use iamctl_rust_sdk::plan::{render_plan, set_attributes, Differ, Planner};
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::types::AttributeChange;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeSet;
+
+fn paths(changes: &[AttributeChange]) -> Vec<&str> {
+    changes.iter().map(|c| c.path.as_str()).collect()
+}
+
+fn attributes(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn test_diff_reports_nested_attribute_changes() {
+    let before = user(
+        "alice",
+        json!({
+            "email": "alice@old",
+            "settings": { "theme": "dark", "locale": "en" },
+            "roles": ["admin"],
+            "nickname": null,
+        }),
+    );
+    let after = user(
+        "alice",
+        json!({
+            "email": "alice@new",
+            "settings": { "theme": "dark", "locale": "fr" },
+            "roles": ["admin"],
+            "enabled": true,
+        }),
+    );
+
+    let differ = Differ::new();
+    let changes = differ.diff(Some(&before), Some(&after));
+    assert_eq!(
+        changes,
+        vec![
+            AttributeChange {
+                path: "/email".to_string(),
+                before: Some(json!("alice@old")),
+                after: Some(json!("alice@new")),
+                force_new: false,
+            },
+            AttributeChange {
+                path: "/enabled".to_string(),
+                before: None,
+                after: Some(json!(true)),
+                force_new: false,
+            },
+            AttributeChange {
+                path: "/settings/locale".to_string(),
+                before: Some(json!("en")),
+                after: Some(json!("fr")),
+                force_new: false,
+            },
+        ]
+    );
+    assert_eq!(
+        differ.change_type(Some(&before), Some(&after), &changes),
+        ChangeType::Update
+    );
+
+    // A null attribute equals a missing one.
+    let same = differ.diff(
+        Some(&before),
+        Some(&user(
+            "alice",
+            json!({
+                "email": "alice@old",
+                "settings": { "locale": "en", "theme": "dark" },
+                "roles": ["admin"],
+            }),
+        )),
+    );
+    assert!(same.is_empty());
+    assert_eq!(
+        differ.change_type(Some(&before), Some(&before), &same),
+        ChangeType::NoOp
+    );
+
+    let created = differ.diff(None, Some(&after));
+    assert_eq!(
+        paths(&created),
+        vec!["/email", "/enabled", "/roles", "/settings"]
+    );
+    assert!(created.iter().all(|c| c.before.is_none()));
+    assert_eq!(
+        differ.change_type(None, Some(&after), &created),
+        ChangeType::Create
+    );
+
+    let deleted = differ.diff(Some(&before), None);
+    assert_eq!(paths(&deleted), vec!["/email", "/roles", "/settings"]);
+    assert!(deleted.iter().all(|c| c.after.is_none()));
+}
+
+#[test]
+fn test_computed_and_ignored_attributes() {
+    let differ = Differ::new()
+        .computed(&attributes(&["/id", "/status"]))
+        .ignore(&attributes(&["/metadata/updated_at"]));
+    let current = user(
+        "alice",
+        json!({
+            "id": "42",
+            "email": "alice@example.com",
+            "status": { "state": "active" },
+            "metadata": { "updated_at": "2024-01-01", "owner": "ops" },
+        }),
+    );
+
+    // Computed attributes the desired spec leaves unset are filled in by the provider.
+    let desired = user(
+        "alice",
+        json!({
+            "email": "alice@example.com",
+            "metadata": { "updated_at": "2025-06-01", "owner": "ops" },
+        }),
+    );
+    assert!(differ.diff(Some(&current), Some(&desired)).is_empty());
+
+    let pinned = user(
+        "alice",
+        json!({
+            "id": "43",
+            "email": "alice@example.com",
+            "metadata": { "owner": "ops" },
+        }),
+    );
+    let changes = differ.diff(Some(&current), Some(&pinned));
+    assert_eq!(paths(&changes), vec!["/id"]);
+}
+
+#[test]
+fn test_defaults_sets_and_coercion() {
+    let differ = Differ::new()
+        .default_value("/enabled", json!(true))
+        .default_value("/settings/theme", json!("light"))
+        .set(&attributes(&["/groups"]));
+    let current = user(
+        "alice",
+        json!({
+            "enabled": true,
+            "settings": { "theme": "light" },
+            "groups": ["ops", "admins", "ops"],
+            "max_sessions": 3,
+        }),
+    );
+    let desired = user(
+        "alice",
+        json!({
+            "enabled": null,
+            "groups": ["admins", "ops"],
+            "max_sessions": 3.0,
+        }),
+    );
+    assert!(differ.diff(Some(&current), Some(&desired)).is_empty());
+
+    // Set attributes report their values sorted, for a stable rendering.
+    let changes = differ.diff(
+        Some(&current),
+        Some(&user(
+            "alice",
+            json!({ "groups": ["ops", "devs"], "max_sessions": 3 }),
+        )),
+    );
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].before, Some(json!(["admins", "ops"])));
+    assert_eq!(changes[0].after, Some(json!(["devs", "ops"])));
+
+    let echoed = user("alice", json!({ "max_sessions": "3" }));
+    let strict = Differ::new();
+    assert_eq!(
+        paths(&strict.diff(
+            Some(&user("alice", json!({ "max_sessions": 3 }))),
+            Some(&echoed)
+        )),
+        vec!["/max_sessions"]
+    );
+    let lenient = Differ::new().coerce_scalars(true);
+    assert!(lenient
+        .diff(
+            Some(&user("alice", json!({ "max_sessions": 3 }))),
+            Some(&echoed)
+        )
+        .is_empty());
+    assert_eq!(
+        lenient
+            .diff(
+                Some(&user("alice", json!({ "max_sessions": 3 }))),
+                Some(&user("alice", json!({ "max_sessions": "three" })))
+            )
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_force_new_attributes_yield_replace() {
+    let differ = Differ::new().force_new(&attributes(&["/username", "/settings/realm"]));
+    let before = user(
+        "alice",
+        json!({ "username": "alice", "settings": { "realm": "a" } }),
+    );
+
+    let renamed = user(
+        "alice",
+        json!({ "username": "alice2", "settings": { "realm": "a" } }),
+    );
+    let changes = differ.diff(Some(&before), Some(&renamed));
+    assert!(changes[0].force_new);
+    assert_eq!(
+        differ.change_type(Some(&before), Some(&renamed), &changes),
+        ChangeType::Replace
+    );
+
+    // Removing the parent object of a force-new attribute also forces replacement.
+    let moved = user("alice", json!({ "username": "alice" }));
+    let changes = differ.diff(Some(&before), Some(&moved));
+    assert_eq!(paths(&changes), vec!["/settings"]);
+    assert!(changes[0].force_new);
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct GroupSpec {
+    name: String,
+    #[serde(default)]
+    members: BTreeSet<String>,
+    #[serde(default = "default_visibility")]
+    visibility: String,
+}
+
+fn default_visibility() -> String {
+    "private".to_string()
+}
+
+#[test]
+fn test_planner_uses_schema_and_attribute_changes() {
+    let schema = serde_json::to_value(schemars::schema_for!(GroupSpec)).unwrap();
+    assert_eq!(set_attributes(&schema), vec!["/members"]);
+
+    let group = |spec| resource("group.admins", spec);
+    let current = vec![group(json!({
+        "name": "admins",
+        "members": ["bob", "alice"],
+        "visibility": "private",
+        "etag": "1",
+    }))];
+    let desired = vec![group(
+        json!({ "name": "admins", "members": ["alice", "bob"] }),
+    )];
+
+    let planner = Planner::new()
+        .schema("group", &schema)
+        .ignore("group", &attributes(&["/etag"]));
+    let changes = planner.plan(&desired, &current);
+    assert_eq!(changes[0].change_type, ChangeType::NoOp);
+    assert!(changes[0].attribute_changes.is_empty());
+
+    // Without the schema, the default, the member order and the etag all differ.
+    let changes = Planner::new().plan(&desired, &current);
+    assert_eq!(changes[0].change_type, ChangeType::Update);
+    assert_eq!(
+        paths(&changes[0].attribute_changes),
+        vec!["/etag", "/members", "/visibility"]
+    );
+
+    let delete = Planner::new().plan(&[], &current);
+    assert_eq!(delete[0].change_type, ChangeType::Delete);
+    assert_eq!(delete[0].attribute_changes.len(), 4);
+
+    // Changes serialized before attribute changes existed still deserialize.
+    let mut value = serde_json::to_value(&changes[0]).unwrap();
+    let _ = value.as_object_mut().unwrap().remove("attribute_changes");
+    let change: Change = serde_json::from_value(value).unwrap();
+    assert!(change.attribute_changes.is_empty());
+}
+
+#[test]
+fn test_paths_are_json_pointers() {
+    let before = user(
+        "alice",
+        json!({
+            "attributes": { "pkce.code.challenge.method": "plain", "a/b": 1 },
+        }),
+    );
+    let after = user(
+        "alice",
+        json!({
+            "attributes": { "pkce.code.challenge.method": "S256", "a/b": 2 },
+        }),
+    );
+
+    let differ = Differ::new().ignore(&attributes(&["/attributes/a~1b"]));
+    let changes = differ.diff(Some(&before), Some(&after));
+    assert_eq!(
+        paths(&changes),
+        vec!["/attributes/pkce.code.challenge.method"]
+    );
+
+    // A dotted path names a single key, not a nested one.
+    let differ = Differ::new().force_new(&attributes(&["/attributes/pkce"]));
+    assert!(!differ.diff(Some(&before), Some(&after))[1].force_new);
+
+    let rendered = render_plan(&Planner::new().plan(&[after], &[before]));
+    assert!(
+        rendered.contains("~ \"pkce.code.challenge.method\" = \"plain\" -> \"S256\""),
+        "{rendered}"
+    );
+}
@@ -0,0 +1,155 @@
+use async_trait::async_trait;
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::provider::{
+    suggest_name, DiscoverRequest, DiscoverResponse, DiscoveredResource,
+};
+use iamctl_rust_sdk::server::{JsonRpcRequest, RequestHandler};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Pretends to be a Keycloak realm with a handful of users.
+struct DiscoveringProvider;
+
+#[async_trait]
+impl Provider for DiscoveringProvider {
+    fn metadata(&self) -> ProviderMetadata {
+        ProviderMetadata {
+            name: "keycloak".to_string(),
+            version: "0.1.0".to_string(),
+            author: None,
+            repository: None,
+            description: None,
+        }
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            supported_resources: vec!["user".to_string()],
+            can_import: true,
+            can_validate: false,
+            can_refresh: false,
+            config_schema: None,
+            can_discover: true,
+            resource_types: vec![],
+            data_sources: vec![],
+        }
+    }
+
+    async fn plan(&self, _request: PlanRequest) -> anyhow::Result<PlanResponse> {
+        Ok(PlanResponse {
+            changes: vec![],
+            diagnostics: vec![],
+        })
+    }
+
+    async fn apply(&self, _request: ApplyRequest) -> anyhow::Result<ApplyResponse> {
+        Ok(ApplyResponse {
+            successful_addresses: vec![],
+            diagnostics: vec![],
+            results: vec![],
+        })
+    }
+
+    async fn discover(&self, request: DiscoverRequest) -> anyhow::Result<DiscoverResponse> {
+        anyhow::ensure!(
+            request.resource_type == "user",
+            "cannot discover {}",
+            request.resource_type
+        );
+        let realm = request.filter.get("realm").map(String::as_str);
+        let users = ["Alice Smith", "bob", "Carol", "dave@corp", "Eve"]
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| DiscoveredResource::new("user", realm, name, &format!("uuid-{idx}")))
+            .collect();
+        Ok(DiscoverResponse::paginate(users, &request)?)
+    }
+}
+
+fn request(page_token: Option<String>) -> DiscoverRequest {
+    DiscoverRequest {
+        resource_type: "user".to_string(),
+        filter: HashMap::from([("realm".to_string(), "demo".to_string())]),
+        page_size: Some(2),
+        page_token,
+    }
+}
+
+#[test]
+fn test_suggest_name() {
+    assert_eq!(suggest_name("Alice Smith"), "alice-smith");
+    assert_eq!(suggest_name("dave@corp.example"), "dave-corp-example");
+    assert_eq!(suggest_name("  --weird//name--  "), "weird-name");
+    assert_eq!(suggest_name("***"), "unnamed");
+}
+
+#[tokio::test]
+async fn test_discover_paginates_until_exhausted() {
+    let provider = DiscoveringProvider;
+    let mut token = None;
+    let mut all = vec![];
+    let mut pages = 0;
+
+    loop {
+        let page = provider.discover(request(token)).await.unwrap();
+        pages += 1;
+        assert!(page.resources.len() <= 2);
+        all.extend(page.resources);
+        token = page.next_page_token;
+        if token.is_none() {
+            break;
+        }
+    }
+
+    assert_eq!(pages, 3);
+    assert_eq!(all.len(), 5);
+    assert_eq!(all[0].address.name, "alice-smith");
+    assert_eq!(all[0].address.namespace.as_deref(), Some("demo"));
+    assert_eq!(all[0].display_name.as_deref(), Some("Alice Smith"));
+}
+
+#[tokio::test]
+async fn test_discovered_resources_feed_import() {
+    let page = DiscoveringProvider.discover(request(None)).await.unwrap();
+    let imports = page.to_import_requests();
+    assert_eq!(imports.len(), 2);
+    assert_eq!(imports[1].address.to_string(), "user.demo/bob");
+    assert_eq!(imports[1].id, "uuid-1");
+}
+
+#[test]
+fn test_paginate_rejects_bad_tokens() {
+    assert!(DiscoverResponse::paginate(vec![], &request(Some("abc".to_string()))).is_err());
+
+    let past_end = DiscoverResponse::paginate(vec![], &request(Some("10".to_string()))).unwrap();
+    assert!(past_end.resources.is_empty());
+    assert!(past_end.next_page_token.is_none());
+}
+
+#[tokio::test]
+async fn test_discover_over_json_rpc() {
+    let handler = RequestHandler::new(Arc::new(DiscoveringProvider));
+
+    let resp = handler
+        .handle(JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "discover".to_string(),
+            params: json!({ "resource_type": "user", "page_size": 10 }),
+            id: Some(json!(1)),
+        })
+        .await;
+    let result: DiscoverResponse = serde_json::from_value(resp.result.unwrap()).unwrap();
+    assert_eq!(result.resources.len(), 5);
+    assert!(result.next_page_token.is_none());
+
+    let resp = handler
+        .handle(JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "discover".to_string(),
+            params: json!({ "resource_type": "realm" }),
+            id: Some(json!(2)),
+        })
+        .await;
+    assert_eq!(resp.error.unwrap().code, -32000);
+}
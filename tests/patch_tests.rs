@@ -0,0 +1,239 @@
+mod common;
+
+use common::user;
+use iamctl_rust_sdk::plan::{Differ, Planner};
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::types::patch::{
+    apply_merge_patch, apply_patch, json_patch, merge_patch, pointer,
+};
+use iamctl_rust_sdk::types::PatchOperation;
+use iamctl_rust_sdk::utils::Error;
+use serde_json::json;
+
+#[test]
+fn test_planner_attaches_patch_to_updates() {
+    let mut current = vec![user(
+        "alice",
+        json!({
+            "id": "42",
+            "email": "alice@old",
+            "nickname": "al",
+            "settings": { "theme": "dark", "locale": "en" },
+        }),
+    )];
+    let _ = current[0]
+        .metadata
+        .insert("owner".to_string(), "ops".to_string());
+    let desired = vec![user(
+        "alice",
+        json!({
+            "email": "alice@new",
+            "settings": { "theme": "dark", "locale": "fr" },
+            "groups": ["admins"],
+        }),
+    )];
+
+    let planner = Planner::new().computed("user", &["/id".to_string()]);
+    let changes = planner.plan(&desired, &current);
+    assert_eq!(changes[0].change_type, ChangeType::Update);
+
+    let patch = changes[0].patch.clone().unwrap();
+    assert_eq!(
+        serde_json::to_value(&patch).unwrap(),
+        json!([
+            { "op": "replace", "path": "/email", "value": "alice@new" },
+            { "op": "add", "path": "/groups", "value": ["admins"] },
+            { "op": "remove", "path": "/nickname" },
+            { "op": "replace", "path": "/settings/locale", "value": "fr" },
+        ])
+    );
+    assert_eq!(
+        changes[0].merge_patch(),
+        Some(json!({
+            "email": "alice@new",
+            "groups": ["admins"],
+            "nickname": null,
+            "settings": { "locale": "fr" },
+        }))
+    );
+
+    // Both patches turn the current spec into the desired one, keeping computed values.
+    let patched = apply_patch(&current[0], &patch).unwrap();
+    let merged = apply_merge_patch(&current[0], &changes[0].merge_patch().unwrap()).unwrap();
+    assert_eq!(patched, merged);
+    assert_eq!(patched.spec["id"], "42");
+    assert_eq!(patched.metadata, current[0].metadata);
+    assert!(Differ::new()
+        .computed(&["/id".to_string()])
+        .diff(Some(&patched), Some(&desired[0]))
+        .is_empty());
+
+    // Only updates carry a patch.
+    let created = planner.plan(&desired, &[]);
+    assert_eq!(created[0].patch, None);
+    assert_eq!(created[0].merge_patch(), None);
+    let unchanged = planner.plan(&current, &current);
+    assert_eq!(unchanged[0].patch, None);
+}
+
+#[test]
+fn test_patch_creates_missing_parents() {
+    let before = user(
+        "alice",
+        json!({ "email": "alice@example.com", "profile": null }),
+    );
+    let differ = Differ::new().default_value("/settings/theme", json!("light"));
+    let after = user(
+        "alice",
+        json!({
+            "email": "alice@example.com",
+            "profile": { "title": "Engineer" },
+            "settings": { "theme": "dark", "locale": "fr" },
+        }),
+    );
+
+    let changes = differ.diff(Some(&before), Some(&after));
+    let patch = json_patch(&before, &changes);
+    assert_eq!(
+        patch,
+        vec![
+            PatchOperation::Replace {
+                path: "/profile".to_string(),
+                value: json!({ "title": "Engineer" }),
+            },
+            PatchOperation::Add {
+                path: "/settings".to_string(),
+                value: json!({ "locale": "fr" }),
+            },
+            PatchOperation::Add {
+                path: "/settings/theme".to_string(),
+                value: json!("dark"),
+            },
+        ]
+    );
+    assert_eq!(apply_patch(&before, &patch).unwrap().spec, after.spec);
+}
+
+#[test]
+fn test_patch_keeps_keys_with_dots_and_slashes() {
+    let current = vec![user(
+        "alice",
+        json!({
+            "attributes": { "pkce.code.challenge.method": "plain" },
+        }),
+    )];
+    let desired = vec![user(
+        "alice",
+        json!({
+            "attributes": { "pkce.code.challenge.method": "S256", "post.logout/uris": "+" },
+        }),
+    )];
+
+    let changes = Planner::new().plan(&desired, &current);
+    let patch = changes[0].patch.clone().unwrap();
+    assert_eq!(
+        patch,
+        vec![
+            PatchOperation::Replace {
+                path: "/attributes/pkce.code.challenge.method".to_string(),
+                value: json!("S256"),
+            },
+            PatchOperation::Add {
+                path: "/attributes/post.logout~1uris".to_string(),
+                value: json!("+"),
+            },
+        ]
+    );
+    assert_eq!(
+        apply_patch(&current[0], &patch).unwrap().spec,
+        desired[0].spec
+    );
+    assert_eq!(
+        changes[0].merge_patch(),
+        Some(json!({
+            "attributes": { "pkce.code.challenge.method": "S256", "post.logout/uris": "+" },
+        }))
+    );
+    assert_eq!(
+        pointer(&["attributes", "post.logout/uris"]),
+        "/attributes/post.logout~1uris"
+    );
+}
+
+#[test]
+fn test_apply_patch_operations() {
+    let resource = user(
+        "alice",
+        json!({
+            "roles": ["a", "c"],
+            "settings": { "theme": "dark" },
+            "a/b": 1,
+        }),
+    );
+    let patch: Vec<PatchOperation> = serde_json::from_value(json!([
+        { "op": "test", "path": "/settings/theme", "value": "dark" },
+        { "op": "add", "path": "/roles/1", "value": "b" },
+        { "op": "add", "path": "/roles/-", "value": "d" },
+        { "op": "copy", "from": "/settings", "path": "/defaults" },
+        { "op": "move", "from": "/a~1b", "path": "/count" },
+        { "op": "remove", "path": "/roles/0" },
+    ]))
+    .unwrap();
+    let patched = apply_patch(&resource, &patch).unwrap();
+    assert_eq!(
+        serde_json::to_value(&patched.spec).unwrap(),
+        json!({
+            "roles": ["b", "c", "d"],
+            "settings": { "theme": "dark" },
+            "defaults": { "theme": "dark" },
+            "count": 1,
+        })
+    );
+    // Patches from other operations have no merge patch view.
+    assert_eq!(merge_patch(&patch), None);
+
+    let failing: Vec<PatchOperation> = serde_json::from_value(json!([
+        { "op": "replace", "path": "/settings/theme", "value": "light" },
+        { "op": "test", "path": "/settings/theme", "value": "dark" },
+    ]))
+    .unwrap();
+    match apply_patch(&resource, &failing) {
+        Err(Error::InvalidPatch { path, reason }) => {
+            assert_eq!(path, "/settings/theme");
+            assert!(reason.contains("expected \"dark\""), "{reason}");
+        }
+        other => panic!("expected an invalid patch error, got {other:?}"),
+    }
+
+    for operation in [
+        json!({ "op": "remove", "path": "/missing" }),
+        json!({ "op": "replace", "path": "/missing/x", "value": 1 }),
+        json!({ "op": "add", "path": "/roles/5", "value": "x" }),
+        json!({ "op": "add", "path": "/roles/01", "value": "x" }),
+        json!({ "op": "move", "from": "/settings", "path": "/settings/inner" }),
+        json!({ "op": "remove", "path": "" }),
+        json!({ "op": "add", "path": "", "value": [] }),
+    ] {
+        let patch = vec![serde_json::from_value(operation.clone()).unwrap()];
+        assert!(
+            matches!(
+                apply_patch(&resource, &patch),
+                Err(Error::InvalidPatch { .. })
+            ),
+            "{operation} should fail"
+        );
+    }
+}
+
+#[test]
+fn test_change_without_patch_deserializes() {
+    let change: Change = serde_json::from_value(json!({
+        "address": { "resource_type": "user", "name": "alice" },
+        "change_type": "Update",
+        "before": null,
+        "after": null,
+    }))
+    .unwrap();
+    assert_eq!(change.patch, None);
+    assert!(change.attribute_changes.is_empty());
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use common::user;
+use iamctl_rust_sdk::plan::file::{state_hash, PLAN_FILE_FORMAT_VERSION};
+use iamctl_rust_sdk::plan::{plan_changes, PlanFile};
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::utils::Error;
+use serde_json::json;
+
+fn provider(version: &str) -> ProviderMetadata {
+    ProviderMetadata {
+        name: "users".to_string(),
+        version: version.to_string(),
+        author: None,
+        repository: None,
+        description: None,
+    }
+}
+
+fn state(resources: &[Resource]) -> State {
+    let mut state = State::default();
+    for resource in resources {
+        let _ = state
+            .resources
+            .insert(resource.address.to_string(), resource.clone());
+    }
+    state
+}
+
+fn plan_file(state: &State) -> PlanFile {
+    let current: Vec<_> = state.resources.values().cloned().collect();
+    let desired = vec![
+        user("alice", json!({ "email": "alice@new" })),
+        user("bob", json!({ "email": "bob@example.com" })),
+    ];
+    let plan = PlanResponse {
+        changes: plan_changes(&desired, &current),
+        diagnostics: vec![],
+    };
+    PlanFile::new(&provider("1.2.0"), state, plan).unwrap()
+}
+
+#[tokio::test]
+async fn test_saved_plan_round_trips_and_applies() {
+    let state = state(&[user("alice", json!({ "email": "alice@old" }))]);
+    let file = plan_file(&state);
+    assert_eq!(file.format_version, PLAN_FILE_FORMAT_VERSION);
+    assert_eq!(file.provider_name, "users");
+    assert_eq!(file.state_hash, state_hash(&state).unwrap());
+    assert_eq!(file.checksum.len(), 64);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("plan.json");
+    file.save(&path).await.unwrap();
+    let loaded = PlanFile::load(&path).await.unwrap();
+    assert_eq!(loaded.checksum, file.checksum);
+    assert_eq!(loaded.created_at, file.created_at);
+
+    let request = loaded.apply_request(&provider("1.2.0"), &state).unwrap();
+    assert_eq!(request.changes.len(), 2);
+    assert_eq!(request.changes[0].change_type, ChangeType::Update);
+    assert_eq!(request.changes[1].change_type, ChangeType::Create);
+}
+
+#[tokio::test]
+async fn test_modified_plan_is_rejected() {
+    let state = state(&[user("alice", json!({ "email": "alice@old" }))]);
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("plan.json");
+    plan_file(&state).save(&path).await.unwrap();
+
+    let tampered = std::fs::read_to_string(&path)
+        .unwrap()
+        .replace("bob@example.com", "mallory@example.com");
+    std::fs::write(&path, tampered).unwrap();
+    match PlanFile::load(&path).await {
+        Err(Error::InvalidPlanFile(reason)) => assert!(reason.contains("checksum"), "{reason}"),
+        other => panic!("expected an invalid plan file, got {other:?}"),
+    }
+
+    let mut edited = plan_file(&state);
+    edited.plan.changes.pop();
+    assert!(matches!(
+        edited.apply_request(&provider("1.2.0"), &state),
+        Err(Error::InvalidPlanFile(_))
+    ));
+
+    let mut future = plan_file(&state);
+    future.format_version = PLAN_FILE_FORMAT_VERSION + 1;
+    future.checksum = future.compute_checksum().unwrap();
+    match future.verify_integrity() {
+        Err(Error::InvalidPlanFile(reason)) => assert!(reason.contains("format version")),
+        other => panic!("expected an unsupported format, got {other:?}"),
+    }
+
+    std::fs::write(&path, "{ not json").unwrap();
+    assert!(matches!(
+        PlanFile::load(&path).await,
+        Err(Error::InvalidPlanFile(_))
+    ));
+}
+
+#[test]
+fn test_stale_plan_and_other_provider_are_rejected() {
+    let base = state(&[
+        user("alice", json!({ "email": "alice@old" })),
+        user("carol", json!({ "email": "carol@example.com" })),
+    ]);
+    let file = plan_file(&base);
+
+    // The hash does not depend on map order, only on content.
+    let reordered = state(&[
+        user("carol", json!({ "email": "carol@example.com" })),
+        user("alice", json!({ "email": "alice@old" })),
+    ]);
+    assert!(file.verify(&provider("1.2.0"), &reordered).is_ok());
+
+    let changed = state(&[
+        user("alice", json!({ "email": "alice@changed" })),
+        user("carol", json!({ "email": "carol@example.com" })),
+    ]);
+    match file.apply_request(&provider("1.2.0"), &changed) {
+        Err(Error::StalePlan { planned, current }) => {
+            assert_eq!(planned, file.state_hash);
+            assert_eq!(current, state_hash(&changed).unwrap());
+        }
+        other => panic!("expected a stale plan, got {other:?}"),
+    }
+
+    match file.verify(&provider("1.3.0"), &base) {
+        Err(Error::InvalidPlanFile(reason)) => {
+            assert!(reason.contains("users 1.2.0, not users 1.3.0"), "{reason}")
+        }
+        other => panic!("expected a provider mismatch, got {other:?}"),
+    }
+}
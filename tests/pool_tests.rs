@@ -0,0 +1,212 @@
+mod common;
+
+use common::resource;
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::provider::{ApplyResult, ApplyStatus};
+use iamctl_rust_sdk::server::{
+    Cassette, JsonRpcClient, JsonRpcRequest, JsonRpcResponse, ProviderPool,
+};
+use serde_json::json;
+
+fn request(method: &str, params: serde_json::Value) -> JsonRpcRequest {
+    JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        method: method.to_string(),
+        params,
+        id: Some(json!(1)),
+    }
+}
+
+fn capabilities(resources: &[&str]) -> serde_json::Value {
+    json!({
+        "supported_resources": resources,
+        "can_import": false,
+        "can_validate": false
+    })
+}
+
+fn create_change(resource: &Resource) -> Change {
+    Change {
+        address: resource.address.clone(),
+        change_type: ChangeType::Create,
+        before: None,
+        after: Some(resource.clone()),
+        computed_fields: vec![],
+        replace_order: None,
+        attribute_changes: vec![],
+        patch: None,
+    }
+}
+
+fn provider_cassette(resources: &[&str], desired: &[Resource]) -> Cassette {
+    let mut cassette = Cassette::default();
+    cassette.push(
+        request("capabilities", json!(null)),
+        JsonRpcResponse::success(json!(1), capabilities(resources)),
+    );
+    let plan = PlanRequest {
+        workspace_path: ".".to_string(),
+        desired_state: desired.to_vec(),
+        current_state: vec![],
+        data_sources: vec![],
+    };
+    let changes: Vec<Change> = desired.iter().map(create_change).collect();
+    cassette.push(
+        request("plan", serde_json::to_value(plan).unwrap()),
+        JsonRpcResponse::success(json!(1), json!({ "changes": changes })),
+    );
+    cassette.push(
+        request("apply", json!({ "changes": changes })),
+        JsonRpcResponse::success(
+            json!(1),
+            json!({
+                "successful_addresses": desired.iter().map(|r| &r.address).collect::<Vec<_>>(),
+                "diagnostics": []
+            }),
+        ),
+    );
+    cassette
+}
+
+#[tokio::test]
+async fn test_pool_routes_by_resource_type() {
+    let user = resource("user.alice", json!({ "name": "alice" }));
+    let role = resource("role.admin", json!({ "name": "admin" }));
+
+    let mut pool = ProviderPool::new();
+    pool.register(
+        "keycloak",
+        JsonRpcClient::from_cassette(provider_cassette(&["user"], std::slice::from_ref(&user))),
+    )
+    .await
+    .unwrap();
+    pool.register(
+        "aws",
+        JsonRpcClient::from_cassette(provider_cassette(&["role"], std::slice::from_ref(&role))),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(pool.provider_names(), vec!["aws", "keycloak"]);
+    assert_eq!(pool.provider_for("user"), Some("keycloak"));
+    assert_eq!(pool.provider_for("role"), Some("aws"));
+    assert_eq!(pool.provider_for("group"), None);
+
+    let plan = pool
+        .plan(PlanRequest {
+            workspace_path: ".".to_string(),
+            desired_state: vec![user.clone(), role.clone()],
+            current_state: vec![],
+            data_sources: vec![],
+        })
+        .await
+        .unwrap();
+    assert_eq!(plan.changes.len(), 2);
+
+    let applied = pool
+        .apply(ApplyRequest {
+            changes: plan.changes,
+        })
+        .await
+        .unwrap();
+    assert_eq!(applied.successful_addresses.len(), 2);
+    assert!(applied.successful_addresses.contains(&user.address));
+    assert!(applied.successful_addresses.contains(&role.address));
+
+    pool.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_pool_rejects_conflicts_and_unknown_types() {
+    let mut pool = ProviderPool::new();
+    pool.register(
+        "keycloak",
+        JsonRpcClient::from_cassette(provider_cassette(&["user"], &[])),
+    )
+    .await
+    .unwrap();
+
+    let duplicate_type = pool
+        .register(
+            "other",
+            JsonRpcClient::from_cassette(provider_cassette(&["user"], &[])),
+        )
+        .await;
+    assert!(duplicate_type.is_err());
+    assert_eq!(pool.provider_names(), vec!["keycloak"]);
+
+    let unknown = pool
+        .plan(PlanRequest {
+            workspace_path: ".".to_string(),
+            desired_state: vec![resource("group.devs", json!({ "name": "devs" }))],
+            current_state: vec![],
+            data_sources: vec![],
+        })
+        .await;
+    assert!(unknown.unwrap_err().to_string().contains("group"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_client_shutdown_waits_for_process() {
+    // `cat` exits as soon as its stdin is closed.
+    let client = JsonRpcClient::launch("cat").unwrap();
+    client.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_pool_applies_changes_in_order() {
+    let alice = resource("user.alice", json!({ "name": "alice" }));
+    let admin = resource("role.admin", json!({ "name": "admin" }));
+    let bob = resource("user.bob", json!({ "name": "bob" }));
+
+    let mut keycloak = provider_cassette(&["user"], &[]);
+    for user in [&alice, &bob] {
+        let result = ApplyResult::succeeded(user.address.clone(), Some(user.clone()));
+        keycloak.push(
+            request("apply", json!({ "changes": [create_change(user)] })),
+            JsonRpcResponse::success(
+                json!(1),
+                serde_json::to_value(ApplyResponse::from_results(vec![result])).unwrap(),
+            ),
+        );
+    }
+    let mut pool = ProviderPool::new();
+    pool.register("keycloak", JsonRpcClient::from_cassette(keycloak))
+        .await
+        .unwrap();
+    // The role provider has no recorded apply, so its call fails.
+    pool.register(
+        "aws",
+        JsonRpcClient::from_cassette(provider_cassette(&["role"], &[])),
+    )
+    .await
+    .unwrap();
+
+    let changes = [&alice, &admin, &bob].map(create_change).to_vec();
+    let applied = pool.apply(ApplyRequest { changes }).await.unwrap();
+    let outcomes: Vec<_> = applied
+        .results
+        .iter()
+        .map(|r| (r.address.name.as_str(), r.status))
+        .collect();
+    assert_eq!(
+        outcomes,
+        [
+            ("alice", ApplyStatus::Succeeded),
+            ("admin", ApplyStatus::Failed),
+            ("bob", ApplyStatus::Succeeded),
+        ]
+    );
+    assert_eq!(
+        applied.successful_addresses,
+        vec![alice.address, bob.address]
+    );
+    assert_eq!(applied.failed_addresses(), vec![&admin.address]);
+    assert!(applied.results[1]
+        .error
+        .as_ref()
+        .unwrap()
+        .message
+        .starts_with("Provider 'aws' failed to apply"));
+}
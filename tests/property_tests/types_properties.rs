@@ -1,4 +1,6 @@
 use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::provider::DataSource;
+use iamctl_rust_sdk::state::FileBackend;
 use proptest::prelude::*;
 use serde_json::json;
 use std::collections::HashMap;
@@ -24,6 +26,22 @@ proptest! {
         assert_eq!(deserialized.namespace, namespace);
     }
 
+    #[test]
+    fn test_resource_address_string_round_trip(
+        resource_type in "[a-z./\\\\-]{1,8}",
+        name in "[a-z./\\\\ -]{1,8}",
+        namespace in prop::option::of("[a-z./\\\\-]{1,8}")
+    ) {
+        let address = ResourceAddress {
+            resource_type,
+            name,
+            namespace,
+        };
+
+        let parsed: ResourceAddress = address.to_string().parse().unwrap();
+        assert_eq!(parsed, address);
+    }
+
     #[test]
     fn test_change_type_properties(change_type_idx in 0..3u8) {
         let change_type = match change_type_idx {
@@ -59,3 +77,93 @@ proptest! {
         assert!(serialized.is_object());
     }
 }
+
+fn resource_from(entries: &[(String, String)]) -> Resource {
+    Resource {
+        address: ResourceAddress {
+            resource_type: "user".to_string(),
+            name: "alice".to_string(),
+            namespace: None,
+        },
+        spec: entries
+            .iter()
+            .map(|(key, value)| (key.clone(), json!({ "value": value, "key": key })))
+            .collect(),
+        metadata: entries.iter().cloned().collect(),
+    }
+}
+
+fn state_from(entries: &[(String, String)]) -> State {
+    let mut state = State::default();
+    for (key, value) in entries {
+        let _ = state.metadata.insert(key.clone(), value.clone());
+        let mut resource = resource_from(entries);
+        resource.address.name = key.clone();
+        let _ = state.resources.insert(format!("user.{key}"), resource);
+    }
+    state
+}
+
+fn shuffled(entries: &[(String, String)]) -> Vec<(String, String)> {
+    let mut reversed = entries.to_vec();
+    reversed.reverse();
+    reversed
+}
+
+proptest! {
+    #[test]
+    fn test_resource_serialization_is_deterministic(
+        entries in prop::collection::hash_map("[a-z]{1,6}", "[a-z0-9]{0,4}", 0..16)
+    ) {
+        let entries: Vec<_> = entries.into_iter().collect();
+        let first = serde_json::to_string(&resource_from(&entries)).unwrap();
+        let second = serde_json::to_string(&resource_from(&shuffled(&entries))).unwrap();
+        prop_assert_eq!(&first, &second);
+
+        // Keys are written in sorted order.
+        let mut keys: Vec<_> = entries.iter().map(|(key, _)| key.clone()).collect();
+        keys.sort();
+        let positions: Vec<_> = keys
+            .iter()
+            .map(|key| first.find(&format!("\"{key}\":{{")).unwrap())
+            .collect();
+        prop_assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_state_file_is_byte_stable(
+        entries in prop::collection::hash_map("[a-z]{1,6}", "[a-z0-9]{0,4}", 0..12)
+    ) {
+        let entries: Vec<_> = entries.into_iter().collect();
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first.json");
+        let second = dir.path().join("second.json");
+
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        runtime.block_on(async {
+            FileBackend::new(&first).save(&state_from(&entries)).await.unwrap();
+            FileBackend::new(&second).save(&state_from(&shuffled(&entries))).await.unwrap();
+        });
+        prop_assert_eq!(std::fs::read(&first).unwrap(), std::fs::read(&second).unwrap());
+    }
+
+    #[test]
+    fn test_plan_request_payload_is_deterministic(
+        entries in prop::collection::hash_map("[a-z]{1,6}", "[a-z0-9]{0,4}", 0..12)
+    ) {
+        let entries: Vec<_> = entries.into_iter().collect();
+        let request = |entries: &[(String, String)]| PlanRequest {
+            workspace_path: ".".to_string(),
+            desired_state: vec![resource_from(entries)],
+            current_state: vec![],
+            data_sources: vec![DataSource {
+                address: resource_from(entries).address,
+                values: entries.iter().map(|(key, value)| (key.clone(), json!(value))).collect(),
+            }],
+        };
+        prop_assert_eq!(
+            serde_json::to_vec(&request(&entries)).unwrap(),
+            serde_json::to_vec(&request(&shuffled(&entries))).unwrap()
+        );
+    }
+}
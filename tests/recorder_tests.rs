@@ -0,0 +1,198 @@
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::server::recorder::{Direction, RecordedMessage};
+use iamctl_rust_sdk::server::{Cassette, JsonRpcClient, JsonRpcRequest, JsonRpcResponse, Recorder};
+use serde_json::json;
+use tempfile::tempdir;
+
+fn request(method: &str, params: serde_json::Value) -> JsonRpcRequest {
+    JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        method: method.to_string(),
+        params,
+        id: Some(json!(7)),
+    }
+}
+
+#[tokio::test]
+async fn test_recorder_writes_jsonl_transcript() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("transcript.jsonl");
+    let recorder = Recorder::create(&path).unwrap();
+
+    recorder
+        .record_request(&request("metadata", json!({})))
+        .await
+        .unwrap();
+    recorder
+        .record_response(&JsonRpcResponse::success(json!(7), json!({"name": "p"})))
+        .await
+        .unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<RecordedMessage> = content
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0].direction, Direction::Request);
+    assert_eq!(lines[0].message["method"], "metadata");
+    assert_eq!(lines[1].direction, Direction::Response);
+    assert!(lines[0].timestamp <= lines[1].timestamp);
+}
+
+#[tokio::test]
+async fn test_replay_serves_recorded_responses() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("cassette.jsonl");
+    let recorder = Recorder::create(&path).unwrap();
+
+    let metadata = json!({
+        "name": "recorded-provider",
+        "version": "1.2.3",
+        "author": null,
+        "repository": null,
+        "description": null
+    });
+    recorder
+        .record_request(&request("metadata", json!(null)))
+        .await
+        .unwrap();
+    recorder
+        .record_response(&JsonRpcResponse::success(json!(7), metadata))
+        .await
+        .unwrap();
+
+    let mut client = JsonRpcClient::replay(&path).await.unwrap();
+    let result: ProviderMetadata = client.call("metadata", ()).await.unwrap();
+    assert_eq!(result.name, "recorded-provider");
+    assert_eq!(result.version, "1.2.3");
+
+    // Each interaction is served once.
+    let again: iamctl_rust_sdk::utils::Result<ProviderMetadata> = client.call("metadata", ()).await;
+    assert!(again.is_err());
+}
+
+#[tokio::test]
+async fn test_replay_matches_on_params() {
+    let mut cassette = Cassette::default();
+    cassette.push(
+        request("import", json!({"id": "a"})),
+        JsonRpcResponse::success(json!(1), json!("first")),
+    );
+    cassette.push(
+        request("import", json!({"id": "b"})),
+        JsonRpcResponse::success(json!(2), json!("second")),
+    );
+
+    let mut client = JsonRpcClient::from_cassette(cassette);
+    let second: String = client.call("import", json!({"id": "b"})).await.unwrap();
+    assert_eq!(second, "second");
+    let first: String = client.call("import", json!({"id": "a"})).await.unwrap();
+    assert_eq!(first, "first");
+}
+
+#[tokio::test]
+async fn test_replay_propagates_recorded_errors_and_records_client_side() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("client.jsonl");
+
+    let mut cassette = Cassette::default();
+    cassette.push(
+        request("plan", json!({})),
+        JsonRpcResponse::error(json!(1), -32000, "Provider error: boom".to_string()),
+    );
+
+    let mut client =
+        JsonRpcClient::from_cassette(cassette).with_recorder(Recorder::create(&path).unwrap());
+    let result: iamctl_rust_sdk::utils::Result<PlanResponse> = client.call("plan", json!({})).await;
+    assert!(result.unwrap_err().to_string().contains("boom"));
+
+    let replayed = Cassette::load(&path).await.unwrap();
+    assert_eq!(replayed.len(), 1);
+}
+
+#[test]
+fn test_cassette_pairs_pipelined_requests_by_id() {
+    let message = |direction, message: serde_json::Value| RecordedMessage {
+        timestamp: chrono::Utc::now(),
+        direction,
+        message,
+    };
+    let first = request("import", json!({ "id": "a" }));
+    let mut second = request("import", json!({ "id": "b" }));
+    second.id = Some(json!(8));
+    let cancel = JsonRpcRequest {
+        id: None,
+        ..request("$/cancelRequest", json!({ "id": 3 }))
+    };
+    // Both requests were read before either was answered.
+    let messages = vec![
+        message(Direction::Request, serde_json::to_value(&first).unwrap()),
+        message(Direction::Request, serde_json::to_value(&second).unwrap()),
+        message(Direction::Request, serde_json::to_value(&cancel).unwrap()),
+        message(
+            Direction::Response,
+            serde_json::to_value(JsonRpcResponse::success(json!(7), json!("first"))).unwrap(),
+        ),
+        message(
+            Direction::Response,
+            serde_json::to_value(JsonRpcResponse::success(json!(8), json!("second"))).unwrap(),
+        ),
+    ];
+
+    let mut cassette = Cassette::from_messages(messages).unwrap();
+    assert_eq!(cassette.len(), 2);
+    assert_eq!(
+        cassette.respond(&second).unwrap().result,
+        Some(json!("second"))
+    );
+    assert_eq!(
+        cassette.respond(&first).unwrap().result,
+        Some(json!("first"))
+    );
+}
+
+#[tokio::test]
+async fn test_recorders_of_one_file_share_it() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("pool.jsonl");
+
+    let mut clients: Vec<JsonRpcClient> = ["a", "b", "c"]
+        .into_iter()
+        .map(|name| {
+            let mut cassette = Cassette::default();
+            for _ in 0..20 {
+                cassette.push(
+                    request("metadata", json!({ "provider": name })),
+                    JsonRpcResponse::success(json!(1), json!(name)),
+                );
+            }
+            JsonRpcClient::from_cassette(cassette).with_recorder(Recorder::create(&path).unwrap())
+        })
+        .collect();
+    let calls = clients
+        .iter_mut()
+        .zip(["a", "b", "c"])
+        .map(|(client, name)| async move {
+            for _ in 0..20 {
+                let answer: String = client
+                    .call("metadata", json!({ "provider": name }))
+                    .await
+                    .unwrap();
+                assert_eq!(answer, name);
+            }
+        });
+    let _ = futures::future::join_all(calls).await;
+
+    // Every line is whole and every response pairs with its own client's request.
+    let mut replay = Cassette::load(&path).await.unwrap();
+    assert_eq!(replay.len(), 60);
+    for name in ["a", "b", "c"] {
+        for _ in 0..20 {
+            let response = replay
+                .respond(&request("metadata", json!({ "provider": name })))
+                .unwrap();
+            assert_eq!(response.result, Some(json!(name)));
+        }
+    }
+}
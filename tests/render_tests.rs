@@ -0,0 +1,139 @@
+mod common;
+
+use common::resource;
+use iamctl_rust_sdk::plan::{render_plan, PlanRenderer, Planner};
+use iamctl_rust_sdk::prelude::*;
+use serde_json::json;
+
+fn attributes(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+fn planned_changes() -> Vec<Change> {
+    let current = vec![
+        resource(
+            "user.bob",
+            json!({
+                "id": "u-2",
+                "email": "bob@old.example.com",
+                "password": "hunter2",
+                "settings": { "theme": "dark", "locale": "en" },
+                "groups": ["devs"],
+            }),
+        ),
+        resource(
+            "client.demo/app",
+            json!({ "id": "c-1", "client_id": "app", "redirect_uris": ["https://app"] }),
+        ),
+        resource(
+            "group.legacy",
+            json!({ "name": "legacy", "members": ["carol"] }),
+        ),
+        resource("group.admins", json!({ "name": "admins" })),
+    ];
+    let desired = vec![
+        resource(
+            "user.alice",
+            json!({
+                "email": "alice@example.com",
+                "password": "s3cret",
+                "settings": { "theme": "light", "notifications": true },
+            }),
+        ),
+        resource(
+            "user.bob",
+            json!({
+                "email": "bob@new.example.com",
+                "password": "hunter3",
+                "settings": { "theme": "dark", "locale": "fr" },
+                "groups": ["devs", "ops"],
+            }),
+        ),
+        resource(
+            "client.demo/app",
+            json!({ "client_id": "app-v2", "redirect_uris": ["https://app"] }),
+        ),
+        resource("group.admins", json!({ "name": "admins" })),
+    ];
+
+    Planner::new()
+        .computed("user", &attributes(&["/id"]))
+        .computed("client", &attributes(&["/id"]))
+        .force_new("client", &attributes(&["/client_id"]))
+        .plan(&desired, &current)
+}
+
+#[test]
+fn test_render_plan() {
+    let rendered = PlanRenderer::new()
+        .sensitive("user", &attributes(&["/password"]))
+        .render(&planned_changes());
+    insta::assert_snapshot!(rendered);
+}
+
+#[test]
+fn test_render_plan_with_color() {
+    let changes: Vec<_> = planned_changes()
+        .into_iter()
+        .filter(|c| c.address.resource_type == "client")
+        .collect();
+    let rendered = PlanRenderer::new().color(true).render(&changes);
+    assert!(rendered.contains("\x1b[31m-\x1b[0m/\x1b[32m+\x1b[0m client.demo/app {"));
+    insta::assert_snapshot!(rendered);
+}
+
+#[test]
+fn test_render_sensitive_objects_and_external_changes() {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "credentials": { "type": "object", "x-iamctl-sensitive": true },
+        },
+    });
+    // A change built by hand, without attribute changes, is diffed for display.
+    let update = Change {
+        address: "user.carol".parse().unwrap(),
+        change_type: ChangeType::Update,
+        before: Some(resource(
+            "user.carol",
+            json!({ "credentials": { "key": "a" }, "enabled": true }),
+        )),
+        after: Some(resource(
+            "user.carol",
+            json!({ "credentials": { "key": "b" }, "enabled": false }),
+        )),
+        computed_fields: vec![],
+        replace_order: None,
+        attribute_changes: vec![],
+        patch: None,
+    };
+    let create = Change {
+        address: "user.dave".parse().unwrap(),
+        change_type: ChangeType::Create,
+        before: None,
+        after: Some(resource(
+            "user.dave",
+            json!({ "credentials": { "key": "c" }, "profile": { "title": "Engineer", "team": "IAM" } }),
+        )),
+        computed_fields: vec![],
+        replace_order: None,
+        attribute_changes: vec![],
+        patch: None,
+    };
+
+    let rendered = PlanRenderer::new()
+        .schema("user", &schema)
+        .render(&[update, create]);
+    assert!(!rendered.contains("\"a\"") && !rendered.contains("\"c\""));
+    insta::assert_snapshot!(rendered);
+}
+
+#[test]
+fn test_render_no_changes() {
+    let unchanged = vec![resource("group.admins", json!({ "name": "admins" }))];
+    let changes = Planner::new().plan(&unchanged, &unchanged);
+    assert_eq!(
+        render_plan(&changes),
+        "No changes. The current state matches the desired state.\n"
+    );
+}
@@ -0,0 +1,255 @@
+mod common;
+
+use async_trait::async_trait;
+use common::resource;
+use iamctl_rust_sdk::plan::{force_new_attributes, Planner, FORCE_NEW_KEY};
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::provider::{ApplyStatus, HandlerProvider};
+use iamctl_rust_sdk::types::ReplaceOrder;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+struct ClientSpec {
+    #[schemars(schema_with = "iamctl_rust_sdk::plan::force_new::<String>")]
+    client_id: String,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// Records the operations applied, in order, failing the one named in `fail`.
+#[derive(Clone, Default)]
+struct ClientHandler {
+    log: Arc<Mutex<Vec<String>>>,
+    fail: Option<&'static str>,
+}
+
+#[async_trait]
+impl ResourceHandler for ClientHandler {
+    type Spec = ClientSpec;
+
+    async fn create(
+        &self,
+        _address: &ResourceAddress,
+        spec: ClientSpec,
+    ) -> anyhow::Result<ClientSpec> {
+        if self.fail == Some("create") {
+            anyhow::bail!("quota exceeded");
+        }
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("create {}", spec.client_id));
+        Ok(spec)
+    }
+
+    async fn read(
+        &self,
+        _address: &ResourceAddress,
+        spec: ClientSpec,
+    ) -> anyhow::Result<Option<ClientSpec>> {
+        Ok(Some(spec))
+    }
+
+    async fn update(
+        &self,
+        _address: &ResourceAddress,
+        _before: ClientSpec,
+        after: ClientSpec,
+    ) -> anyhow::Result<ClientSpec> {
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("update {}", after.client_id));
+        Ok(after)
+    }
+
+    async fn delete(&self, _address: &ResourceAddress, spec: ClientSpec) -> anyhow::Result<()> {
+        if self.fail == Some("delete") {
+            anyhow::bail!("client is in use");
+        }
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("delete {}", spec.client_id));
+        Ok(())
+    }
+}
+
+fn client(client_id: &str, description: &str) -> Resource {
+    resource(
+        "client.demo/app",
+        json!({ "client_id": client_id, "description": description }),
+    )
+}
+
+fn provider(handler: ClientHandler) -> HandlerProvider {
+    ProviderBuilder::new(ProviderMetadata {
+        name: "clients".to_string(),
+        version: "0.1.0".to_string(),
+        author: None,
+        repository: None,
+        description: None,
+    })
+    .resource("client", handler)
+    .build()
+}
+
+#[test]
+fn test_force_new_attributes_from_schema() {
+    let schema = serde_json::to_value(schemars::schema_for!(ClientSpec)).unwrap();
+    assert_eq!(schema["properties"]["client_id"][FORCE_NEW_KEY], true);
+    assert_eq!(
+        force_new_attributes(&schema),
+        vec!["/client_id".to_string()]
+    );
+
+    let nested = json!({
+        "properties": { "credentials": { "$ref": "#/definitions/Credentials" } },
+        "definitions": {
+            "Credentials": {
+                "properties": { "kind": { "type": "string", FORCE_NEW_KEY: true } }
+            }
+        }
+    });
+    assert_eq!(
+        force_new_attributes(&nested),
+        vec!["/credentials/kind".to_string()]
+    );
+}
+
+#[test]
+fn test_planner_emits_replace_for_force_new_changes() {
+    let planner = Planner::new()
+        .force_new("client", &["/client_id".to_string()])
+        .replace_order("client", ReplaceOrder::CreateBeforeDestroy);
+
+    let changes = planner.plan(&[client("app-v2", "app")], &[client("app", "app")]);
+    assert_eq!(changes[0].change_type, ChangeType::Replace);
+    assert_eq!(
+        changes[0].replace_order,
+        Some(ReplaceOrder::CreateBeforeDestroy)
+    );
+
+    let changes = planner.plan(&[client("app", "new")], &[client("app", "old")]);
+    assert_eq!(changes[0].change_type, ChangeType::Update);
+    assert_eq!(changes[0].replace_order, None);
+
+    // Without force-new attributes every change is an update.
+    let changes = Planner::new().plan(&[client("app-v2", "app")], &[client("app", "app")]);
+    assert_eq!(changes[0].change_type, ChangeType::Update);
+}
+
+#[test]
+fn test_replace_order_serialization() {
+    let change: Change = serde_json::from_value(json!({
+        "address": { "resource_type": "client", "name": "app", "namespace": null },
+        "change_type": "Replace",
+        "before": null,
+        "after": null,
+        "replace_order": "create_before_destroy"
+    }))
+    .unwrap();
+    assert_eq!(change.change_type, ChangeType::Replace);
+    assert_eq!(
+        change.replace_order,
+        Some(ReplaceOrder::CreateBeforeDestroy)
+    );
+}
+
+#[tokio::test]
+async fn test_builder_plans_and_applies_replacements() {
+    let handler = ClientHandler::default();
+    let provider = provider(handler.clone());
+
+    let plan = provider
+        .plan(PlanRequest {
+            workspace_path: ".".to_string(),
+            desired_state: vec![client("app-v2", "app")],
+            current_state: vec![client("app", "app")],
+            data_sources: vec![],
+        })
+        .await
+        .unwrap();
+    assert_eq!(plan.changes[0].change_type, ChangeType::Replace);
+    assert!(plan.diagnostics.is_empty());
+
+    let mut create_first = plan.changes[0].clone();
+    create_first.replace_order = Some(ReplaceOrder::CreateBeforeDestroy);
+    let changes = vec![plan.changes[0].clone(), create_first];
+
+    let applied = provider.apply(ApplyRequest { changes }).await.unwrap();
+    assert_eq!(applied.successful_addresses.len(), 2);
+    assert_eq!(
+        applied.results[0].resource.as_ref().unwrap().spec["client_id"],
+        json!("app-v2")
+    );
+    assert_eq!(
+        *handler.log.lock().unwrap(),
+        vec!["delete app", "create app-v2", "create app-v2", "delete app"]
+    );
+}
+
+#[tokio::test]
+async fn test_partially_applied_replacements_are_reported() {
+    let replace = |order| Change {
+        address: client("app", "app").address,
+        change_type: ChangeType::Replace,
+        before: Some(client("app", "app")),
+        after: Some(client("app-v2", "app")),
+        computed_fields: vec![],
+        replace_order: Some(order),
+        attribute_changes: vec![],
+        patch: None,
+    };
+    let mut state = State::default();
+    let key = client("app", "app").address.to_string();
+
+    // The replacement exists but the old client could not be deleted: the new object
+    // is returned so it is tracked.
+    let handler = ClientHandler {
+        fail: Some("delete"),
+        ..Default::default()
+    };
+    let changes = vec![replace(ReplaceOrder::CreateBeforeDestroy)];
+    let applied = provider(handler.clone())
+        .apply(ApplyRequest { changes })
+        .await
+        .unwrap();
+    let result = &applied.results[0];
+    assert_eq!(result.status, ApplyStatus::Failed);
+    assert_eq!(
+        result.resource.as_ref().unwrap().spec["client_id"],
+        json!("app-v2")
+    );
+    assert!(!result.deleted);
+    assert!(result
+        .error
+        .as_ref()
+        .unwrap()
+        .message
+        .contains("failed to delete the previous object: client is in use"));
+    assert!(applied.successful_addresses.is_empty());
+    applied.update_state(&mut state);
+    assert_eq!(state.resources[&key].spec["client_id"], json!("app-v2"));
+
+    // The old client is gone but its replacement could not be created.
+    let handler = ClientHandler {
+        fail: Some("create"),
+        ..Default::default()
+    };
+    let changes = vec![replace(ReplaceOrder::DestroyBeforeCreate)];
+    let applied = provider(handler.clone())
+        .apply(ApplyRequest { changes })
+        .await
+        .unwrap();
+    let result = &applied.results[0];
+    assert_eq!(result.status, ApplyStatus::Failed);
+    assert!(result.deleted);
+    assert!(result.resource.is_none());
+    assert_eq!(*handler.log.lock().unwrap(), vec!["delete app"]);
+    applied.update_state(&mut state);
+    assert!(!state.resources.contains_key(&key));
+}
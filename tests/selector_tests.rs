@@ -0,0 +1,187 @@
+mod common;
+
+use common::resource;
+use iamctl_rust_sdk::plan::{AddressPattern, Planner, ResourceSelector};
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::state::State;
+use iamctl_rust_sdk::utils::Error;
+use serde_json::json;
+
+fn address(input: &str) -> ResourceAddress {
+    input.parse().unwrap()
+}
+
+fn pattern(input: &str) -> AddressPattern {
+    input.parse().unwrap()
+}
+
+#[test]
+fn test_pattern_globs_each_component() {
+    let clients = pattern("client.demo/*");
+    assert!(clients.matches(&address("client.demo/my-app")));
+    assert!(!clients.matches(&address("client.other/my-app")));
+    assert!(!clients.matches(&address("client.my-app")));
+
+    // Without a namespace, any namespace (or none) matches.
+    let users = pattern("user.*");
+    assert!(users.matches(&address("user.alice")));
+    assert!(users.matches(&address("user.demo/alice")));
+    assert!(!users.matches(&address("group.admins")));
+
+    let admins = pattern("*.prod/admin-?");
+    assert!(admins.matches(&address("user.prod/admin-1")));
+    assert!(admins.matches(&address("group.prod/admin-x")));
+    assert!(!admins.matches(&address("user.prod/admin-10")));
+    assert!(!admins.matches(&address("user.prod/admin-")));
+
+    assert!(pattern("user.a*c*e").matches(&address("user.alice")));
+    assert!(!pattern("user.a*c*e").matches(&address("user.alicia")));
+}
+
+#[test]
+fn test_pattern_escapes_and_errors() {
+    let literal = pattern(r"user.a\*b");
+    assert!(literal.matches(&address("user.a*b")));
+    assert!(!literal.matches(&address("user.axb")));
+    assert_eq!(literal.to_string(), r"user.a\*b");
+
+    let dotted = pattern(r"a\.b.team\/x/*");
+    assert!(dotted.matches(&address(r"a\.b.team\/x/alice")));
+    assert_eq!(dotted.to_string(), r"a\.b.team\/x/*");
+
+    // Exact patterns round-trip through their string form.
+    let exact = AddressPattern::exact(&address(r"user.demo/a\/b"));
+    assert_eq!(exact.to_string(), r"user.demo/a\/b");
+    assert_eq!(pattern(&exact.to_string()), exact);
+    assert!(!exact.matches(&address("user.a/b")));
+
+    // An exact pattern for an address without a namespace only matches that address.
+    let exact = AddressPattern::exact(&address("user.alice"));
+    assert_eq!(exact.to_string(), "user./alice");
+    assert_eq!(pattern(&exact.to_string()), exact);
+    assert_eq!(
+        serde_json::from_value::<AddressPattern>(serde_json::to_value(&exact).unwrap()).unwrap(),
+        exact
+    );
+    assert!(exact.matches(&address("user.alice")));
+    assert!(!exact.matches(&address("user.demo/alice")));
+    assert!(pattern("user./*").matches(&address("user.bob")));
+    assert!(!pattern("user./*").matches(&address("user.demo/bob")));
+
+    for input in [
+        "user",
+        "user.",
+        ".alice",
+        "user.a/b/c",
+        "user./",
+        r"user.a\x",
+        "user.a\\",
+    ] {
+        assert!(
+            matches!(
+                input.parse::<AddressPattern>(),
+                Err(Error::InvalidAddress { .. })
+            ),
+            "{input} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn test_selector_includes_and_excludes() {
+    assert!(ResourceSelector::new().is_empty());
+    assert!(ResourceSelector::new().matches(&address("user.alice")));
+
+    let selector = ResourceSelector::new()
+        .include(pattern("user.*"))
+        .include(pattern("client.demo/*"))
+        .exclude(pattern("user.root"));
+    assert!(!selector.is_empty());
+    assert!(selector.matches(&address("user.alice")));
+    assert!(selector.matches(&address("client.demo/my-app")));
+    assert!(!selector.matches(&address("user.root")));
+    assert!(!selector.matches(&address("group.admins")));
+
+    let everything_but_roots = ResourceSelector::new().exclude(pattern("*.root"));
+    assert!(everything_but_roots.matches(&address("group.admins")));
+    assert!(!everything_but_roots.matches(&address("user.root")));
+
+    let json = serde_json::to_value(&selector).unwrap();
+    assert_eq!(
+        json,
+        json!({
+            "include": ["user.*", "client.demo/*"],
+            "exclude": ["user.root"],
+        })
+    );
+    let parsed: ResourceSelector =
+        serde_json::from_value(json!({ "exclude": ["user.root"] })).unwrap();
+    assert_eq!(
+        parsed,
+        ResourceSelector::new().exclude(pattern("user.root"))
+    );
+    assert!(serde_json::from_value::<ResourceSelector>(json!({ "include": ["user"] })).is_err());
+}
+
+#[test]
+fn test_filtered_plan_leaves_unselected_resources_alone() {
+    let request = PlanRequest {
+        workspace_path: ".".to_string(),
+        desired_state: vec![
+            resource("user.alice", json!({ "email": "alice@new" })),
+            resource("group.admins", json!({ "members": ["alice"] })),
+        ],
+        current_state: vec![
+            resource("user.alice", json!({ "email": "alice@old" })),
+            resource("user.bob", json!({})),
+            resource("group.devs", json!({})),
+        ],
+        data_sources: vec![],
+    };
+
+    let selector = ResourceSelector::new().include(pattern("user.*"));
+    let request = selector.filter_plan_request(request);
+    assert_eq!(request.desired_state.len(), 1);
+    assert_eq!(request.current_state.len(), 2);
+
+    let changes = Planner::new().plan(&request.desired_state, &request.current_state);
+    let planned: Vec<_> = changes
+        .iter()
+        .map(|c| (c.address.to_string(), c.change_type.clone()))
+        .collect();
+    assert_eq!(
+        planned,
+        vec![
+            ("user.alice".to_string(), ChangeType::Update),
+            ("user.bob".to_string(), ChangeType::Delete),
+        ]
+    );
+
+    let without_bob = ResourceSelector::new().exclude(pattern("user.bob"));
+    let kept = without_bob.filter_changes(changes);
+    assert_eq!(kept.len(), 1);
+    assert_eq!(kept[0].address, address("user.alice"));
+}
+
+#[test]
+fn test_filter_state() {
+    let mut state = State::default();
+    let _ = state
+        .metadata
+        .insert("workspace".to_string(), "demo".to_string());
+    for input in ["client.demo/a", "client.demo/b", "client.prod/a"] {
+        let _ = state
+            .resources
+            .insert(input.to_string(), resource(input, json!({})));
+    }
+
+    let filtered = ResourceSelector::new()
+        .include(pattern("client.demo/*"))
+        .filter_state(&state);
+    let mut keys: Vec<_> = filtered.resources.keys().cloned().collect();
+    keys.sort();
+    assert_eq!(keys, vec!["client.demo/a", "client.demo/b"]);
+    assert_eq!(filtered.version, state.version);
+    assert_eq!(filtered.metadata, state.metadata);
+    assert_eq!(state.resources.len(), 3);
+}
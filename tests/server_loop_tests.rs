@@ -1,6 +1,9 @@
 use async_trait::async_trait;
 use iamctl_rust_sdk::prelude::*;
-use iamctl_rust_sdk::provider::{ImportRequest, ImportResponse, ValidateRequest, ValidateResponse};
+use iamctl_rust_sdk::provider::{
+    ImportRequest, ImportResponse, RefreshRequest, RefreshResponse, RefreshedResource,
+    ValidateRequest, ValidateResponse,
+};
 use iamctl_rust_sdk::server::{JsonRpcRequest, RequestHandler};
 use serde_json::json;
 use std::sync::Arc;
@@ -23,21 +26,30 @@ impl Provider for MockProvider {
             supported_resources: vec!["test".to_string()],
             can_import: true,
             can_validate: true,
+            can_refresh: true,
+            config_schema: None,
+            can_discover: false,
+            resource_types: vec![],
+            data_sources: vec![],
         }
     }
     async fn plan(&self, _: PlanRequest) -> anyhow::Result<PlanResponse> {
-        Ok(PlanResponse { changes: vec![] })
+        Ok(PlanResponse {
+            changes: vec![],
+            diagnostics: vec![],
+        })
     }
     async fn apply(&self, _: ApplyRequest) -> anyhow::Result<ApplyResponse> {
         Ok(ApplyResponse {
             successful_addresses: vec![],
-            failed_addresses: vec![],
+            diagnostics: vec![],
+            results: vec![],
         })
     }
     async fn validate(&self, _: ValidateRequest) -> anyhow::Result<ValidateResponse> {
         Ok(ValidateResponse {
             valid: true,
-            errors: vec![],
+            diagnostics: vec![],
         })
     }
     async fn import(&self, _: ImportRequest) -> anyhow::Result<ImportResponse> {
@@ -53,6 +65,23 @@ impl Provider for MockProvider {
             },
         })
     }
+    async fn refresh(&self, request: RefreshRequest) -> anyhow::Result<RefreshResponse> {
+        // Anything named "deleted" has disappeared remotely.
+        let resources = request
+            .resources
+            .into_iter()
+            .map(|resource| {
+                if resource.address.name == "deleted" {
+                    RefreshedResource::Gone {
+                        address: resource.address,
+                    }
+                } else {
+                    RefreshedResource::Present { resource }
+                }
+            })
+            .collect();
+        Ok(RefreshResponse { resources })
+    }
 }
 
 #[tokio::test]
@@ -143,3 +172,44 @@ async fn test_handler_invalid_params() {
     assert!(resp.error.is_some());
     assert_eq!(resp.error.unwrap().code, -32602);
 }
+
+#[tokio::test]
+async fn test_handler_refresh_reports_gone_resources() {
+    let handler = RequestHandler::new(Arc::new(MockProvider));
+
+    let resp = handler
+        .handle(JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "refresh".to_string(),
+            params: json!({
+                "resources": [
+                    {
+                        "address": { "resource_type": "test", "name": "kept", "namespace": null },
+                        "spec": { "name": "kept" }
+                    },
+                    {
+                        "address": { "resource_type": "test", "name": "deleted", "namespace": null },
+                        "spec": { "name": "deleted" }
+                    }
+                ]
+            }),
+            id: Some(json!(1)),
+        })
+        .await;
+
+    let result: RefreshResponse = serde_json::from_value(resp.result.unwrap()).unwrap();
+    assert_eq!(result.resources.len(), 2);
+    assert!(matches!(
+        result.resources[0],
+        RefreshedResource::Present { .. }
+    ));
+    assert!(matches!(
+        result.resources[1],
+        RefreshedResource::Gone { .. }
+    ));
+    assert_eq!(result.resources[1].address().name, "deleted");
+
+    let current = result.into_current_state();
+    assert_eq!(current.len(), 1);
+    assert_eq!(current[0].address.name, "kept");
+}
@@ -58,6 +58,9 @@ fn test_change_noop() {
         before: None,
         after: None,
         computed_fields: vec![],
+        replace_order: None,
+        attribute_changes: vec![],
+        patch: None,
     };
 
     assert_eq!(change.change_type, ChangeType::NoOp);
@@ -81,6 +84,9 @@ fn test_change_serialization() {
             metadata: HashMap::new(),
         }),
         computed_fields: vec!["id".to_string()],
+        replace_order: None,
+        attribute_changes: vec![],
+        patch: None,
     };
 
     let serialized = serde_json::to_value(&change).unwrap();
@@ -0,0 +1,203 @@
+mod common;
+
+use async_trait::async_trait;
+use common::resource;
+use iamctl_rust_sdk::plan::{render_plan, Differ, Planner};
+use iamctl_rust_sdk::prelude::*;
+use iamctl_rust_sdk::server::{JsonRpcRequest, RequestHandler};
+use iamctl_rust_sdk::types::unknown::{
+    is_unknown, unknown, unknown_paths, unresolved_unknowns, UNRESOLVED_UNKNOWN_CODE,
+};
+use iamctl_rust_sdk::types::{Diagnostic, MaybeUnknown};
+use iamctl_rust_sdk::validation::{JsonSchemaValidator, SchemaValidator};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct MembershipSpec {
+    group_id: MaybeUnknown<String>,
+    user: String,
+}
+
+#[test]
+fn test_maybe_unknown_round_trips() {
+    let pending = MembershipSpec {
+        group_id: MaybeUnknown::Unknown,
+        user: "alice".to_string(),
+    };
+    let value = serde_json::to_value(&pending).unwrap();
+    assert_eq!(value["group_id"], unknown());
+    assert!(is_unknown(&value["group_id"]));
+    assert_eq!(
+        serde_json::from_value::<MembershipSpec>(value).unwrap(),
+        pending
+    );
+
+    let known: MembershipSpec =
+        serde_json::from_value(json!({ "group_id": "g-1", "user": "alice" })).unwrap();
+    assert_eq!(known.group_id.as_known().map(String::as_str), Some("g-1"));
+    assert!(!is_unknown(&json!({ "$iamctl_unknown": true, "other": 1 })));
+}
+
+#[test]
+fn test_unknown_values_diff_and_render() {
+    let current = vec![resource(
+        "membership.alice",
+        json!({ "group_id": "g-1", "user": "alice", "tags": ["a"] }),
+    )];
+    let desired = vec![
+        resource(
+            "membership.alice",
+            json!({ "group_id": unknown(), "user": "alice", "tags": [unknown(), unknown()] }),
+        ),
+        resource(
+            "group.ops",
+            json!({ "name": "ops", "owner": { "id": unknown() } }),
+        ),
+    ];
+
+    let differ = Differ::new()
+        .set(&["/tags".to_string()])
+        .force_new(&["/group_id".to_string()]);
+    let changes = Planner::new()
+        .differ("membership", differ)
+        .plan(&desired, &current);
+    let membership = &changes[0];
+    assert_eq!(membership.change_type, ChangeType::Replace);
+    let paths: Vec<_> = membership
+        .attribute_changes
+        .iter()
+        .map(|c| c.path.as_str())
+        .collect();
+    assert_eq!(paths, ["/group_id", "/tags"]);
+    assert_eq!(
+        membership.attribute_changes[1].after,
+        Some(json!([unknown(), unknown()]))
+    );
+
+    // Two unknowns are considered equal; an unknown never equals a known value.
+    let after = Differ::new().diff(Some(&desired[1]), Some(&desired[1]));
+    assert!(after.is_empty());
+
+    let rendered = render_plan(&changes);
+    assert!(rendered.contains("group_id = \"g-1\" -> (known after apply) # forces replacement"));
+    assert!(rendered.contains("tags     = [\"a\"] -> [(known after apply), (known after apply)]"));
+    assert!(rendered.contains("id = (known after apply)"), "{rendered}");
+    assert!(!rendered.contains("$iamctl_unknown"));
+}
+
+#[test]
+fn test_unresolved_unknowns_are_reported() {
+    let spec = resource(
+        "group.ops",
+        json!({ "name": "ops", "owner": { "id": unknown() }, "members": ["a", unknown()] }),
+    );
+    assert_eq!(unknown_paths(&spec.spec), ["/members/1", "/owner/id"]);
+
+    let changes = Planner::new().plan(std::slice::from_ref(&spec), &[]);
+    let diagnostics = unresolved_unknowns(&changes);
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].attribute.as_deref(), Some("spec/members/1"));
+    assert_eq!(diagnostics[1].address.as_ref(), Some(&spec.address));
+    assert_eq!(
+        diagnostics[1].code.as_deref(),
+        Some(UNRESOLVED_UNKNOWN_CODE)
+    );
+
+    // Deleting a resource never needs its values.
+    let deletes = Planner::new().plan(&[], &[spec]);
+    assert!(unresolved_unknowns(&deletes).is_empty());
+}
+
+#[test]
+fn test_schema_validation_accepts_unknown_values() {
+    let mut validator = JsonSchemaValidator::new();
+    validator.add_schema(
+        "group",
+        json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string", "minLength": 1 },
+                "owner": {
+                    "type": "object",
+                    "properties": { "id": { "type": "string" } },
+                    "required": ["id"],
+                },
+            },
+            "required": ["name", "owner"],
+        }),
+    );
+
+    let pending = resource(
+        "group.ops",
+        json!({ "name": unknown(), "owner": { "id": unknown() } }),
+    );
+    assert!(validator.validate(&pending).unwrap().valid);
+
+    let invalid = resource(
+        "group.ops",
+        json!({ "name": unknown(), "owner": { "id": 42 } }),
+    );
+    assert!(!validator.validate(&invalid).unwrap().valid);
+}
+
+struct RecordingProvider;
+
+#[async_trait]
+impl Provider for RecordingProvider {
+    fn metadata(&self) -> ProviderMetadata {
+        ProviderMetadata {
+            name: "groups".to_string(),
+            version: "0.1.0".to_string(),
+            author: None,
+            repository: None,
+            description: None,
+        }
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            supported_resources: vec!["group".to_string()],
+            can_import: false,
+            can_validate: false,
+            can_refresh: false,
+            config_schema: None,
+            can_discover: false,
+            resource_types: vec![],
+            data_sources: vec![],
+        }
+    }
+
+    async fn plan(&self, _request: PlanRequest) -> anyhow::Result<PlanResponse> {
+        Ok(PlanResponse {
+            changes: vec![],
+            diagnostics: vec![],
+        })
+    }
+
+    async fn apply(&self, _request: ApplyRequest) -> anyhow::Result<ApplyResponse> {
+        panic!("unknown values must not reach the provider");
+    }
+}
+
+#[tokio::test]
+async fn test_apply_rejects_unresolved_unknowns() {
+    let group = resource("group.ops", json!({ "owner": { "id": unknown() } }));
+    let changes = Planner::new().plan(&[group], &[]);
+    let response = RequestHandler::new(Arc::new(RecordingProvider))
+        .handle(JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "apply".to_string(),
+            params: serde_json::to_value(ApplyRequest { changes }).unwrap(),
+            id: Some(json!(1)),
+        })
+        .await;
+
+    let error = response.error.unwrap();
+    assert_eq!(error.code, -32602);
+    assert!(error.message.contains("still unknown"), "{}", error.message);
+    let diagnostics: Vec<Diagnostic> =
+        serde_json::from_value(error.data.unwrap()["diagnostics"].clone()).unwrap();
+    assert_eq!(diagnostics[0].attribute.as_deref(), Some("spec/owner/id"));
+}